@@ -0,0 +1,15 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rouge_haddock_bevy_lib::map_gen::get_cell_map_sized;
+
+fn map_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_generation");
+    for size in [20, 30, 50] {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| get_cell_map_sized((size, size), 50, 50))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, map_generation);
+criterion_main!(benches);
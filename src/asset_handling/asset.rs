@@ -26,6 +26,13 @@ pub enum ImageAsset {
     UiHealthEmpty,
     UiPowerFull,
     UiPowerEmpty,
+    UiShield,
+    UiStarfish,
+    UiLowHealthVignette,
+    IntentionArrow,
+    CrabShell,
+    DepositChest,
+    SonarRing,
 }
 
 impl ImageAsset {
@@ -44,8 +51,13 @@ pub enum AudioAsset {
     Chomp,
     FishSlap,
     Death,
+    LastStand,
+    EmptyClick,
+    TurnTick,
     Pickup,
     VortexSpawn,
+    MenuMusic,
+    GameMusic,
 }
 
 impl AssetClass for AudioAsset {
@@ -56,10 +68,16 @@ impl AssetClass for AudioAsset {
             Self::Chomp => "audio/carrotnom.wav",
             Self::FishSlap => "audio/fish_slap.ogg",
             Self::Death => "audio/398068__happyparakeet__pixel-death.wav",
+            Self::LastStand => "audio/450830__kyles__fish-slap-ground-or-snow-writhing-wet.wav",
+            Self::EmptyClick => "audio/button_press2.ogg",
+            // Placeholder until the turn tick has a sound of its own
+            Self::TurnTick => "audio/button_hover2.ogg",
 
             Self::VortexSpawn | Self::Pickup => {
                 "audio/608431__plasterbrain__shiny-coin-pickup.flac"
             }
+            Self::MenuMusic => "audio/menu_music.wav",
+            Self::GameMusic => "audio/game_music.wav",
         }
     }
 }
@@ -86,6 +104,13 @@ impl AssetClass for ImageAsset {
             Self::UiHealthEmpty => "ui/heart_3.png",
             Self::UiPowerFull => "ui/power_bolt_1.png",
             Self::UiPowerEmpty => "ui/power_bolt_2.png",
+            Self::UiShield => "ui/shield.png",
+            Self::UiStarfish => "ui/starfish.png",
+            Self::UiLowHealthVignette => "ui/vignette.png",
+            Self::IntentionArrow => "sprites/intention_arrow.png",
+            Self::CrabShell => "sprites/crab_shell.png",
+            Self::DepositChest => "sprites/deposit_chest.png",
+            Self::SonarRing => "sprites/sonar_ring.png",
         }
     }
 }
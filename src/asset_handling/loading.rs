@@ -1,5 +1,6 @@
 use super::asset::{AssetClass, ImageAsset, TextureAtlasAsset};
 use crate::asset_handling::asset::AudioAsset;
+use crate::menu_core::menu_core::text::UiFont;
 use crate::CoreState;
 use bevy::asset::LoadState;
 use bevy::prelude::*;
@@ -45,6 +46,10 @@ impl Plugin for LoadingPlugin {
             .add_system_set(SystemSet::on_enter(state).with_system(load_all))
             .add_system_set(SystemSet::on_update(state).with_system(loading_watcher))
             .add_system_set(SystemSet::on_exit(state).with_system(finalise));
+        // Assets are only watched for changes in debug builds, see [crate::main]
+        if cfg!(debug_assertions) {
+            app.add_system(hot_reload_system);
+        }
     }
 }
 
@@ -92,16 +97,39 @@ fn load_texture_atlases(
     TextureAtlasStore(atlas_handles)
 }
 
+/// Shown in place of the main menu when any asset failed to load, as the game can't run without
+/// them
+#[derive(Component)]
+struct MissingAssetsScreen;
+
 fn loading_watcher(
+    mut commands: Commands,
     asset_server: Res<AssetServer>,
     image_handles: Res<ImageAssetStore>,
+    audio_handles: Res<AudioAssetStore>,
     mut state: ResMut<State<CoreState>>,
     mut print_timer: ResMut<LoadingPrintTimer>,
     time: Res<Time>,
+    ui_font: Res<UiFont>,
+    missing_assets_screen_query: Query<Entity, With<MissingAssetsScreen>>,
 ) {
+    if !missing_assets_screen_query.is_empty() {
+        return;
+    }
     let mut count = LoadStateCount::default();
-    for handle in image_handles.0.values() {
+    let mut failed = Vec::new();
+    for (asset, handle) in image_handles.0.iter() {
         let load_state = asset_server.get_load_state(handle);
+        if load_state == LoadState::Failed {
+            failed.push(asset.to_filename().to_string());
+        }
+        count.incr(&load_state);
+    }
+    for (asset, handle) in audio_handles.0.iter() {
+        let load_state = asset_server.get_load_state(handle);
+        if load_state == LoadState::Failed {
+            failed.push(asset.to_filename().to_string());
+        }
         count.incr(&load_state);
     }
     print_timer.0.tick(time.delta());
@@ -110,18 +138,58 @@ fn loading_watcher(
     }
 
     if count.all_finished() {
-        info!("Finished Loading: {:?}", count);
-        state.set(CoreState::MainMenu).unwrap();
+        if count.failed > 0 {
+            failed.sort();
+            error!(
+                "Failed to load {} assets, check they exist under assets/: {}",
+                count.failed,
+                failed.join(", ")
+            );
+            spawn_missing_assets_screen(&mut commands, &ui_font, &failed);
+        } else {
+            info!("Finished Loading: {:?}", count);
+            state.set(CoreState::MainMenu).unwrap();
+        }
     }
 }
 
+fn spawn_missing_assets_screen(commands: &mut Commands, ui_font: &UiFont, failed: &[String]) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: UiColor(Color::BLACK),
+            ..Default::default()
+        })
+        .insert(MissingAssetsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Missing assets:",
+                ui_font.text_style(40.0),
+            ));
+            for filename in failed {
+                parent.spawn_bundle(TextBundle::from_section(
+                    filename.clone(),
+                    ui_font.text_style(20.0),
+                ));
+            }
+        });
+}
+
+const TILEMAP_TEXTURE_USAGES: TextureUsages = TextureUsages::TEXTURE_BINDING
+    .union(TextureUsages::COPY_SRC)
+    .union(TextureUsages::COPY_DST);
+
 fn finalise(mut textures: ResMut<Assets<Image>>, image_handles: Res<ImageAssetStore>) {
     for (image_asset, image_handle) in image_handles.0.iter() {
         if image_asset.is_for_tilemap() {
             if let Some(mut texture) = textures.get_mut(image_handle) {
-                texture.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::COPY_DST;
+                texture.texture_descriptor.usage = TILEMAP_TEXTURE_USAGES;
             } else {
                 warn!("Did not get image from images, but thought we were all loaded!");
             }
@@ -129,6 +197,54 @@ fn finalise(mut textures: ResMut<Assets<Image>>, image_handles: Res<ImageAssetSt
     }
 }
 
+/// Picks up images changed on disk while running: re-cuts the texture atlases from them, in
+/// place so everything holding an atlas handle sees the change, and re-applies the tilemap's
+/// texture usages, which a reloaded image comes back without
+fn hot_reload_system(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    image_asset_store: Option<Res<ImageAssetStore>>,
+    texture_atlas_store: Option<Res<TextureAtlasStore>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut textures: ResMut<Assets<Image>>,
+) {
+    let (image_asset_store, texture_atlas_store) = match (image_asset_store, texture_atlas_store) {
+        (Some(image_asset_store), Some(texture_atlas_store)) => {
+            (image_asset_store, texture_atlas_store)
+        }
+        _ => return,
+    };
+    for event in image_events.iter() {
+        let modified = match event {
+            AssetEvent::Modified { handle } => handle,
+            AssetEvent::Created { .. } | AssetEvent::Removed { .. } => continue,
+        };
+        for asset in TextureAtlasAsset::iter() {
+            let image_handle = image_asset_store.get(&asset.to_image_asset());
+            if image_handle == *modified {
+                info!("Reloading texture atlas {:?}", asset);
+                let atlas = TextureAtlas::from_grid(
+                    image_handle,
+                    asset.frame_size(),
+                    asset.columns(),
+                    asset.rows(),
+                );
+                texture_atlases.set_untracked(texture_atlas_store.get(&asset), atlas);
+            }
+        }
+        for (image_asset, image_handle) in image_asset_store.0.iter() {
+            // Checked first as setting them modifies the image again, which would loop forever
+            let needs_usages = textures.get(image_handle).map_or(false, |texture| {
+                texture.texture_descriptor.usage != TILEMAP_TEXTURE_USAGES
+            });
+            if image_asset.is_for_tilemap() && image_handle == modified && needs_usages {
+                if let Some(mut texture) = textures.get_mut(image_handle) {
+                    texture.texture_descriptor.usage = TILEMAP_TEXTURE_USAGES;
+                }
+            }
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 struct LoadStateCount {
     not_loaded: usize,
@@ -144,7 +260,7 @@ impl LoadStateCount {
             LoadState::NotLoaded => self.not_loaded += 1,
             LoadState::Loading => self.loading += 1,
             LoadState::Loaded => self.loaded += 1,
-            LoadState::Failed => self.loaded += 1,
+            LoadState::Failed => self.failed += 1,
             LoadState::Unloaded => self.unloaded += 1,
         }
     }
@@ -153,3 +269,24 @@ impl LoadStateCount {
         self.not_loaded == 0 && self.loading == 0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::LoadStateCount;
+    use bevy::asset::LoadState;
+
+    #[test]
+    fn test_load_state_count() {
+        let mut count = LoadStateCount::default();
+        count.incr(&LoadState::Loaded);
+        count.incr(&LoadState::Loading);
+        assert!(!count.all_finished());
+
+        let mut count = LoadStateCount::default();
+        count.incr(&LoadState::Loaded);
+        count.incr(&LoadState::Failed);
+        assert!(count.all_finished());
+        assert_eq!(1, count.loaded);
+        assert_eq!(1, count.failed);
+    }
+}
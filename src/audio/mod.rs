@@ -0,0 +1,4 @@
+mod music;
+
+pub use music::MusicPlugin as Plugin;
+pub use music::{AudioSettings, MusicController};
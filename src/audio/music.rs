@@ -0,0 +1,136 @@
+use crate::asset_handling::asset::AudioAsset;
+use crate::asset_handling::AudioAssetStore;
+use crate::profiles::settings::Settings;
+use crate::CoreState;
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use std::time::Duration;
+
+pub struct MusicPlugin;
+
+/// Music plays on its own channel so its volume can be set, and its loop stopped, without
+/// touching sound effects on the main channel
+pub struct MusicChannel;
+
+const CROSSFADE_DURATION: Duration = Duration::from_millis(800);
+
+/// Volume levels, each in the range 0.0..=1.0. `volume` scales everything, `music_volume` scales
+/// the music on top of that. Kept in step with [Settings::volume] and [Settings::music_volume]
+pub struct AudioSettings {
+    pub volume: f64,
+    pub music_volume: f64,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            music_volume: 0.5,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn music_output_volume(&self) -> f64 {
+        self.volume * self.music_volume
+    }
+}
+
+/// Tracks the currently looping music track so it can be stopped when the state calls for a
+/// different one
+#[derive(Default)]
+pub struct MusicController {
+    current: Option<(AudioAsset, Handle<AudioInstance>)>,
+}
+
+impl MusicController {
+    pub fn current_track(&self) -> Option<AudioAsset> {
+        self.current.as_ref().map(|(track, _)| *track)
+    }
+}
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_channel::<MusicChannel>()
+            .init_resource::<AudioSettings>()
+            .init_resource::<MusicController>()
+            .add_system(music_state_system)
+            .add_system(audio_settings_sync_system.before(volume_system))
+            .add_system(volume_system);
+    }
+}
+
+fn track_for_state(state: &CoreState) -> Option<AudioAsset> {
+    match state {
+        CoreState::Loading => None,
+        CoreState::MainMenu
+        | CoreState::LoadMenu
+        | CoreState::NewGameMenu
+        | CoreState::GameHub
+        | CoreState::GameStore
+        // The demo stands in for the idle main menu, so keeps its music
+        | CoreState::Demo => Some(AudioAsset::MenuMusic),
+        CoreState::GameLevel | CoreState::GameLevelTransition | CoreState::GameOverlay => {
+            Some(AudioAsset::GameMusic)
+        }
+    }
+}
+
+/// On any state change, fades out the playing loop and fades in the one for the new state. States
+/// sharing a track (e.g. moving between menus) leave the current loop untouched
+fn music_state_system(
+    state: Res<State<CoreState>>,
+    mut music_controller: ResMut<MusicController>,
+    music_channel: Res<AudioChannel<MusicChannel>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    audio_asset_store: Option<Res<AudioAssetStore>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let wanted = track_for_state(state.current());
+    if wanted == music_controller.current_track() {
+        return;
+    }
+
+    if let Some((track, instance_handle)) = music_controller.current.take() {
+        debug!("Stopping music {:?}", track);
+        if let Some(instance) = audio_instances.get_mut(&instance_handle) {
+            instance.stop(AudioTween::linear(CROSSFADE_DURATION));
+        }
+    }
+
+    if let (Some(track), Some(audio_asset_store)) = (wanted, audio_asset_store) {
+        debug!("Starting music {:?}", track);
+        let instance_handle = music_channel
+            .play(audio_asset_store.get(&track))
+            .looped()
+            .fade_in(AudioTween::linear(CROSSFADE_DURATION))
+            .handle();
+        music_controller.current = Some((track, instance_handle));
+    }
+}
+
+fn audio_settings_sync_system(settings: Res<Settings>, mut audio_settings: ResMut<AudioSettings>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (volume, music_volume) = (settings.volume as f64, settings.music_volume as f64);
+    if audio_settings.volume != volume || audio_settings.music_volume != music_volume {
+        audio_settings.volume = volume;
+        audio_settings.music_volume = music_volume;
+    }
+}
+
+/// Sets each channel's volume, which covers everything played on it, so sounds are played without
+/// a volume of their own
+fn volume_system(
+    settings: Res<AudioSettings>,
+    audio: Res<Audio>,
+    music_channel: Res<AudioChannel<MusicChannel>>,
+) {
+    if settings.is_changed() {
+        audio.set_volume(settings.volume);
+        music_channel.set_volume(settings.music_output_volume());
+    }
+}
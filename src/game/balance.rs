@@ -0,0 +1,252 @@
+//! Gameplay numbers that are worth tuning without a recompile, read from [BALANCE_PATH] at
+//! startup and kept as a resource. Anything missing from the file keeps its default, so the file
+//! only needs what's being changed. A file with values out of range is rejected as a whole
+
+use log::error;
+use serde::{Deserialize, Serialize};
+
+const BALANCE_PATH: &str = "assets/balance.ron";
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    pub vortex: VortexBalance,
+    pub jellyfish: JellyfishBalance,
+    pub projectile: ProjectileBalance,
+    pub sudden_death: SuddenDeathBalance,
+    pub enemy_phase: EnemyPhaseBalance,
+    pub sonar: SonarBalance,
+    pub aggro: AggroBalance,
+    pub level_transition: LevelTransitionBalance,
+}
+
+/// When the vortex spawns, see [super::end_game::vortex_spawn_status]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct VortexBalance {
+    /// Turn after which the vortex spawns regardless of enemies
+    pub late_spawn_turn: usize,
+    /// Enemy count below which the vortex spawns regardless of turn
+    pub late_spawn_enemy_count: usize,
+}
+
+impl Default for VortexBalance {
+    fn default() -> Self {
+        Self {
+            late_spawn_turn: 34,
+            late_spawn_enemy_count: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct JellyfishBalance {
+    /// Chance each turn a ready jellyfish starts charging its lightning
+    pub charge_chance: f64,
+    /// Turns a jellyfish waits after firing before it can charge again
+    pub recharge_turns: usize,
+}
+
+impl Default for JellyfishBalance {
+    fn default() -> Self {
+        Self {
+            charge_chance: 0.5,
+            recharge_turns: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectileBalance {
+    /// Pixels per second the power's projectile travels
+    pub speed: f32,
+}
+
+impl Default for ProjectileBalance {
+    fn default() -> Self {
+        Self { speed: 500.0 }
+    }
+}
+
+/// When and how hard sudden death presses the player, see [super::sudden_death]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SuddenDeathBalance {
+    /// Turn from which pursuers start spawning, once the vortex is out
+    pub start_turn: usize,
+    /// Turns between each pursuer spawning
+    pub spawn_interval: usize,
+    /// Multiplier on a pursuer's weighting of moves towards the player
+    pub pursuit_weighting: f32,
+}
+
+impl Default for SuddenDeathBalance {
+    fn default() -> Self {
+        Self {
+            start_turn: 50,
+            spawn_interval: 4,
+            pursuit_weighting: 5.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EnemyPhaseBalance {
+    /// Enemies deciding their move each frame of the enemy phase, 0 for all of them in one frame.
+    /// Worth setting on levels with a great many enemies, to spread the work out
+    pub batch_size: usize,
+}
+
+/// How often the sonar pings and how far it reaches, see [super::sonar]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SonarBalance {
+    /// Turns between pings, 0 for no sonar at all
+    pub interval: usize,
+    /// Tiles from the player within which enemies show up
+    pub radius: usize,
+}
+
+impl Default for SonarBalance {
+    fn default() -> Self {
+        Self {
+            interval: 5,
+            radius: 6,
+        }
+    }
+}
+
+/// How close the player must be for enemies to give chase, see
+/// [super::movement::alerted_enemies]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AggroBalance {
+    /// Tiles from the player within which an enemy spots it, 0 to have every enemy always chasing
+    pub radius: usize,
+    /// Tiles from an enemy that's spotted the player within which others are alerted too
+    pub alert_radius: usize,
+}
+
+impl Default for AggroBalance {
+    fn default() -> Self {
+        Self {
+            radius: 6,
+            alert_radius: 3,
+        }
+    }
+}
+
+/// The pause between levels, see [super::game::LevelTransition]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LevelTransitionBalance {
+    /// Milliseconds paused between one level and the next
+    pub duration_ms: u64,
+}
+
+impl Default for LevelTransitionBalance {
+    fn default() -> Self {
+        Self { duration_ms: 100 }
+    }
+}
+
+impl BalanceConfig {
+    /// Loads [BALANCE_PATH], falling back to defaults if it's missing, can't be read or is invalid
+    pub fn load() -> Self {
+        Self::default().reload()
+    }
+
+    /// Reads [BALANCE_PATH] again, keeping this config if the file is missing, can't be read or is
+    /// invalid
+    pub fn reload(&self) -> Self {
+        match read_config() {
+            Ok(Some(config)) => config,
+            Ok(None) => self.clone(),
+            Err(e) => {
+                error!(
+                    "Failed to load balance config, keeping the previous one: {}",
+                    e
+                );
+                self.clone()
+            }
+        }
+    }
+
+    pub fn from_ron(ron_str: &str) -> Result<Self, String> {
+        let config: Self = ron::de::from_str(ron_str).map_err(|e| e.to_string())?;
+        config.validate().map(|()| config)
+    }
+
+    /// Checks for values the game can't play with, e.g. a chance outside 0.0..=1.0 which would
+    /// panic when rolled
+    pub fn validate(&self) -> Result<(), String> {
+        let charge_chance = self.jellyfish.charge_chance;
+        if !(0.0..=1.0).contains(&charge_chance) {
+            return Err(format!(
+                "jellyfish.charge_chance must be within 0.0..=1.0, got {}",
+                charge_chance
+            ));
+        }
+        let speed = self.projectile.speed;
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(format!("projectile.speed must be above 0.0, got {}", speed));
+        }
+        let pursuit_weighting = self.sudden_death.pursuit_weighting;
+        if !(pursuit_weighting.is_finite() && pursuit_weighting >= 0.0) {
+            return Err(format!(
+                "sudden_death.pursuit_weighting must be 0.0 or above, got {}",
+                pursuit_weighting
+            ));
+        }
+        if self.sudden_death.spawn_interval == 0 {
+            return Err("sudden_death.spawn_interval must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_config() -> Result<Option<BalanceConfig>, String> {
+    match std::fs::read_to_string(BALANCE_PATH) {
+        Ok(ron_str) => BalanceConfig::from_ron(&ron_str).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// There's no file system to tune from on the web, so it always plays with the defaults
+#[cfg(target_arch = "wasm32")]
+fn read_config() -> Result<Option<BalanceConfig>, String> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::BalanceConfig;
+
+    #[test]
+    fn test_shipped_config_matches_defaults() {
+        let shipped = BalanceConfig::from_ron(include_str!("../../assets/balance.ron")).unwrap();
+        assert_eq!(BalanceConfig::default(), shipped);
+    }
+
+    #[test]
+    fn test_missing_values_use_defaults() {
+        let config = BalanceConfig::from_ron("(vortex: (late_spawn_turn: 10))").unwrap();
+        assert_eq!(10, config.vortex.late_spawn_turn);
+        assert_eq!(4, config.vortex.late_spawn_enemy_count);
+        assert_eq!(500.0, config.projectile.speed);
+    }
+
+    #[test]
+    fn test_out_of_range_values_rejected() {
+        assert!(BalanceConfig::from_ron("(jellyfish: (charge_chance: 1.5))").is_err());
+        assert!(BalanceConfig::from_ron("(jellyfish: (charge_chance: -0.1))").is_err());
+        assert!(BalanceConfig::from_ron("(projectile: (speed: 0.0))").is_err());
+        assert!(BalanceConfig::from_ron("(sudden_death: (spawn_interval: 0))").is_err());
+        assert!(BalanceConfig::from_ron("(jellyfish: (charge_chance: 1.0))").is_ok());
+    }
+}
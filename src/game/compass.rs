@@ -0,0 +1,248 @@
+//! The compass, a rare pickup that points the way out. Once the player has picked one up a
+//! [CompassArrow] circles them pointing at the nearest exit, or while there isn't one yet, at the
+//! furthest part of the map they've not been near
+
+use crate::asset_handling::asset::ImageAsset;
+use crate::asset_handling::ImageAssetStore;
+use crate::game::components::{GameOnly, Player};
+use crate::game::end_game::{EndGameHook, EndGameVortex};
+use crate::game::events::InfoEvent;
+use crate::game::tilemap::TilePosExt;
+use crate::map_gen::cell_map::CellMap;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use rand::Rng;
+use std::collections::HashSet;
+
+pub struct CompassPlugin;
+
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        let state = crate::CoreState::GameLevel;
+        app.insert_resource(ExploredCells::default())
+            .add_system_set(SystemSet::on_enter(state).with_system(explored_cells_reset))
+            .add_system_set(
+                SystemSet::on_update(state)
+                    .with_system(explored_cells_system)
+                    .with_system(compass_pickup_system)
+                    .with_system(compass_arrow_system),
+            );
+    }
+}
+
+const COMPASS_SPAWN_CHANCE: f64 = 0.1;
+const COMPASS_TINT: Color = Color::rgb(1.0, 0.85, 0.2);
+/// Cells within this many steps of the player count as explored
+const EXPLORE_RADIUS: i32 = 4;
+/// Pixels from the player's centre the arrow sits
+const ARROW_DISTANCE: f32 = 48.0;
+
+/// A compass lying on the map, waiting to be picked up
+#[derive(Debug, Component)]
+pub struct Compass;
+
+/// On the player once they've picked up a [Compass], for the rest of the level
+#[derive(Debug, Component)]
+pub struct HasCompass;
+
+#[derive(Debug, Component)]
+struct CompassArrow;
+
+/// Resource of the cells the player has been near this level
+#[derive(Debug, Default)]
+pub struct ExploredCells(HashSet<(i32, i32)>);
+
+/// Rarely spawns a [Compass], returning where it was put
+pub fn maybe_spawn_compass(
+    commands: &mut Commands,
+    image_assets: &ImageAssetStore,
+    cell_map: &CellMap<i32>,
+    exclude_positions: Option<&Vec<(i32, i32)>>,
+) -> Option<(i32, i32)> {
+    if !rand::thread_rng().gen_bool(COMPASS_SPAWN_CHANCE) {
+        return None;
+    }
+    let (x, y) = *cell_map
+        .distribute_points_by_cost(1, exclude_positions)
+        .first()?;
+    let tile_pos = TilePos {
+        x: x as u32,
+        y: y as u32,
+    };
+    let mut transform = Transform::from_translation(tile_pos.to_world_pos(9.0));
+    transform.scale = Vec3::splat(0.6);
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: image_assets.get(&ImageAsset::IntentionArrow),
+            sprite: Sprite {
+                color: COMPASS_TINT,
+                ..Default::default()
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(tile_pos)
+        .insert(GameOnly)
+        .insert(Compass);
+    Some((x, y))
+}
+
+/// The unexplored cell furthest from [from] by path, [None] once everything reachable has been
+/// explored. Ties go to the highest position so the choice is stable
+pub fn furthest_unexplored(
+    cell_map: &CellMap<i32>,
+    from: (i32, i32),
+    explored: &HashSet<(i32, i32)>,
+) -> Option<(i32, i32)> {
+    cell_map
+        .recalculate(from)
+        .0
+        .into_iter()
+        .filter(|(cell, _)| !explored.contains(cell))
+        .max_by_key(|(cell, cost)| (*cost, *cell))
+        .map(|(cell, _)| cell)
+}
+
+fn explored_cells_reset(mut explored_cells: ResMut<ExploredCells>) {
+    *explored_cells = ExploredCells::default();
+}
+
+fn explored_cells_system(
+    mut explored_cells: ResMut<ExploredCells>,
+    cell_map: Option<Res<CellMap<i32>>>,
+    player_query: Query<&TilePos, (With<Player>, Changed<TilePos>)>,
+) {
+    let (cell_map, player_pos) = match (cell_map, player_query.get_single()) {
+        (Some(cell_map), Ok(player_pos)) => (cell_map, player_pos),
+        _ => return,
+    };
+    explored_cells
+        .0
+        .extend(cell_map.cells_within(player_pos.as_i32s(), EXPLORE_RADIUS));
+}
+
+fn compass_pickup_system(
+    mut commands: Commands,
+    compass_query: Query<(Entity, &TilePos), With<Compass>>,
+    player_query: Query<(Entity, &TilePos), With<Player>>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+) {
+    for (player_entity, player_pos) in player_query.iter() {
+        for (compass_entity, compass_pos) in compass_query.iter() {
+            if compass_pos == player_pos {
+                info!("Player picked up a compass");
+                commands.entity(compass_entity).despawn();
+                commands.entity(player_entity).insert(HasCompass);
+                info_event_writer.send(InfoEvent::PlayerPickedUpItem);
+            }
+        }
+    }
+}
+
+/// Keeps the [CompassArrow] beside the player, pointing at the nearest exit or the furthest
+/// unexplored cell. The latter is only worked out again as the player moves
+fn compass_arrow_system(
+    mut commands: Commands,
+    image_assets: Res<ImageAssetStore>,
+    cell_map: Option<Res<CellMap<i32>>>,
+    explored_cells: Res<ExploredCells>,
+    player_query: Query<(&Transform, &TilePos, ChangeTrackers<TilePos>), With<HasCompass>>,
+    exit_query: Query<&Transform, Or<(With<EndGameVortex>, With<EndGameHook>)>>,
+    mut arrow_query: Query<
+        (&mut Transform, &mut Visibility),
+        (
+            With<CompassArrow>,
+            Without<HasCompass>,
+            Without<EndGameVortex>,
+            Without<EndGameHook>,
+        ),
+    >,
+    mut unexplored_target: Local<Option<Vec2>>,
+) {
+    let (player_transform, player_pos, change_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let (mut arrow_transform, mut visibility) = match arrow_query.get_single_mut() {
+        Ok(arrow) => arrow,
+        Err(_) => {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    texture: image_assets.get(&ImageAsset::IntentionArrow),
+                    sprite: Sprite {
+                        color: COMPASS_TINT,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(GameOnly)
+                .insert(CompassArrow);
+            return;
+        }
+    };
+    let player_world_pos = player_transform.translation.truncate();
+    let nearest_exit = exit_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .min_by(|a, b| {
+            a.distance(player_world_pos)
+                .total_cmp(&b.distance(player_world_pos))
+        });
+    let target = match nearest_exit {
+        Some(exit) => Some(exit),
+        None => {
+            if change_tracker.is_changed() || unexplored_target.is_none() {
+                *unexplored_target = cell_map.and_then(|cell_map| {
+                    let (x, y) =
+                        furthest_unexplored(&cell_map, player_pos.as_i32s(), &explored_cells.0)?;
+                    let tile_pos = TilePos {
+                        x: x as u32,
+                        y: y as u32,
+                    };
+                    Some(tile_pos.to_world_pos(0.0).truncate())
+                });
+            }
+            *unexplored_target
+        }
+    };
+    // Nothing to point at, or already there
+    let direction = target
+        .map(|target| target - player_world_pos)
+        .filter(|direction| direction.length() > 1.0)
+        .map(Vec2::normalize);
+    if visibility.is_visible != direction.is_some() {
+        visibility.is_visible = direction.is_some();
+    }
+    if let Some(direction) = direction {
+        let position = player_world_pos + direction * ARROW_DISTANCE;
+        arrow_transform.translation = position.extend(player_transform.translation.z + 1.0);
+        arrow_transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::furthest_unexplored;
+    use crate::map_gen::cell_map::CellMap;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_furthest_unexplored() {
+        // A corridor from (0, 0) to (5, 0) with a side passage at (2, 1)
+        let mut cells: HashMap<(i32, i32), i32> = (0..=5).map(|x| ((x, 0), 0)).collect();
+        cells.insert((2, 1), 0);
+        let cell_map = CellMap::new(cells);
+        let mut explored = HashSet::new();
+        assert_eq!(
+            Some((5, 0)),
+            furthest_unexplored(&cell_map, (0, 0), &explored)
+        );
+        explored.extend([(3, 0), (4, 0), (5, 0)]);
+        assert_eq!(
+            Some((2, 1)),
+            furthest_unexplored(&cell_map, (0, 0), &explored)
+        );
+        explored.extend([(0, 0), (1, 0), (2, 0), (2, 1)]);
+        assert_eq!(None, furthest_unexplored(&cell_map, (0, 0), &explored));
+    }
+}
@@ -1,4 +1,6 @@
+use crate::asset_handling::asset::ImageAsset;
 use crate::game::tilemap::TilePosExt;
+use crate::profiles::settings::CameraFollowMode;
 use bevy::prelude::*;
 use bevy::utils::Duration;
 use bevy_ecs_tilemap::tiles::TilePos;
@@ -6,6 +8,7 @@ use bevy_ecs_tilemap::tiles::TileTexture;
 use interpolation::Lerp;
 use num::clamp;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::f32::consts::{FRAC_PI_2, PI};
 
@@ -23,6 +26,7 @@ pub struct GameCamera;
 pub struct CameraFollow {
     pub x_threshold: f32,
     pub y_threshold: f32,
+    pub mode: CameraFollowMode,
 }
 
 impl CameraFollow {
@@ -33,6 +37,7 @@ impl CameraFollow {
         Self {
             x_threshold,
             y_threshold,
+            mode: CameraFollowMode::default(),
         }
     }
 
@@ -44,11 +49,82 @@ impl CameraFollow {
     }
 }
 
+/// Fraction of the way to the edge of the box the camera covers each frame when smoothed, at 60
+/// frames a second
+const CAMERA_SMOOTHING_FACTOR: f32 = 0.1;
+
+/// The camera's new position along one axis with `mode`, given the target's, the box's half width
+/// and the seconds since last frame
+pub fn camera_follow_axis(
+    mode: CameraFollowMode,
+    camera: f32,
+    target: f32,
+    threshold: f32,
+    delta_seconds: f32,
+) -> f32 {
+    let offset = target - camera;
+    match mode {
+        CameraFollowMode::Box if offset.abs() > threshold => target,
+        CameraFollowMode::Box => camera,
+        CameraFollowMode::Centred => target,
+        CameraFollowMode::SmoothBox => {
+            // The nearest camera position with the target inside the box
+            let edge = target - offset.clamp(-threshold, threshold);
+            camera + (edge - camera) * smoothing_fraction(CAMERA_SMOOTHING_FACTOR, delta_seconds)
+        }
+    }
+}
+
+/// How much of the way to cover over `delta_seconds` when easing `fraction_per_frame` of the way
+/// each frame at 60 frames a second, so easing takes as long whatever the frame rate
+pub fn smoothing_fraction(fraction_per_frame: f32, delta_seconds: f32) -> f32 {
+    1.0 - (1.0 - fraction_per_frame).powf(delta_seconds * 60.0)
+}
+
+/// Shakes the [GameCamera] by an offset on top of wherever following or panning puts it. Big
+/// events add [CameraShake::trauma], which drains away at [CameraShake::decay] per second
+#[derive(Debug, Component)]
+pub struct CameraShake {
+    pub trauma: f32,
+    pub decay: f32,
+    /// Applied to the camera last frame, taken back off before it's moved again
+    pub offset: Vec2,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.5,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraShake {
+    /// Pixels the camera moves along each axis at full trauma
+    const MAX_OFFSET: f32 = 16.0;
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+
+    pub fn decay(&mut self, delta_seconds: f32) {
+        self.trauma = (self.trauma - self.decay * delta_seconds).max(0.0);
+    }
+
+    /// Offset for [noise], each axis in -1.0 to 1.0. Scaled by trauma squared so small knocks
+    /// stay subtle
+    pub fn offset_for(&self, noise: Vec2) -> Vec2 {
+        noise * Self::MAX_OFFSET * self.trauma * self.trauma
+    }
+}
+
 #[derive(Debug, Default, Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
 
 // Not called "Direction" as to not smash with the Direction in bevy prelude
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MapDirection {
     Up,
     Right,
@@ -75,6 +151,15 @@ impl MapDirection {
         }
     }
 
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Right => Self::Left,
+            Self::Left => Self::Right,
+        }
+    }
+
     pub fn to_rotation_from_right_zero(&self) -> f32 {
         match self {
             Self::Right => 0.0,
@@ -86,6 +171,18 @@ impl MapDirection {
 
     pub const ALL: [MapDirection; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
 
+    /// The direction from [from] to [to], if they share a row or column and aren't the same tile
+    pub fn in_line_towards(from: &TilePos, to: &TilePos) -> Option<Self> {
+        use std::cmp::Ordering;
+        match (to.x.cmp(&from.x), to.y.cmp(&from.y)) {
+            (Ordering::Equal, Ordering::Greater) => Some(Self::Up),
+            (Ordering::Equal, Ordering::Less) => Some(Self::Down),
+            (Ordering::Greater, Ordering::Equal) => Some(Self::Right),
+            (Ordering::Less, Ordering::Equal) => Some(Self::Left),
+            _ => None,
+        }
+    }
+
     pub fn rand_choice() -> Self {
         use rand::seq::SliceRandom;
         let mut rng = rand::thread_rng();
@@ -120,6 +217,16 @@ impl MapDirection {
             .unwrap()
             .clone()
     }
+
+    /// For an enemy that hasn't noticed the player, picked by [external_weights] alone
+    pub fn wander_choice(external_weights: &MoveWeighting) -> Self {
+        use rand::seq::SliceRandom;
+        let mut rng = rand::thread_rng();
+        Self::ALL
+            .choose_weighted(&mut rng, |map_dir| external_weights.get(map_dir))
+            .unwrap()
+            .clone()
+    }
 }
 
 fn pick_up_down(dy: isize, high_cost: f32, low_cost: f32, costs: &mut HashMap<MapDirection, f32>) {
@@ -193,11 +300,37 @@ where
             MapDirection::Down => self.down,
         }
     }
+
+    pub fn set(&mut self, direction: &MapDirection, value: T) {
+        match direction {
+            MapDirection::Left => self.left = value,
+            MapDirection::Right => self.right = value,
+            MapDirection::Up => self.up = value,
+            MapDirection::Down => self.down = value,
+        }
+    }
 }
 
 pub type CanMoveDistance = DirectionDependentValue<usize>;
 pub type MoveWeighting = DirectionDependentValue<f32>;
 
+/// The direction an enemy last moved in. Carrying on that way is weighted up a little when
+/// picking its next move, so it doesn't jitter back and forth when the player is about as far
+/// away in both directions
+#[derive(Debug, Component)]
+pub struct LastMove(pub MapDirection);
+
+impl LastMove {
+    /// Kept small so the enemy still turns to follow the player
+    const CONTINUE_BONUS: f32 = 1.3;
+
+    pub fn weighting(&self, move_weights: &MoveWeighting) -> MoveWeighting {
+        let mut weights = move_weights.clone();
+        weights.set(&self.0, move_weights.get(&self.0) * Self::CONTINUE_BONUS);
+        weights
+    }
+}
+
 /// Struct for handling animated sprite frames from a spritesheet where all frames are used
 #[derive(Debug, Component, Default)]
 pub struct SimpleSpriteAnimation {
@@ -291,6 +424,25 @@ impl DirectionalSpriteAnimation {
     }
 }
 
+/// For sprites without a row of frames per direction, mirrors the sprite to face left or right
+/// instead. Facing up or down keeps whichever way it last faced
+#[derive(Debug, Component)]
+pub struct FlipForFacing {
+    /// The way the spritesheet's frames face unflipped, [MapDirection::Left] or
+    /// [MapDirection::Right]
+    pub drawn_facing: MapDirection,
+}
+
+impl FlipForFacing {
+    /// Whether the sprite should be flipped to face [direction], [None] to leave it as it is
+    pub fn flip_x(&self, direction: &MapDirection) -> Option<bool> {
+        match direction {
+            MapDirection::Left | MapDirection::Right => Some(*direction != self.drawn_facing),
+            MapDirection::Up | MapDirection::Down => None,
+        }
+    }
+}
+
 /// Component to override the frame index in a [DirectionalSpriteAnimation] with a special frame
 #[derive(Component)]
 pub struct DirectionalSpriteAnimationSpecial(pub usize);
@@ -334,10 +486,17 @@ impl MovementAnimate {
     pub fn finished(&self, from: &Vec3) -> bool {
         self.destination_position.eq(from)
     }
+
+    /// Slows the movement by [multiplier], e.g. 2.0 takes roughly twice as long to arrive
+    pub fn slow_by(&mut self, multiplier: f32) {
+        self.factor /= multiplier.max(1.0);
+    }
 }
 
+/// Scales up by [Growing::factor] per second with no upper limit, so it's best paired with
+/// something to despawn it
 #[derive(Component, Debug)]
-pub struct Shrinking {
+pub struct Growing {
     pub factor: f32,
 }
 
@@ -491,27 +650,207 @@ impl PlayerDeathAnimation {
             false
         }
     }
+
+    /// Jumps to the end, so the next [PlayerDeathAnimation::update] finishes it
+    pub fn skip(&mut self) {
+        self.delay = Duration::ZERO;
+        self.height_pct = 0f32;
+    }
 }
 
 #[derive(Component, Debug)]
 pub struct Health {
     pub hp: usize,
+    /// Turns left during which all damage is ignored
+    pub invulnerable_turns: usize,
+    /// A free revive, spent before any starfish, see
+    /// [crate::profiles::settings::Settings::last_stand]. Only ever on the player, kept in step
+    /// with the run's [super::game::LastStand]
+    pub last_stand: bool,
+    /// Set when a hit that would have killed was survived by spending a revive, for the health
+    /// watcher to announce and account for
+    pub revived: Option<Revival>,
 }
 
 impl Default for Health {
     fn default() -> Self {
-        Self { hp: 1 }
+        Self {
+            hp: 1,
+            invulnerable_turns: 0,
+            last_stand: false,
+            revived: None,
+        }
     }
 }
 
+/// What a would-be killing hit was survived with, see [Health::decr_by_shielded]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Revival {
+    /// The run's free [Health::last_stand]
+    LastStand,
+    /// A starfish from the [Inventory], which also leaves a few turns of invulnerability
+    Starfish,
+}
+
+/// Turns of invulnerability given on using a starfish, so the hit that would have killed isn't
+/// immediately followed by another
+pub const SECOND_CHANCE_INVULNERABLE_TURNS: usize = 2;
+
 impl Health {
+    pub fn new(hp: usize) -> Self {
+        Self {
+            hp,
+            ..Default::default()
+        }
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.invulnerable_turns > 0
+    }
+
     pub fn decr_by(&mut self, d: usize) {
+        if self.invulnerable() {
+            return;
+        }
         if self.hp >= d {
             self.hp = self.hp.overflowing_sub(d).0;
         } else {
             self.hp = 0
         }
     }
+
+    /// As [decr_by], unless the [Inventory] holds a shield, in which case one is used up to
+    /// absorb the hit instead. A hit that lands and would kill is survived on 1 hp if there's a
+    /// revive to spend, see [Health::decr_by_or_revive]. Returns whether the hit landed
+    ///
+    /// Invulnerability comes first: no hit lands while it lasts, so no shield is used up either.
+    /// Takes the [Mut] so that hits changing nothing, while invulnerable, shielded or of 0 damage
+    /// like poisonous lightning, don't mark the [Health] changed and set off the hurt effects
+    pub fn decr_by_shielded(
+        health: &mut Mut<Health>,
+        d: usize,
+        maybe_inventory: Option<Mut<Inventory>>,
+    ) -> bool {
+        match maybe_inventory {
+            _ if health.invulnerable() => false,
+            Some(mut inventory) if inventory.holds(&InventoryItem::Shield) => {
+                inventory.take(&InventoryItem::Shield);
+                false
+            }
+            maybe_inventory => {
+                if d > 0 {
+                    health.decr_by_or_revive(d, maybe_inventory);
+                }
+                true
+            }
+        }
+    }
+
+    /// As [decr_by], but rather than dropping to 0 hp spends a revive to stay on 1 hp: the last
+    /// stand first, as it's free, then a starfish from the [Inventory] if it holds one. Done as the
+    /// damage is dealt so whatever dealt it sees a survivor, e.g. an attacking enemy doesn't move
+    /// onto the tile
+    pub fn decr_by_or_revive(&mut self, d: usize, maybe_inventory: Option<Mut<Inventory>>) {
+        self.decr_by(d);
+        if self.hp > 0 {
+            return;
+        }
+        if self.last_stand {
+            self.hp = 1;
+            self.last_stand = false;
+            self.revived = Some(Revival::LastStand);
+            return;
+        }
+        let starfish = maybe_inventory.map_or(false, |mut inventory| {
+            inventory.take(&InventoryItem::Starfish)
+        });
+        if starfish {
+            self.hp = 1;
+            self.invulnerable_turns = SECOND_CHANCE_INVULNERABLE_TURNS;
+            self.revived = Some(Revival::Starfish);
+        }
+    }
+}
+
+/// Damage dealt to [Health] at the start of each player turn, for a number of turns. Poison is not
+/// blocked by shields, which only absorb the hit that applied it. Once [expired], it is removed
+/// (along with its tint) by the poison tint system
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Poison {
+    pub turns: usize,
+    pub dmg_per_turn: usize,
+}
+
+impl Poison {
+    pub const TINT: Color = Color::rgb(0.55, 1.0, 0.45);
+
+    /// Counts down one turn, returning the damage to deal for it
+    pub fn tick(&mut self) -> usize {
+        if self.expired() {
+            0
+        } else {
+            self.turns -= 1;
+            self.dmg_per_turn
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.turns == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InventoryItem {
+    Shield,
+    /// Bought in the store, used up in place of the player dying
+    Starfish,
+}
+
+impl InventoryItem {
+    pub fn to_image_asset(&self) -> ImageAsset {
+        match self {
+            Self::Shield => ImageAsset::UiShield,
+            Self::Starfish => ImageAsset::UiStarfish,
+        }
+    }
+}
+
+/// Items held by the player, shown in the bottom bar
+#[derive(Debug, Component, Default)]
+pub struct Inventory {
+    items: Vec<InventoryItem>,
+}
+
+impl Inventory {
+    /// An inventory holding only the given number of [InventoryItem::Starfish]
+    pub fn with_starfish(count: usize) -> Self {
+        Self {
+            items: vec![InventoryItem::Starfish; count],
+        }
+    }
+
+    pub fn add(&mut self, item: InventoryItem) {
+        self.items.push(item);
+    }
+
+    /// Removes one of [item] if held, returning whether it was
+    pub fn take(&mut self, item: &InventoryItem) -> bool {
+        match self.items.iter().position(|held| held == item) {
+            Some(index) => {
+                self.items.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn holds(&self, item: &InventoryItem) -> bool {
+        self.items.contains(item)
+    }
+
+    pub fn items(&self) -> &[InventoryItem] {
+        &self.items
+    }
 }
 
 #[derive(Component, Debug)]
@@ -539,6 +878,15 @@ impl PowerCharges {
     }
 }
 
+/// On the player whilst the charged power key is held. Charging doesn't take a turn, and the
+/// player can go on moving, turning and waiting as usual, each of which adds a turn of charge.
+/// Once the key is released the power fires on the player's next turn, taking that turn
+#[derive(Component, Debug, Default)]
+pub struct PowerCharge {
+    pub turns_held: usize,
+    pub released: bool,
+}
+
 #[derive(Bundle, Default)]
 pub struct TileResidentBundle {
     #[bundle]
@@ -578,10 +926,22 @@ impl TileResidentBundle {
             ),
             tile_pos: (tile_pos),
             movement_animate: (MovementAnimate::default()),
-            health: Health { hp: initial_hp },
+            health: Health::new(initial_hp),
             game_only: GameOnly {},
         }
     }
+
+    /// Slows movement and sprite animation by [multiplier], see [MovementAnimate::slow_by]
+    pub fn with_timing(mut self, multiplier: f32) -> Self {
+        self.movement_animate.slow_by(multiplier);
+        slow_animation_timer(&mut self.animation_timer, multiplier);
+        self
+    }
+}
+
+fn slow_animation_timer(animation_timer: &mut AnimationTimer, multiplier: f32) {
+    let duration = animation_timer.0.duration().mul_f32(multiplier.max(1.0));
+    animation_timer.0.set_duration(duration);
 }
 
 #[derive(Bundle, Default)]
@@ -623,8 +983,144 @@ impl SimpleTileResidentBundle {
             simple_animation: SimpleSpriteAnimation::new(initial_frame, animation_frames),
             tile_pos: (tile_pos),
             movement_animate: (MovementAnimate::default()),
-            health: Health { hp: initial_hp },
+            health: Health::new(initial_hp),
             game_only: GameOnly {},
         }
     }
+    pub fn with_colour(mut self, colour: Color) -> Self {
+        self.sprite_sheet_bundle.sprite.color = colour;
+        self
+    }
+
+    /// Slows movement and sprite animation by [multiplier], see [MovementAnimate::slow_by]
+    pub fn with_timing(mut self, multiplier: f32) -> Self {
+        self.movement_animate.slow_by(multiplier);
+        slow_animation_timer(&mut self.animation_timer, multiplier);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        camera_follow_axis, smoothing_fraction, CameraShake, FlipForFacing, Health, LastMove,
+        MapDirection, MoveWeighting, PlayerDeathAnimation, Poison,
+    };
+    use crate::profiles::settings::CameraFollowMode;
+    use bevy::prelude::{Transform, Vec2};
+    use bevy_ecs_tilemap::tiles::TilePos;
+    use std::time::Duration;
+
+    #[test]
+    fn test_poison_ticks_down_then_expires() {
+        let mut poison = Poison {
+            turns: 2,
+            dmg_per_turn: 1,
+        };
+        assert!(!poison.expired());
+        assert_eq!(1, poison.tick());
+        assert_eq!(1, poison.turns);
+        assert_eq!(1, poison.tick());
+        assert!(poison.expired());
+        // Expired poison does nothing further
+        assert_eq!(0, poison.tick());
+        assert_eq!(0, poison.turns);
+    }
+
+    #[test]
+    fn test_in_line_towards() {
+        let from = TilePos { x: 3, y: 3 };
+        let towards = |x, y| MapDirection::in_line_towards(&from, &TilePos { x, y });
+        assert_eq!(Some(MapDirection::Up), towards(3, 7));
+        assert_eq!(Some(MapDirection::Down), towards(3, 0));
+        assert_eq!(Some(MapDirection::Right), towards(4, 3));
+        assert_eq!(Some(MapDirection::Left), towards(1, 3));
+        assert_eq!(None, towards(3, 3));
+        assert_eq!(None, towards(4, 4));
+    }
+
+    #[test]
+    fn test_flip_for_facing() {
+        let flip = FlipForFacing {
+            drawn_facing: MapDirection::Right,
+        };
+        assert_eq!(Some(false), flip.flip_x(&MapDirection::Right));
+        assert_eq!(Some(true), flip.flip_x(&MapDirection::Left));
+        assert_eq!(None, flip.flip_x(&MapDirection::Up));
+        assert_eq!(None, flip.flip_x(&MapDirection::Down));
+    }
+
+    #[test]
+    fn test_skipped_death_animation_finishes_on_next_update() {
+        let mut animation = PlayerDeathAnimation::new(Duration::from_secs(1), 50.0);
+        let mut transform = Transform::default();
+        assert!(!animation.update(&mut transform, &Duration::ZERO));
+        animation.skip();
+        assert!(animation.update(&mut transform, &Duration::ZERO));
+        assert_eq!(0.0, transform.scale.y);
+    }
+
+    #[test]
+    fn test_invulnerable_health_ignores_damage() {
+        let mut health = Health {
+            hp: 2,
+            invulnerable_turns: 1,
+            ..Default::default()
+        };
+        health.decr_by(1);
+        assert_eq!(2, health.hp);
+        health.invulnerable_turns = 0;
+        health.decr_by(1);
+        assert_eq!(1, health.hp);
+    }
+
+    #[test]
+    fn test_camera_follow_axis() {
+        let follow = |mode, target| camera_follow_axis(mode, 0.0, target, 100.0, 1.0 / 60.0);
+        // Inside the box
+        assert_eq!(0.0, follow(CameraFollowMode::Box, 50.0));
+        assert_eq!(50.0, follow(CameraFollowMode::Centred, 50.0));
+        assert_eq!(0.0, follow(CameraFollowMode::SmoothBox, 50.0));
+        // Outside it
+        assert_eq!(150.0, follow(CameraFollowMode::Box, 150.0));
+        assert_eq!(150.0, follow(CameraFollowMode::Centred, 150.0));
+        let smoothed = follow(CameraFollowMode::SmoothBox, -150.0);
+        assert!(smoothed < 0.0 && smoothed > -50.0);
+    }
+
+    #[test]
+    fn test_smoothing_fraction_independent_of_frame_rate() {
+        assert!((smoothing_fraction(0.1, 1.0 / 60.0) - 0.1).abs() < 1e-5);
+        // Two frames at 120 cover what one does at 60
+        let half_step = smoothing_fraction(0.1, 1.0 / 120.0);
+        let remaining = (1.0 - half_step) * (1.0 - half_step);
+        assert!((remaining - 0.9).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_last_move_weighting() {
+        let weights =
+            LastMove(MapDirection::Up).weighting(&MoveWeighting::updown_leftright(0.5, 1.0));
+        assert!(weights.get(&MapDirection::Up) > 0.5);
+        assert!(weights.get(&MapDirection::Up) < weights.get(&MapDirection::Left));
+        assert_eq!(0.5, weights.get(&MapDirection::Down));
+        assert_eq!(1.0, weights.get(&MapDirection::Right));
+    }
+
+    #[test]
+    fn test_camera_shake_decays() {
+        let mut shake = CameraShake::default();
+        assert_eq!(Vec2::ZERO, shake.offset_for(Vec2::ONE));
+        shake.add_trauma(0.5);
+        shake.add_trauma(0.7);
+        assert_eq!(1.0, shake.trauma);
+        assert_eq!(
+            Vec2::new(CameraShake::MAX_OFFSET, -CameraShake::MAX_OFFSET),
+            shake.offset_for(Vec2::new(1.0, -1.0))
+        );
+        shake.decay(0.5);
+        assert_eq!(0.25, shake.trauma);
+        shake.decay(1.0);
+        assert_eq!(0.0, shake.trauma);
+    }
 }
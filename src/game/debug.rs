@@ -1,12 +1,22 @@
 use crate::asset_handling::{ImageAssetStore, TextureAtlasStore};
 use crate::game::components::*;
-use crate::game::enemy::Enemy;
+use crate::game::dev_console::{dev_console_command_system, dev_console_system, ConsoleCommand};
+use crate::game::enemy::{Enemy, EnemyType};
 use crate::game::events::{InfoEvent, InputEvent};
 
 use crate::game::end_game::VortexSpawnEvent;
+use crate::game::game::MapSeed;
+use crate::game::quick_save::QuickSave;
 use crate::game::tilemap::{HasTileType, TilePosExt, TileStorageQuery};
-use crate::game::turn::{GamePhase, GlobalTurnCounter, TurnCounter};
+use crate::game::turn::{GamePhase, GlobalLevelCounter, GlobalTurnCounter, TurnCounter};
 use crate::map_gen::cell_map::CellMap;
+use crate::menu_core::menu_core::text::UiFont;
+use crate::menu_core::text_input::TextInput;
+use crate::profiles::settings::Settings;
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::entity::Entities;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::tiles::TilePos;
 
@@ -17,16 +27,46 @@ impl Plugin for GameDebugPlugin {
         //This plugin is empty unless "debug_assetions" is enabled, i.e. it is in dev
         // so no systems below will be run in release builds
         if cfg!(debug_assertions) {
-            app.add_system_set(
-                SystemSet::on_update(crate::CoreState::GameLevel)
-                    .with_system(debug_print_input_system)
-                    .with_system(input_event_debug_system)
-                    .with_system(mouse_click_debug_system),
-            );
+            app.insert_resource(QuickSave::default())
+                .init_resource::<DevHud>()
+                .add_plugin(FrameTimeDiagnosticsPlugin)
+                .add_event::<ConsoleCommand>()
+                // The console clears key presses while it's open, so it runs ahead of the update
+                // stage to go before anything reading them
+                .add_system_set_to_stage(
+                    CoreStage::PreUpdate,
+                    SystemSet::new()
+                        .with_run_criteria(in_game_level)
+                        .after(InputSystem)
+                        .with_system(TextInput::system.label("dev_console_text"))
+                        .with_system(dev_console_system.after("dev_console_text")),
+                )
+                .add_system_set(
+                    SystemSet::on_update(crate::CoreState::GameLevel)
+                        .with_system(dev_console_command_system)
+                        .with_system(debug_print_input_system)
+                        .with_system(input_event_debug_system)
+                        .with_system(mouse_click_debug_system)
+                        .with_system(map_export_system)
+                        .with_system(dev_hud_toggle_system)
+                        .with_system(dev_hud_system)
+                        .with_system(super::quick_save::quick_save_system)
+                        .with_system(super::quick_save::quick_load_system),
+                );
         }
     }
 }
 
+/// Run criteria for the console, as state run criteria only work in the stage the state is driven
+/// in
+fn in_game_level(state: Res<State<crate::CoreState>>) -> ShouldRun {
+    if *state.current() == crate::CoreState::GameLevel {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
 fn debug_print_input_system(
     mut query: ParamSet<(
         Query<(&Transform, &GlobalTransform)>,
@@ -42,9 +82,11 @@ fn debug_print_input_system(
     _asset_server: Res<AssetServer>,
     _texture_atlases: ResMut<Assets<TextureAtlas>>,
     atlases: Res<TextureAtlasStore>,
+    global_level_counter: Res<GlobalLevelCounter>,
     mut info_event_writer: EventWriter<InfoEvent>,
     mut vortex_spawn_event_writer: EventWriter<VortexSpawnEvent>,
-    _image_assets: Res<ImageAssetStore>,
+    image_assets: Res<ImageAssetStore>,
+    settings: Res<Settings>,
 ) {
     if input.just_pressed(KeyCode::P) {
         for (trans, global_trans) in query.p0().iter() {
@@ -75,12 +117,16 @@ fn debug_print_input_system(
             (*x as i32, *y as i32)
         };
         let recalculated_map = cell_map.recalculate(start_point);
-        let _: Vec<(i32, i32)> = super::enemy::add_sharks(
+        let _: Vec<(i32, i32)> = super::enemy::add_enemies(
             &mut commands,
             &atlases,
+            &image_assets,
+            EnemyType::Shark,
             4,
+            global_level_counter.level(),
             &recalculated_map,
             Some(&exclude_positions),
+            settings.enemy_timing,
         );
         *cell_map = recalculated_map;
     }
@@ -90,6 +136,88 @@ fn debug_print_input_system(
     }
 }
 
+/// Exports an image of the current level's map, named after the run's seed and the level, to
+/// share alongside the seed
+fn map_export_system(
+    input: Res<Input<KeyCode>>,
+    cell_map: Res<CellMap<i32>>,
+    map_seed: Res<MapSeed>,
+    global_level_counter: Res<GlobalLevelCounter>,
+) {
+    if input.just_pressed(KeyCode::F6) {
+        let image = crate::map_gen::render_cell_map_to_image(&cell_map, 8);
+        let name = match map_seed.seed {
+            Some(seed) => format!("map_{}_level_{}", seed, global_level_counter.level()),
+            None => format!("map_level_{}", global_level_counter.level()),
+        };
+        match crate::map_gen::export_map_image(&image, &name) {
+            Ok(location) => info!("Exported map image: {}", location),
+            Err(e) => warn!("Failed to export map image: {}", e),
+        }
+    }
+}
+
+/// Shows the FPS and live entity count in the corner, to watch for entities piling up over a run
+#[derive(Debug, Default)]
+struct DevHud {
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct DevHudText;
+
+const DEV_HUD_FONT_SIZE: f32 = 20.0;
+
+fn dev_hud_toggle_system(input: Res<Input<KeyCode>>, mut dev_hud: ResMut<DevHud>) {
+    if input.just_pressed(KeyCode::F3) {
+        dev_hud.enabled = !dev_hud.enabled;
+        info!("Dev HUD enabled: {}", dev_hud.enabled);
+    }
+}
+
+/// Keeps the [DevHud] text spawned while enabled, including after the level's cleanup removes
+/// it, and updates it every frame
+fn dev_hud_system(
+    mut commands: Commands,
+    dev_hud: Res<DevHud>,
+    diagnostics: Res<Diagnostics>,
+    entities: &Entities,
+    ui_font: Res<UiFont>,
+    mut text_query: Query<(Entity, &mut Text), With<DevHudText>>,
+) {
+    if !dev_hud.enabled {
+        for (entity, _) in text_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.average())
+        .map_or_else(|| "-".to_string(), |fps| format!("{:.0}", fps));
+    let hud_text = format!("FPS: {}\nEntities: {}", fps, entities.len());
+    match text_query.get_single_mut() {
+        Ok((_, mut text)) => text.sections[0].value = hud_text,
+        Err(_) => {
+            commands
+                .spawn_bundle(
+                    TextBundle::from_section(hud_text, ui_font.text_style(DEV_HUD_FONT_SIZE))
+                        .with_style(Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(10.0),
+                                bottom: Val::Px(10.0),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        }),
+                )
+                .insert(DevHudText)
+                .insert(GameOnly);
+        }
+    }
+}
+
 fn input_event_debug_system(
     mut input_events: EventReader<InputEvent>,
     global_turn_counter: Res<GlobalTurnCounter>,
@@ -0,0 +1,273 @@
+//! Attract mode: after the main menu has sat idle for a while a bot plays a level, without sound
+//! effects or HUD but over the menu music, until it dies, leaves through the vortex, runs out of
+//! turns or the user presses anything, at which point it returns to the main menu.
+//!
+//! The level runs on the shared [turn_system_set](super::game::turn_system_set), the bot only
+//! stands in for the input systems by sending [InputEvent]s.
+
+use crate::game::components::{GameOnly, MapDirection, MovementAnimate, Player};
+use crate::game::end_game::EndGameVortex;
+use crate::game::enemy::Enemy;
+use crate::game::events::{GameEvent, InputEvent};
+use crate::game::game::{RegularGameEnable, SnailsCollectedThisRun};
+use crate::game::snails::Snail;
+use crate::game::tilemap::TilePosExt;
+use crate::game::turn::{GamePhase, GlobalTurnCounter};
+use crate::helpers::cleanup::recursive_cleanup;
+use crate::map_gen::cell_map::CellMap;
+use crate::profiles::profiles::{LoadedUserProfile, UserProfile};
+use crate::profiles::settings::Settings;
+use crate::CoreState;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use std::collections::HashMap;
+
+pub struct DemoPlugin;
+
+/// Resource timing how long the main menu has been idle, falling into the demo once it runs out
+/// if [Settings::idle_demo] is on
+pub struct DemoSettings {
+    idle_timer: Timer,
+}
+
+impl Default for DemoSettings {
+    fn default() -> Self {
+        Self {
+            idle_timer: Timer::from_seconds(20.0, false),
+        }
+    }
+}
+
+/// The demo gives up and returns to the menu after this many turns
+const MAX_DEMO_TURNS: usize = 150;
+/// Pause between the bot's moves so the demo reads at a watchable pace
+const BOT_MOVE_INTERVAL_SECS: f32 = 0.4;
+/// How much the bot is put off by each enemy next to a tile, in tiles of distance to its target
+const ENEMY_ADJACENT_PENALTY: i32 = 4;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        let state = CoreState::Demo;
+        app.init_resource::<DemoSettings>()
+            .add_system_set(
+                SystemSet::on_enter(CoreState::MainMenu).with_system(demo_idle_reset_system),
+            )
+            .add_system_set(SystemSet::on_update(CoreState::MainMenu).with_system(demo_idle_system))
+            .add_system_set(SystemSet::on_enter(state).with_system(super::game::setup))
+            .add_system_set(
+                super::game::turn_system_set(state)
+                    .with_system(bot_input_system.before("player_movement"))
+                    .with_system(demo_end_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(state)
+                    .with_system(recursive_cleanup::<GameOnly>)
+                    .with_system(super::game::state_cleanup)
+                    .with_system(super::tilemap::cleanup)
+                    .with_system(demo_cleanup),
+            );
+    }
+}
+
+fn demo_idle_reset_system(mut demo_settings: ResMut<DemoSettings>) {
+    demo_settings.idle_timer.reset();
+}
+
+/// Starts the demo once the main menu has had no keyboard or mouse input for the idle time
+fn demo_idle_system(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut mouse_button_events: EventReader<MouseButtonInput>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut demo_settings: ResMut<DemoSettings>,
+    settings: Res<Settings>,
+    mut app_state: ResMut<State<CoreState>>,
+    mut commands: Commands,
+) {
+    let mouse_input = mouse_button_events.iter().count() > 0;
+    let cursor_moved = cursor_moved_events.iter().count() > 0;
+    let key_held = keyboard_input.get_pressed().next().is_some();
+    if mouse_input || cursor_moved || key_held || !settings.idle_demo {
+        demo_settings.idle_timer.reset();
+        return;
+    }
+    if demo_settings.idle_timer.tick(time.delta()).just_finished() {
+        info!("Main menu idle, starting demo");
+        // Level setup reads stats from the profile, this stands in for one and is removed again
+        // when the demo ends so it can never be saved
+        commands.insert_resource(LoadedUserProfile::new(UserProfile::default(), 0));
+        app_state.set(CoreState::Demo).unwrap();
+    }
+}
+
+/// Returns to the main menu when the bot's run ends or the user wants the menu back
+fn demo_end_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut game_event_reader: EventReader<GameEvent>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut app_state: ResMut<State<CoreState>>,
+) {
+    let user_input = keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some();
+    let run_over = game_event_reader.iter().any(|event| {
+        matches!(
+            event,
+            GameEvent::PlayerDied | GameEvent::HookCompleted | GameEvent::VortexCompleted
+        )
+    });
+    let out_of_turns = global_turn_counter.turn_count > MAX_DEMO_TURNS;
+    if user_input || run_over || out_of_turns {
+        info!("Demo over, returning to main menu");
+        if let Err(e) = app_state.set(CoreState::MainMenu) {
+            warn!("Error leaving demo, not considering it a problem: {:?}", e);
+        }
+    }
+}
+
+fn demo_cleanup(mut commands: Commands, mut snails_collected: ResMut<SnailsCollectedThisRun>) {
+    commands.remove_resource::<LoadedUserProfile>();
+    // Shells the bot picked up must not carry over into the user's next run
+    snails_collected.0 = 0;
+}
+
+/// Sends the bot's move whenever it's the player's turn and the last move has finished animating
+fn bot_input_system(
+    time: Res<Time>,
+    mut move_timer: Local<Option<Timer>>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    regular_game_enable: Res<RegularGameEnable>,
+    cell_map: Option<Res<CellMap<i32>>>,
+    player_query: Query<&TilePos, With<Player>>,
+    snail_query: Query<&TilePos, With<Snail>>,
+    vortex_query: Query<&TilePos, With<EndGameVortex>>,
+    enemy_query: Query<&TilePos, With<Enemy>>,
+    animate_query: Query<&MovementAnimate>,
+    mut input_events: EventWriter<InputEvent>,
+) {
+    let move_timer =
+        move_timer.get_or_insert_with(|| Timer::from_seconds(BOT_MOVE_INTERVAL_SECS, true));
+    if !move_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let animating = animate_query.iter().any(|animate| animate.active);
+    let players_turn = global_turn_counter.current_phase == GamePhase::PlayerMovement;
+    if animating || !players_turn || !regular_game_enable.enabled {
+        return;
+    }
+    let (cell_map, player_pos) = match (cell_map, player_query.get_single()) {
+        (Some(cell_map), Ok(player_pos)) => (cell_map, player_pos.as_i32s()),
+        _ => return,
+    };
+    // Snails first, the vortex once they're all collected
+    let mut targets: Vec<(i32, i32)> = snail_query.iter().map(TilePosExt::as_i32s).collect();
+    if targets.is_empty() {
+        targets = vortex_query.iter().map(TilePosExt::as_i32s).collect();
+    }
+    let enemies: Vec<(i32, i32)> = enemy_query.iter().map(TilePosExt::as_i32s).collect();
+    let event = match choose_move(player_pos, &cell_map, &targets, &enemies) {
+        Some(direction) => InputEvent::MoveDirection(direction),
+        None => InputEvent::Wait,
+    };
+    input_events.send(event);
+}
+
+/// Greedily picks the step that gets closest to a target while keeping away from enemies, or
+/// [None] to wait where it is. Moving into an enemy attacks it, so that's scored as staying put
+/// with one less enemy around
+fn choose_move(
+    player_pos: (i32, i32),
+    cell_map: &CellMap<i32>,
+    targets: &[(i32, i32)],
+    enemies: &[(i32, i32)],
+) -> Option<MapDirection> {
+    let distances = distances_to_targets(cell_map, targets);
+    let score = |pos: (i32, i32), ignored_enemy: Option<(i32, i32)>| {
+        let enemies_adjacent = enemies
+            .iter()
+            .filter(|enemy| Some(**enemy) != ignored_enemy)
+            .filter(|(x, y)| (x - pos.0).abs() + (y - pos.1).abs() <= 1)
+            .count() as i32;
+        let distance = distances.get(&pos).cloned().unwrap_or(0);
+        -distance - ENEMY_ADJACENT_PENALTY * enemies_adjacent
+    };
+
+    let mut best = (score(player_pos, None), None);
+    for direction in [
+        MapDirection::Up,
+        MapDirection::Right,
+        MapDirection::Down,
+        MapDirection::Left,
+    ] {
+        let (dx, dy) = direction.to_pos_move();
+        let next = (player_pos.0 + dx, player_pos.1 + dy);
+        if !cell_map.contains(&next) {
+            continue;
+        }
+        let next_score = if enemies.contains(&next) {
+            score(player_pos, Some(next))
+        } else {
+            score(next, None)
+        };
+        if next_score > best.0 {
+            best = (next_score, Some(direction));
+        }
+    }
+    best.1
+}
+
+/// Walking distance from each cell to its nearest target
+fn distances_to_targets(
+    cell_map: &CellMap<i32>,
+    targets: &[(i32, i32)],
+) -> HashMap<(i32, i32), i32> {
+    let mut distances: HashMap<(i32, i32), i32> = HashMap::new();
+    for target in targets.iter().filter(|target| cell_map.contains(target)) {
+        for (cell, distance) in cell_map.recalculate(*target).0 {
+            let nearest = distances.entry(cell).or_insert(distance);
+            *nearest = (*nearest).min(distance);
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod test {
+    use super::choose_move;
+    use crate::game::components::MapDirection;
+    use crate::map_gen::cell_map::CellMap;
+
+    /// A straight corridor along x from 0 to 4
+    fn corridor() -> CellMap<i32> {
+        CellMap::new((0..5).map(|x| ((x, 0), 0)).collect())
+    }
+
+    #[test]
+    fn test_moves_towards_target() {
+        let cell_map = corridor();
+        assert_eq!(
+            Some(MapDirection::Right),
+            choose_move((1, 0), &cell_map, &[(4, 0)], &[])
+        );
+        assert_eq!(
+            Some(MapDirection::Left),
+            choose_move((3, 0), &cell_map, &[(0, 0)], &[])
+        );
+    }
+
+    #[test]
+    fn test_attacks_enemy_in_the_way() {
+        let cell_map = corridor();
+        assert_eq!(
+            Some(MapDirection::Right),
+            choose_move((1, 0), &cell_map, &[(4, 0)], &[(2, 0)])
+        );
+    }
+
+    #[test]
+    fn test_waits_without_target() {
+        let cell_map = corridor();
+        assert_eq!(None, choose_move((2, 0), &cell_map, &[], &[]));
+    }
+}
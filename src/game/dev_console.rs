@@ -0,0 +1,253 @@
+//! A text console for dev commands, opened in game with the backtick key in debug builds. Typed
+//! lines are parsed into [ConsoleCommand] events, carried out by [dev_console_command_system]
+
+use crate::asset_handling::{ImageAssetStore, TextureAtlasStore};
+use crate::game::components::{GameOnly, Health, Player};
+use crate::game::enemy::{Enemy, EnemyType};
+use crate::game::game::MapSeed;
+use crate::game::tilemap::TilePosExt;
+use crate::game::turn::GlobalLevelCounter;
+use crate::map_gen::cell_map::CellMap;
+use crate::menu_core::menu_core::text::UiFont;
+use crate::menu_core::text_input::TextInput;
+use crate::profiles::profiles::LoadedUserProfile;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use std::str::FromStr;
+
+const HELP: &str = "spawn <enemy> [count], heal [hp], setlevel <level>, seed <seed>, killall";
+const CONSOLE_FONT_SIZE: f32 = 20.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    /// Spawns this many of the enemy, spread across the map away from the player
+    Spawn(EnemyType, usize),
+    /// Heals the player by this much, or back to full with [None]
+    Heal(Option<usize>),
+    /// Sets the level counter, which the next level carries on from
+    SetLevel(usize),
+    /// Sets the run's seed, which the following levels' maps are generated from
+    Seed(u64),
+    KillAll,
+    Help,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        fn number<T: FromStr>(arg: &str) -> Result<T, String> {
+            arg.parse().map_err(|_| format!("Not a number: {}", arg))
+        }
+        fn enemy_type(arg: &str) -> Result<EnemyType, String> {
+            match arg {
+                "shark" => Ok(EnemyType::Shark),
+                "crab" => Ok(EnemyType::Crab),
+                "jelly" | "jellyfish" => Ok(EnemyType::Jellyfish),
+                "phantom" => Ok(EnemyType::Phantom),
+                "barnacle" => Ok(EnemyType::Barnacle),
+                _ => Err(format!("Unknown enemy: {}", arg)),
+            }
+        }
+        let line = line.to_lowercase();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["spawn", enemy] => Ok(Self::Spawn(enemy_type(enemy)?, 1)),
+            ["spawn", enemy, count] => Ok(Self::Spawn(enemy_type(enemy)?, number(count)?)),
+            ["heal"] => Ok(Self::Heal(None)),
+            ["heal", hp] => Ok(Self::Heal(Some(number(hp)?))),
+            ["setlevel", level] => Ok(Self::SetLevel(number(level)?)),
+            ["seed", seed] => Ok(Self::Seed(number(seed)?)),
+            ["killall"] => Ok(Self::KillAll),
+            ["help"] => Ok(Self::Help),
+            [] => Err("No command given".to_string()),
+            _ => Err(format!("Unknown command: {}, try help", line.trim())),
+        }
+    }
+}
+
+/// On the console's root node, alongside its [TextInput]
+#[derive(Component)]
+pub struct DevConsole {
+    /// Text node showing the result of the last command
+    output: Entity,
+}
+
+fn is_valid_console_char(c: &char) -> bool {
+    c.is_ascii_alphanumeric() || c == &' '
+}
+
+fn spawn_console(commands: &mut Commands, ui_font: &UiFont) {
+    let mut output = None;
+    let mut text_input = None;
+    let console = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: UiRect::all(Val::Px(5.0)),
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+            ..Default::default()
+        })
+        .insert(GameOnly)
+        .with_children(|parent| {
+            output = Some(
+                parent
+                    .spawn_bundle(TextBundle::from_section(
+                        HELP,
+                        ui_font.text_style(CONSOLE_FONT_SIZE),
+                    ))
+                    .id(),
+            );
+            text_input = Some(TextInput::create(parent, ui_font, is_valid_console_char));
+        })
+        .id();
+    commands
+        .entity(console)
+        .insert(text_input.unwrap())
+        .insert(DevConsole {
+            output: output.unwrap(),
+        });
+}
+
+/// Opens and closes the console, and sends a [ConsoleCommand] for each line entered. While it's
+/// open, key presses are cleared once read so the game doesn't act on what's typed
+pub fn dev_console_system(
+    mut commands: Commands,
+    mut input: ResMut<Input<KeyCode>>,
+    ui_font: Res<UiFont>,
+    mut console_query: Query<(Entity, &mut TextInput, &DevConsole)>,
+    mut text_query: Query<&mut Text>,
+    mut console_command_writer: EventWriter<ConsoleCommand>,
+) {
+    if input.just_pressed(KeyCode::Grave) {
+        match console_query.get_single() {
+            Ok((entity, _, _)) => commands.entity(entity).despawn_recursive(),
+            Err(_) => spawn_console(&mut commands, &ui_font),
+        }
+        input.clear();
+        return;
+    }
+    let (_, mut text_input, console) = match console_query.get_single_mut() {
+        Ok(console) => console,
+        Err(_) => return,
+    };
+    if input.just_pressed(KeyCode::Return) {
+        let line = text_input.take(&mut text_query);
+        let output = match ConsoleCommand::parse(&line) {
+            Ok(ConsoleCommand::Help) => HELP.to_string(),
+            Ok(command) => {
+                info!("Console command: {:?}", command);
+                console_command_writer.send(command);
+                format!("> {}", line)
+            }
+            Err(e) => e,
+        };
+        if let Ok(mut text) = text_query.get_mut(console.output) {
+            text.sections[0].value = output;
+        }
+    }
+    input.clear();
+}
+
+pub fn dev_console_command_system(
+    mut console_command_reader: EventReader<ConsoleCommand>,
+    mut commands: Commands,
+    atlases: Res<TextureAtlasStore>,
+    image_assets: Res<ImageAssetStore>,
+    cell_map: Res<CellMap<i32>>,
+    mut global_level_counter: ResMut<GlobalLevelCounter>,
+    mut map_seed: ResMut<MapSeed>,
+    settings: Res<Settings>,
+    loaded_profile: Res<LoadedUserProfile>,
+    mut player_query: Query<(&TilePos, &mut Health), (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(&TilePos, &mut Health), (With<Enemy>, Without<Player>)>,
+) {
+    for command in console_command_reader.iter() {
+        match command {
+            ConsoleCommand::Spawn(enemy_type, count) => {
+                let player_pos = match player_query.get_single() {
+                    Ok((player_pos, _)) => player_pos.as_i32s(),
+                    Err(_) => continue,
+                };
+                let mut exclude_positions: Vec<(i32, i32)> = enemy_query
+                    .iter()
+                    .map(|(tile_pos, _)| tile_pos.as_i32s())
+                    .collect();
+                exclude_positions.push(player_pos);
+                super::enemy::add_enemies(
+                    &mut commands,
+                    &atlases,
+                    &image_assets,
+                    *enemy_type,
+                    *count,
+                    global_level_counter.difficulty_level(),
+                    &cell_map.recalculate(player_pos),
+                    Some(&exclude_positions),
+                    settings.enemy_timing,
+                );
+            }
+            ConsoleCommand::Heal(hp) => {
+                let max_health = loaded_profile.user_profile.max_health();
+                for (_, mut health) in player_query.iter_mut() {
+                    health.hp = hp.map_or(max_health, |hp| {
+                        health.hp.saturating_add(hp).min(max_health)
+                    });
+                }
+            }
+            ConsoleCommand::SetLevel(level) => global_level_counter.set(*level),
+            ConsoleCommand::Seed(seed) => map_seed.seed = Some(*seed),
+            // Dead enemies are despawned by the usual health watching
+            ConsoleCommand::KillAll => {
+                for (_, mut health) in enemy_query.iter_mut() {
+                    health.hp = 0;
+                }
+            }
+            ConsoleCommand::Help => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConsoleCommand;
+    use crate::game::enemy::EnemyType;
+
+    #[test]
+    fn test_parse_console_commands() {
+        assert_eq!(
+            Ok(ConsoleCommand::Spawn(EnemyType::Shark, 3)),
+            ConsoleCommand::parse("spawn shark 3")
+        );
+        assert_eq!(
+            Ok(ConsoleCommand::Spawn(EnemyType::Jellyfish, 1)),
+            ConsoleCommand::parse("  Spawn JELLY ")
+        );
+        assert_eq!(
+            Ok(ConsoleCommand::Heal(None)),
+            ConsoleCommand::parse("heal")
+        );
+        assert_eq!(
+            Ok(ConsoleCommand::SetLevel(5)),
+            ConsoleCommand::parse("setlevel 5")
+        );
+        assert_eq!(
+            Ok(ConsoleCommand::Seed(12345)),
+            ConsoleCommand::parse("seed 12345")
+        );
+        assert_eq!(
+            Ok(ConsoleCommand::KillAll),
+            ConsoleCommand::parse("killall")
+        );
+        assert!(ConsoleCommand::parse("spawn whale").is_err());
+        assert!(ConsoleCommand::parse("setlevel five").is_err());
+        assert!(ConsoleCommand::parse("").is_err());
+    }
+}
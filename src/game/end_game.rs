@@ -1,10 +1,12 @@
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
+use crate::game::balance::VortexBalance;
 use crate::game::components::{
-    AnimationTimer, CameraFollow, GameOnly, MovementAnimate, Player, Rotating, Shrinking,
+    AnimationTimer, CameraFollow, GameOnly, MovementAnimate, Player, Rotating,
     SimpleSpriteAnimation,
 };
 use crate::game::events::GameEvent;
+use crate::game::game_time::GameTime;
 use crate::game::tilemap::TilePosExt;
 use bevy::prelude::*;
 use bevy::sprite::MaterialMesh2dBundle;
@@ -24,6 +26,42 @@ pub struct EndGameHookLine;
 
 pub struct VortexSpawnEvent;
 
+/// How close the level is to resolving with a vortex, see [vortex_spawn_status]
+#[derive(Debug, PartialEq, Eq)]
+pub enum VortexSpawnStatus {
+    Ready,
+    /// The vortex spawns after [turns_remaining], or sooner if [enemies_to_clear] are killed
+    Pending {
+        turns_remaining: usize,
+        enemies_to_clear: usize,
+    },
+}
+
+/// The single source of truth for when the vortex spawns, shared by the spawn trigger and the UI
+pub fn vortex_spawn_status(
+    turn_count: usize,
+    enemy_count: usize,
+    balance: &VortexBalance,
+) -> VortexSpawnStatus {
+    let turn_past_threshold = turn_count > balance.late_spawn_turn;
+    let not_too_many_enemies = enemy_count < balance.late_spawn_enemy_count;
+
+    // Late spawn is dependent on being many turns in and killed *some* enemies
+    let can_late_spawn = turn_past_threshold || not_too_many_enemies;
+
+    // Early spawn is if all enemies are killed. Turn count stops this accidentally triggering
+    // before enemies spawn at start
+    let can_early_spawn = enemy_count == 0 && turn_count > 2;
+    if can_late_spawn || can_early_spawn {
+        VortexSpawnStatus::Ready
+    } else {
+        VortexSpawnStatus::Pending {
+            turns_remaining: balance.late_spawn_turn + 1 - turn_count,
+            enemies_to_clear: enemy_count + 1 - balance.late_spawn_enemy_count,
+        }
+    }
+}
+
 #[derive(Default, Component, Clone)]
 pub struct HookedAnimation {
     timer: Timer,
@@ -45,13 +83,54 @@ impl HookedAnimation {
     }
 }
 
-/// Watches for shrinking player (in vortex) and emits event when hits zero size
+/// The player spinning and shrinking away into the vortex. Both are worked out from the time
+/// elapsed rather than stepped each frame, so it looks the same and takes as long at any frame rate
+#[derive(Component, Debug)]
+pub struct VortexEntryAnimation {
+    timer: Timer,
+    /// Radians per second
+    spin: f32,
+}
+
+impl VortexEntryAnimation {
+    const DURATION_SECS: f32 = 1.0;
+
+    fn new(spin: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(Self::DURATION_SECS, false),
+            spin,
+        }
+    }
+
+    /// Returns true once the player has disappeared completely
+    fn tick(&mut self, delta: Duration) -> bool {
+        self.timer.tick(delta);
+        self.timer.finished()
+    }
+
+    fn scale(&self) -> Vec3 {
+        Vec3::splat(self.timer.percent_left())
+    }
+
+    fn rotation(&self) -> Quat {
+        let angle = self.spin * self.timer.elapsed_secs();
+        Quat::from_rotation_z(angle.rem_euclid(std::f32::consts::TAU))
+    }
+}
+
+/// Plays the player's [VortexEntryAnimation], completing the level as it finishes
 pub fn vortex_animation_system(
-    query: Query<&Transform, (Changed<Transform>, With<Player>, With<InVortex>)>,
+    time: Res<GameTime>,
+    mut query: Query<(Entity, &mut Transform, &mut VortexEntryAnimation), With<Player>>,
+    mut commands: Commands,
     mut game_event_writer: EventWriter<GameEvent>,
 ) {
-    for transform in query.iter() {
-        if transform.scale == Vec3::ZERO {
+    for (entity, mut transform, mut animation) in query.iter_mut() {
+        let finished = animation.tick(time.delta());
+        transform.scale = animation.scale();
+        transform.rotation = animation.rotation();
+        if finished {
+            commands.entity(entity).remove::<VortexEntryAnimation>();
             game_event_writer.send(GameEvent::VortexCompleted);
         }
     }
@@ -129,14 +208,13 @@ pub fn end_game_vortex_system(
                 game_event_writer.send(GameEvent::PlayerEnteredVortex);
                 println!("Player entered vortex");
 
-                let rotating = Rotating::new(DEFAULT_VORTEX_ROTATION_SPEED * 2.0);
-                let shrinking = Shrinking { factor: 1.0 };
                 commands
                     .entity(player_entity)
                     .remove::<CameraFollow>()
                     .insert(InVortex)
-                    .insert(shrinking)
-                    .insert(rotating);
+                    .insert(VortexEntryAnimation::new(
+                        DEFAULT_VORTEX_ROTATION_SPEED * 2.0,
+                    ));
             }
         }
     }
@@ -150,7 +228,7 @@ pub fn spawn_vortex(
     let transform = Transform::from_translation(start_pos);
     println!("Vortex Spawned");
 
-    let rotating = Rotating::new(2f32);
+    let rotating = Rotating::new(DEFAULT_VORTEX_ROTATION_SPEED);
     commands
         .spawn_bundle(SpriteBundle {
             texture: image_store.get(&ImageAsset::VortexSprite),
@@ -210,3 +288,43 @@ pub fn spawn_hook(
         .insert(spawn_pos)
         .insert(SimpleSpriteAnimation::new(0, 4));
 }
+
+#[cfg(test)]
+mod test {
+    use super::{vortex_spawn_status, VortexEntryAnimation, VortexSpawnStatus};
+    use crate::game::balance::VortexBalance;
+    use bevy::math::Vec3;
+    use std::time::Duration;
+
+    #[test]
+    fn test_vortex_entry_takes_its_duration() {
+        let mut animation = VortexEntryAnimation::new(4.0);
+        // Uneven frames, as at a low or stuttering frame rate
+        for millis in [300, 17, 450] {
+            assert!(!animation.tick(Duration::from_millis(millis)));
+        }
+        assert!(animation.scale().x > 0.0);
+        assert!(animation.tick(Duration::from_millis(233)));
+        assert_eq!(Vec3::ZERO, animation.scale());
+    }
+
+    #[test]
+    fn test_vortex_spawn_status() {
+        let balance = VortexBalance::default();
+        assert_eq!(
+            VortexSpawnStatus::Pending {
+                turns_remaining: 25,
+                enemies_to_clear: 3
+            },
+            vortex_spawn_status(10, 6, &balance)
+        );
+        assert_eq!(
+            VortexSpawnStatus::Ready,
+            vortex_spawn_status(10, 3, &balance)
+        );
+        assert_eq!(
+            VortexSpawnStatus::Ready,
+            vortex_spawn_status(35, 6, &balance)
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use crate::asset_handling::asset::TextureAtlasAsset;
-use crate::asset_handling::TextureAtlasStore;
+use crate::asset_handling::asset::{ImageAsset, TextureAtlasAsset};
+use crate::asset_handling::{ImageAssetStore, TextureAtlasStore};
 use crate::game::components::{
-    CanMoveDistance, GameOnly, MapDirection, MoveWeighting, Player, SimpleTileResidentBundle,
-    TileResidentBundle,
+    CanMoveDistance, FlipForFacing, GameOnly, MapDirection, MoveWeighting, Player, Poison,
+    SimpleTileResidentBundle, TileResidentBundle,
 };
 use crate::game::projectile::ProjectileFate;
 use crate::game::tilemap::{HasTileType, TilePosExt, TileStorageQuery};
@@ -10,6 +10,8 @@ use crate::game::timed_removal::TimedDespawn;
 use crate::map_gen::cell_map::CellMap;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::tiles::TilePos;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 #[derive(Debug, Component)]
@@ -17,15 +19,86 @@ pub struct Enemy {
     pub can_attack_directly: bool,
 }
 
+/// Damage dealt when an enemy attacks directly
+#[derive(Debug, Component)]
+pub struct AttackDamage(pub usize);
+
+/// The direction an enemy will try to move in its next move, chosen once at the start of the
+/// player's turn so it can be previewed. Cleared once the move resolves
+#[derive(Debug, Component, Default)]
+pub struct MoveIntention(pub Option<MapDirection>);
+
+/// Arrow previewing a [MoveIntention], as a child of the enemy
+#[derive(Debug, Component)]
+pub struct IntentionArrow;
+
+/// Chance each turn that the enemy dozes off and does nothing at all
+#[derive(Debug, Component)]
+pub struct Laziness(pub f64);
+
+impl Laziness {
+    pub fn dozes(&self) -> bool {
+        rand::thread_rng().gen_bool(self.0)
+    }
+}
+
+/// Chance the enemy leaves a [crate::game::snails::DroppedShell] behind when killed
+#[derive(Debug, Component)]
+pub struct ShellDropChance(pub f64);
+
+impl ShellDropChance {
+    pub fn drops(&self) -> bool {
+        rand::thread_rng().gen_bool(self.0)
+    }
+}
+
+/// Row of hearts above an enemy showing its remaining hp, as a child of the enemy
+#[derive(Debug, Component)]
+pub struct HealthPips;
+
 #[derive(Debug, Component)]
 pub struct Shark;
 
+/// Moves like a shark, but now and then teleports next to the player instead
+#[derive(Debug, Component)]
+pub struct Phantom;
+
+impl Phantom {
+    /// Chance each enemy phase that a phantom teleports rather than moving
+    pub const TELEPORT_CHANCE: f64 = 0.2;
+    pub const TINT: Color = Color::rgba(0.7, 0.7, 1.0, 0.6);
+
+    pub fn teleports(&self) -> bool {
+        rand::thread_rng().gen_bool(Self::TELEPORT_CHANCE)
+    }
+}
+
+/// Never moves or attacks, just sits in the way, see [add_barnacles]
+#[derive(Debug, Component)]
+pub struct Barnacle;
+
+impl Barnacle {
+    pub const TINT: Color = Color::rgb(0.6, 0.55, 0.5);
+}
+
 #[derive(Debug, Component)]
 pub struct Crab;
 
+impl Crab {
+    /// Chance a crab spawns armoured, from [Crab::ARMOURED_MIN_LEVEL] on
+    pub const ARMOURED_CHANCE: f64 = 0.3;
+    pub const ARMOURED_MIN_LEVEL: usize = 3;
+}
+
+/// Projectiles pass straight over this entity, it can only be damaged in melee
+#[derive(Debug, Component)]
+pub struct ProjectileImmune;
+
 #[derive(Debug, Component, Default)]
 pub struct Jellyfish {
     pub state: JellyfishState,
+    /// Poisonous jellies' lightning poisons rather than dealing damage outright
+    pub poisonous: bool,
 }
 
 #[derive(Debug, Component)]
@@ -44,99 +117,438 @@ impl Default for JellyfishState {
     }
 }
 
-pub fn add_sharks(
+/// Which kind of enemy an [EnemyDef] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnemyType {
+    Shark,
+    Crab,
+    Jellyfish,
+    Phantom,
+    Barnacle,
+}
+
+impl EnemyType {
+    pub fn def(&self) -> &'static EnemyDef {
+        ENEMY_DEFS
+            .iter()
+            .find(|def| def.enemy_type == *self)
+            .expect("Every enemy type has a def")
+    }
+}
+
+/// How an enemy's sprite sheet is laid out and animated
+#[derive(Debug, Clone, Copy)]
+pub enum EnemySprite {
+    /// A row of frames per facing direction, see [DirectionalSpriteAnimation]
+    Directional,
+    /// One run of frames whatever the facing
+    Simple { frames: usize, frame_secs: f32 },
+}
+
+/// The stats and sprite shared by every enemy of a type. Anything particular to one enemy, like
+/// a crab's armour, is an [EnemyKind] on top of this
+#[derive(Debug)]
+pub struct EnemyDef {
+    pub enemy_type: EnemyType,
+    pub hp: usize,
+    pub atlas: TextureAtlasAsset,
+    pub sprite: EnemySprite,
+    pub can_attack_directly: bool,
+    /// Pairs of (min level, damage), the last one reached applies. Empty for enemies that can't
+    /// attack directly
+    pub damage_by_level: &'static [(usize, usize)],
+    /// Tiles moved per turn, as (up/down, left/right)
+    pub move_distance: (usize, usize),
+    /// Weighting of each direction when picking a move, as (up/down, left/right)
+    pub move_weighting: (f32, f32),
+    /// See [Laziness]
+    pub laziness: Option<f64>,
+    /// For [EnemySprite::Simple] sheets, the way the frames face if they should be mirrored to
+    /// face left or right, see [FlipForFacing]
+    pub flip_for_facing: Option<MapDirection>,
+    /// See [ShellDropChance]
+    pub shell_drop_chance: f64,
+}
+
+impl EnemyDef {
+    /// Damage dealt when attacking directly at this level, if it can
+    pub fn damage_at(&self, level: usize) -> Option<usize> {
+        self.damage_by_level
+            .iter()
+            .filter(|(min_level, _)| level >= *min_level)
+            .last()
+            .map(|(_, damage)| *damage)
+    }
+}
+
+pub static ENEMY_DEFS: [EnemyDef; 5] = [
+    EnemyDef {
+        enemy_type: EnemyType::Shark,
+        hp: 1,
+        atlas: TextureAtlasAsset::SharkSpritesheet,
+        sprite: EnemySprite::Directional,
+        can_attack_directly: true,
+        damage_by_level: &[(0, 1), (5, 2)],
+        move_distance: (1, 1),
+        move_weighting: (1.0, 1.0),
+        laziness: None,
+        flip_for_facing: None,
+        shell_drop_chance: 0.2,
+    },
+    EnemyDef {
+        enemy_type: EnemyType::Crab,
+        hp: 1,
+        atlas: TextureAtlasAsset::CrabSpritesheet,
+        sprite: EnemySprite::Simple {
+            frames: 4,
+            frame_secs: 0.1,
+        },
+        can_attack_directly: true,
+        damage_by_level: &[(0, 1)],
+        move_distance: (1, 2),
+        move_weighting: (0.1, 1.0),
+        laziness: Some(0.2),
+        flip_for_facing: Some(MapDirection::Right),
+        shell_drop_chance: 0.3,
+    },
+    EnemyDef {
+        enemy_type: EnemyType::Jellyfish,
+        hp: 1,
+        atlas: TextureAtlasAsset::JellySpritesheet,
+        sprite: EnemySprite::Simple {
+            frames: 4,
+            frame_secs: 0.2,
+        },
+        can_attack_directly: false,
+        damage_by_level: &[],
+        move_distance: (1, 1),
+        move_weighting: (1.0, 1.0),
+        laziness: None,
+        flip_for_facing: None,
+        shell_drop_chance: 0.15,
+    },
+    EnemyDef {
+        enemy_type: EnemyType::Phantom,
+        hp: 1,
+        atlas: TextureAtlasAsset::SharkSpritesheet,
+        sprite: EnemySprite::Directional,
+        can_attack_directly: true,
+        damage_by_level: &[(0, 1)],
+        move_distance: (1, 1),
+        move_weighting: (1.0, 1.0),
+        laziness: None,
+        flip_for_facing: None,
+        shell_drop_chance: 0.4,
+    },
+    EnemyDef {
+        enemy_type: EnemyType::Barnacle,
+        hp: 3,
+        atlas: TextureAtlasAsset::CrabSpritesheet,
+        // Just the first frame, it doesn't move so shouldn't look like it does
+        sprite: EnemySprite::Simple {
+            frames: 1,
+            frame_secs: 1.0,
+        },
+        can_attack_directly: false,
+        damage_by_level: &[],
+        move_distance: (0, 0),
+        move_weighting: (1.0, 1.0),
+        laziness: None,
+        flip_for_facing: None,
+        shell_drop_chance: 0.5,
+    },
+];
+
+/// One band of the [SPAWN_TABLE]: how many enemies a level spawns from [min_level] on, shared out
+/// between the types by weight
+#[derive(Debug)]
+pub struct SpawnTableEntry {
+    pub min_level: usize,
+    /// Also the cap on the level's enemies. They only spawn as the level's set up, with nothing
+    /// like waves adding more later, so there's no running count to hold under a separate cap
+    pub total: usize,
+    pub weights: &'static [(EnemyType, usize)],
+}
+
+/// Ordered by [SpawnTableEntry::min_level], the last one reached applies. Early levels are mostly
+/// crabs, with sharks, jellyfish and the rest mixed in as the levels go on
+pub static SPAWN_TABLE: [SpawnTableEntry; 3] = [
+    SpawnTableEntry {
+        min_level: 0,
+        total: 10,
+        weights: &[(EnemyType::Crab, 6), (EnemyType::Shark, 4)],
+    },
+    SpawnTableEntry {
+        min_level: 3,
+        total: 12,
+        weights: &[
+            (EnemyType::Shark, 5),
+            (EnemyType::Crab, 4),
+            (EnemyType::Jellyfish, 2),
+            (EnemyType::Barnacle, 1),
+        ],
+    },
+    SpawnTableEntry {
+        min_level: 5,
+        total: 14,
+        weights: &[
+            (EnemyType::Shark, 7),
+            (EnemyType::Crab, 3),
+            (EnemyType::Jellyfish, 1),
+            (EnemyType::Phantom, 1),
+            (EnemyType::Barnacle, 2),
+        ],
+    },
+];
+
+/// The enemies to spawn on [level], from the [SPAWN_TABLE]. The band's total is split by weight,
+/// with what's left over from rounding down going to the largest remainders first
+pub fn spawn_table(level: usize) -> Vec<(&'static EnemyDef, usize)> {
+    let entry = SPAWN_TABLE
+        .iter()
+        .filter(|entry| level >= entry.min_level)
+        .last()
+        .expect("The spawn table starts at level 0");
+    let total_weight: usize = entry.weights.iter().map(|(_, weight)| weight).sum();
+    let mut counts: Vec<(EnemyType, usize, usize)> = entry
+        .weights
+        .iter()
+        .map(|(enemy_type, weight)| {
+            let share = entry.total * weight;
+            (*enemy_type, share / total_weight, share % total_weight)
+        })
+        .collect();
+    let assigned: usize = counts.iter().map(|(_, count, _)| count).sum();
+    let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+    // Stable, so ties go to whichever is listed first
+    by_remainder.sort_by(|a, b| counts[*b].2.cmp(&counts[*a].2));
+    for i in by_remainder.into_iter().take(entry.total - assigned) {
+        counts[i].1 += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count, _)| *count > 0)
+        .map(|(enemy_type, count, _)| (enemy_type.def(), count))
+        .collect()
+}
+
+/// A single enemy to spawn: its type along with anything particular to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Shark,
+    Crab { armoured: bool },
+    Jellyfish { poisonous: bool },
+    Phantom,
+    Barnacle,
+}
+
+impl EnemyKind {
+    /// Rolls the per enemy extras for a new enemy of the type
+    pub fn roll(enemy_type: EnemyType, level: usize, rng: &mut impl Rng) -> Self {
+        match enemy_type {
+            EnemyType::Shark => Self::Shark,
+            EnemyType::Crab => Self::Crab {
+                armoured: level >= Crab::ARMOURED_MIN_LEVEL && rng.gen_bool(Crab::ARMOURED_CHANCE),
+            },
+            EnemyType::Jellyfish => Self::Jellyfish {
+                poisonous: rng.gen_bool(Jellyfish::POISONOUS_CHANCE),
+            },
+            EnemyType::Phantom => Self::Phantom,
+            EnemyType::Barnacle => Self::Barnacle,
+        }
+    }
+
+    pub fn enemy_type(&self) -> EnemyType {
+        match self {
+            Self::Shark => EnemyType::Shark,
+            Self::Crab { .. } => EnemyType::Crab,
+            Self::Jellyfish { .. } => EnemyType::Jellyfish,
+            Self::Phantom => EnemyType::Phantom,
+            Self::Barnacle => EnemyType::Barnacle,
+        }
+    }
+}
+
+/// Spawns [num_enemies] of a type spread across the map, returning where they were put
+pub fn add_enemies(
     commands: &mut Commands,
-    atlases: &Res<TextureAtlasStore>,
-    num_sharks: usize,
+    atlases: &TextureAtlasStore,
+    image_store: &ImageAssetStore,
+    enemy_type: EnemyType,
+    num_enemies: usize,
+    level: usize,
     cell_map: &CellMap<i32>,
     exclude_positions: Option<&Vec<(i32, i32)>>,
+    enemy_timing: f32,
 ) -> Vec<(i32, i32)> {
-    let atlas_handle = atlases.get(&TextureAtlasAsset::SharkSpritesheet);
-    let spawn_positions = cell_map.distribute_points_by_cost(num_sharks, exclude_positions);
+    let spawn_positions = cell_map.distribute_points_by_cost(num_enemies, exclude_positions);
+    let mut rng = rand::thread_rng();
     for (x, y) in spawn_positions.iter() {
         let tile_pos = TilePos {
             x: *x as u32,
             y: *y as u32,
         };
-        commands
-            .spawn_bundle(TileResidentBundle::new(
-                1,
-                tile_pos,
-                atlas_handle.clone(),
-                0,
-            ))
-            .insert(Enemy {
-                can_attack_directly: true,
-            })
-            .insert(CanMoveDistance::all(1))
-            .insert(MoveWeighting::all(1.0))
-            .insert(Shark);
+        let kind = EnemyKind::roll(enemy_type, level, &mut rng);
+        spawn_enemy(
+            commands,
+            atlases,
+            image_store,
+            &kind,
+            tile_pos,
+            level,
+            enemy_timing,
+        );
     }
     spawn_positions
 }
 
-pub fn add_crabs(
+/// Spawns up to [num_barnacles] barnacles in corridors, where they make choke points to fight
+/// through or go around, returning where they were put
+pub fn add_barnacles(
     commands: &mut Commands,
-    atlases: &Res<TextureAtlasStore>,
-    num_crabs: usize,
+    atlases: &TextureAtlasStore,
+    image_store: &ImageAssetStore,
+    num_barnacles: usize,
+    level: usize,
     cell_map: &CellMap<i32>,
     exclude_positions: Option<&Vec<(i32, i32)>>,
+    enemy_timing: f32,
 ) -> Vec<(i32, i32)> {
-    let atlas_handle = atlases.get(&TextureAtlasAsset::CrabSpritesheet);
-    let spawn_positions = cell_map.distribute_points_by_cost(num_crabs, exclude_positions);
+    use rand::seq::SliceRandom;
+    let mut corridor_cells: Vec<(i32, i32)> = cell_map
+        .corridor_cells()
+        .into_iter()
+        .filter(|pos| exclude_positions.map_or(true, |excluded| !excluded.contains(pos)))
+        .collect();
+    // Sorted so the choice only depends on the rng, not on hash order
+    corridor_cells.sort();
+    let spawn_positions: Vec<(i32, i32)> = corridor_cells
+        .choose_multiple(&mut rand::thread_rng(), num_barnacles)
+        .cloned()
+        .collect();
     for (x, y) in spawn_positions.iter() {
         let tile_pos = TilePos {
             x: *x as u32,
             y: *y as u32,
         };
-        commands
-            .spawn_bundle(SimpleTileResidentBundle::new(
-                1,
-                tile_pos,
-                atlas_handle.clone(),
-                4,
-                None,
-            ))
-            .insert(Enemy {
-                can_attack_directly: true,
-            })
-            .insert(CanMoveDistance::updown_leftright(1, 2))
-            .insert(MoveWeighting::updown_leftright(0.1, 1.0))
-            .insert(Crab);
+        spawn_enemy(
+            commands,
+            atlases,
+            image_store,
+            &EnemyKind::Barnacle,
+            tile_pos,
+            level,
+            enemy_timing,
+        );
     }
     spawn_positions
 }
 
-pub fn add_jellyfish(
+/// Spawns an enemy from its type's [EnemyDef], then adds what's particular to its [EnemyKind].
+/// [enemy_timing] slows its movement and animation, see
+/// [crate::profiles::settings::Settings::enemy_timing]
+pub fn spawn_enemy(
     commands: &mut Commands,
-    atlases: &Res<TextureAtlasStore>,
-    num_jellies: usize,
-    cell_map: &CellMap<i32>,
-    exclude_positions: Option<&Vec<(i32, i32)>>,
-) -> Vec<(i32, i32)> {
-    let atlas_handle = atlases.get(&TextureAtlasAsset::JellySpritesheet);
-    let spawn_positions = cell_map.distribute_points_by_cost(num_jellies, exclude_positions);
-    for (x, y) in spawn_positions.iter() {
-        let tile_pos = TilePos {
-            x: *x as u32,
-            y: *y as u32,
-        };
-        commands
-            .spawn_bundle(SimpleTileResidentBundle::new(
-                1,
+    atlases: &TextureAtlasStore,
+    image_store: &ImageAssetStore,
+    kind: &EnemyKind,
+    tile_pos: TilePos,
+    level: usize,
+    enemy_timing: f32,
+) -> Entity {
+    let def = kind.enemy_type().def();
+    let atlas_handle = atlases.get(&def.atlas);
+    let mut entity_commands = match def.sprite {
+        EnemySprite::Directional => commands.spawn_bundle(
+            TileResidentBundle::new(def.hp, tile_pos, atlas_handle, 0).with_timing(enemy_timing),
+        ),
+        EnemySprite::Simple { frames, frame_secs } => commands.spawn_bundle(
+            SimpleTileResidentBundle::new(
+                def.hp,
                 tile_pos,
-                atlas_handle.clone(),
-                4,
-                Some(Timer::from_seconds(0.2, true)),
-            ))
-            .insert(Enemy {
-                can_attack_directly: false,
-            })
-            .insert(CanMoveDistance::updown_leftright(1, 1))
-            .insert(MoveWeighting::updown_leftright(1.0, 1.0))
-            .insert(Jellyfish::default());
+                atlas_handle,
+                frames,
+                Some(Timer::from_seconds(frame_secs, true)),
+            )
+            .with_timing(enemy_timing),
+        ),
+    };
+    let (updown_distance, leftright_distance) = def.move_distance;
+    let (updown_weight, leftright_weight) = def.move_weighting;
+    entity_commands
+        .insert(Enemy {
+            can_attack_directly: def.can_attack_directly,
+        })
+        .insert(CanMoveDistance::updown_leftright(
+            updown_distance,
+            leftright_distance,
+        ))
+        .insert(MoveWeighting::updown_leftright(
+            updown_weight,
+            leftright_weight,
+        ))
+        .insert(MoveIntention::default())
+        .insert(ShellDropChance(def.shell_drop_chance));
+    if let Some(damage) = def.damage_at(level) {
+        entity_commands.insert(AttackDamage(damage));
     }
-    spawn_positions
+    if let Some(laziness) = def.laziness {
+        entity_commands.insert(Laziness(laziness));
+    }
+    if let Some(drawn_facing) = &def.flip_for_facing {
+        entity_commands.insert(FlipForFacing {
+            drawn_facing: drawn_facing.clone(),
+        });
+    }
+
+    match kind {
+        EnemyKind::Shark => {
+            entity_commands.insert(Shark);
+        }
+        EnemyKind::Crab { armoured } => {
+            entity_commands.insert(Crab);
+            if *armoured {
+                entity_commands
+                    .insert(ProjectileImmune)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(SpriteBundle {
+                            texture: image_store.get(&ImageAsset::CrabShell),
+                            transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                            ..Default::default()
+                        });
+                    });
+            }
+        }
+        EnemyKind::Jellyfish { poisonous } => {
+            if *poisonous {
+                entity_commands.insert(TextureAtlasSprite {
+                    color: Poison::TINT,
+                    ..Default::default()
+                });
+            }
+            entity_commands.insert(Jellyfish {
+                poisonous: *poisonous,
+                ..Default::default()
+            });
+        }
+        EnemyKind::Phantom => {
+            entity_commands
+                .insert(TextureAtlasSprite {
+                    color: Phantom::TINT,
+                    ..Default::default()
+                })
+                .insert(Phantom);
+        }
+        EnemyKind::Barnacle => {
+            entity_commands
+                .insert(TextureAtlasSprite {
+                    color: Barnacle::TINT,
+                    ..Default::default()
+                })
+                .insert(Barnacle);
+        }
+    }
+    entity_commands.id()
 }
 
 pub fn jelly_lightning_projection(
@@ -153,6 +565,7 @@ pub fn jelly_lightning_projection(
         tile_storage_query,
         tiletype_query,
         false,
+        None,
     );
     let (final_tilepos, hit_player) = match projectile_fate {
         ProjectileFate::EndNoTarget(last_tile_pos) => (last_tile_pos, None),
@@ -205,14 +618,133 @@ pub fn spawn_jelly_lightning(
 }
 
 impl Jellyfish {
-    pub const CHARGE_CHANCE: f64 = 0.5;
-    pub const RECHARGE_TURNS: usize = 1;
+    pub const POISONOUS_CHANCE: f64 = 0.3;
 
-    pub fn can_move(&self) -> bool {
+    pub fn poison(&self) -> Poison {
+        Poison {
+            turns: 3,
+            dmg_per_turn: 1,
+        }
+    }
+
+    /// [recharge_turns] as in [crate::game::balance::JellyfishBalance]
+    pub fn can_move(&self, recharge_turns: usize) -> bool {
         match self.state {
             JellyfishState::Normal => true,
-            JellyfishState::Recharging(remaining) => remaining < Jellyfish::RECHARGE_TURNS,
+            JellyfishState::Recharging(remaining) => remaining < recharge_turns,
             JellyfishState::Charging(_) => false,
         }
     }
 }
+
+pub fn spawn_intention_arrow(
+    parent: &mut ChildBuilder,
+    image_store: &ImageAssetStore,
+    direction: &MapDirection,
+) {
+    // Relative to the enemy, pushed toward the edge of its tile in the direction of the move
+    let mut transform = Transform::from_translation(direction.to_unit_translation() * 24.0);
+    transform.translation.z = 1.0;
+    transform.rotate(Quat::from_rotation_z(
+        direction.to_rotation_from_right_zero(),
+    ));
+    parent
+        .spawn_bundle(SpriteBundle {
+            texture: image_store.get(&ImageAsset::IntentionArrow),
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                ..Default::default()
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(IntentionArrow);
+}
+
+/// Only shown for enemies that can take more than one hit, a single pip says nothing useful
+pub fn spawn_health_pips(parent: &mut ChildBuilder, image_store: &ImageAssetStore, hp: usize) {
+    if hp <= 1 {
+        return;
+    }
+    let pip_size = 12.0;
+    // Relative to the enemy, centred along the top edge of its tile
+    let first_x = -pip_size * (hp - 1) as f32 / 2.0;
+    parent
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+            first_x, 26.0, 1.0,
+        )))
+        .insert(HealthPips)
+        .with_children(|parent| {
+            for i in 0..hp {
+                parent.spawn_bundle(SpriteBundle {
+                    texture: image_store.get(&ImageAsset::UiHealthFull),
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(pip_size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(pip_size * i as f32, 0.0, 0.0),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::{spawn_table, EnemyType, ENEMY_DEFS, SPAWN_TABLE};
+
+    #[test]
+    fn test_every_enemy_type_has_one_def() {
+        for enemy_type in [
+            EnemyType::Shark,
+            EnemyType::Crab,
+            EnemyType::Jellyfish,
+            EnemyType::Phantom,
+            EnemyType::Barnacle,
+        ] {
+            let defs = ENEMY_DEFS
+                .iter()
+                .filter(|def| def.enemy_type == enemy_type)
+                .count();
+            assert_eq!(1, defs, "{:?}", enemy_type);
+        }
+    }
+
+    #[test]
+    fn test_damage_at_level() {
+        let shark = EnemyType::Shark.def();
+        assert_eq!(Some(1), shark.damage_at(0));
+        assert_eq!(Some(1), shark.damage_at(4));
+        assert_eq!(Some(2), shark.damage_at(5));
+        assert_eq!(Some(2), shark.damage_at(50));
+        assert_eq!(None, EnemyType::Jellyfish.def().damage_at(10));
+    }
+
+    #[test]
+    fn test_spawn_table() {
+        let counts = |level| -> Vec<(EnemyType, usize)> {
+            spawn_table(level)
+                .into_iter()
+                .map(|(def, count)| (def.enemy_type, count))
+                .collect()
+        };
+        assert_eq!(vec![(EnemyType::Crab, 6), (EnemyType::Shark, 4)], counts(1));
+        assert_eq!(
+            vec![
+                (EnemyType::Shark, 7),
+                (EnemyType::Crab, 3),
+                (EnemyType::Jellyfish, 1),
+                (EnemyType::Phantom, 1),
+                (EnemyType::Barnacle, 2),
+            ],
+            counts(20)
+        );
+        for entry in SPAWN_TABLE.iter() {
+            let total: usize = spawn_table(entry.min_level)
+                .iter()
+                .map(|(_, count)| count)
+                .sum();
+            assert_eq!(entry.total, total);
+        }
+    }
+}
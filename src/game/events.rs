@@ -1,5 +1,6 @@
 use crate::game::components::MapDirection;
 use crate::game::turn::GamePhase;
+use bevy_ecs_tilemap::tiles::TilePos;
 
 #[derive(Debug)]
 pub enum InputEvent {
@@ -7,12 +8,52 @@ pub enum InputEvent {
     TurnDirection(MapDirection),
     Wait,
     Power,
+    CancelPower,
+    /// Fires the charged power, built up over the given number of turns
+    ChargedPower(usize),
     Hook,
 }
 
+impl InputEvent {
+    /// Whether the input is a turn action (moving, turning, waiting or using the power) rather
+    /// than something like cancelling aim or calling the hook
+    pub fn is_action(&self) -> bool {
+        match self {
+            Self::MoveDirection(_)
+            | Self::TurnDirection(_)
+            | Self::Wait
+            | Self::Power
+            | Self::ChargedPower(_) => true,
+            Self::CancelPower | Self::Hook => false,
+        }
+    }
+}
+
+/// Filters a batch of inputs read together down to at most one action, the first, so mashing
+/// keys can't queue several up for the same turn. Anything that isn't an action is kept.
+/// Returns the kept inputs, in order, and how many actions were dropped
+pub fn first_action_only<'a>(
+    events: impl IntoIterator<Item = &'a InputEvent>,
+) -> (Vec<&'a InputEvent>, usize) {
+    let mut kept = Vec::new();
+    let mut has_action = false;
+    let mut dropped = 0;
+    for event in events {
+        if event.is_action() {
+            if has_action {
+                dropped += 1;
+                continue;
+            }
+            has_action = true;
+        }
+        kept.push(event);
+    }
+    (kept, dropped)
+}
+
 #[derive(Debug)]
 pub enum PowerEvent {
-    PowerFired,
+    PowerFired { turns_charged: usize },
 }
 
 #[derive(Debug)]
@@ -25,14 +66,71 @@ pub enum GameEvent {
     VortexCompleted,
 }
 
+/// Whether the player had a hand in an enemy dying, see [super::pacifist::PlayerStrikes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillSource {
+    Player,
+    Other,
+}
+
 #[derive(Debug)]
 pub enum InfoEvent {
     // Events specifically for info and not necessarily drive systems
-    EnemyKilled,
+    EnemyKilled(KillSource),
     PlayerHurt,
     PlayerMoved,
     PlayerKilled,
     PlayerPickedUpSnail,
-    JellyLightningFired,
+    PlayerPickedUpItem,
+    /// From the jellyfish at the given position
+    JellyLightningFired(TilePos),
     VortexSpawned,
+    /// A starfish was used up in place of the player dying
+    SecondChanceUsed,
+    /// The player was left on 1 hp in place of dying, see [super::game::LastStand]
+    LastStandUsed,
+    /// The shells collected this run were banked at a deposit chest
+    ShellsDeposited,
+    /// Map generation failed and the level was set up on the hand-built fallback map
+    FallbackMapUsed,
+    /// The level's objective was met, see [super::objective::Objective]
+    ObjectiveCompleted,
+    /// The power was used with no charges left, so nothing happened
+    PowerUnavailable,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{first_action_only, InputEvent};
+    use crate::game::components::MapDirection;
+
+    #[test]
+    fn test_two_moves_in_one_batch_make_one_move() {
+        let events = vec![
+            InputEvent::MoveDirection(MapDirection::Up),
+            InputEvent::MoveDirection(MapDirection::Left),
+        ];
+        let (kept, dropped) = first_action_only(&events);
+        assert_eq!(1, dropped);
+        assert_eq!(1, kept.len());
+        assert!(matches!(
+            kept[0],
+            InputEvent::MoveDirection(MapDirection::Up)
+        ));
+    }
+
+    #[test]
+    fn test_non_actions_are_kept() {
+        let events = vec![
+            InputEvent::CancelPower,
+            InputEvent::Wait,
+            InputEvent::Hook,
+            InputEvent::Power,
+        ];
+        let (kept, dropped) = first_action_only(&events);
+        assert_eq!(1, dropped);
+        assert_eq!(3, kept.len());
+        assert!(matches!(kept[1], InputEvent::Wait));
+        assert!(matches!(kept[2], InputEvent::Hook));
+    }
 }
@@ -2,23 +2,45 @@ use super::{
     components::*,
     enemy::Enemy,
     events::{GameEvent, InputEvent},
-    tilemap::{HasTileType, TilePosExt, TileStorageQuery},
+    game_time::{GameTime, GameTimePlugin, HitStopSettings},
+    tilemap::{HasCurrent, HasTileType, TilePosExt, TileStorageQuery},
     timed_removal::{TimedRemoval, TimedRemovalPlugin},
     turn::{GamePhase, GlobalTurnCounter, TurnCounter},
 };
 use crate::asset_handling::asset::{AudioAsset, TextureAtlasAsset};
 use crate::asset_handling::{AudioAssetStore, ImageAssetStore, TextureAtlasStore};
-use crate::game::end_game::{EndGameHook, EndGameVortex, InHook, InVortex, VortexSpawnEvent};
-use crate::game::enemy::{Jellyfish, JellyfishLightningTile, JellyfishState};
+use crate::game::balance::BalanceConfig;
+use crate::game::compass::CompassPlugin;
+use crate::game::end_game::{
+    EndGameHook, EndGameVortex, InHook, InVortex, VortexSpawnEvent, VortexSpawnStatus,
+};
+use crate::game::enemy::{
+    AttackDamage, Barnacle, EnemyType, HealthPips, IntentionArrow, Jellyfish,
+    JellyfishLightningTile, JellyfishState, Laziness, MoveIntention, Phantom, ProjectileImmune,
+    ShellDropChance,
+};
 use crate::game::events::{InfoEvent, PowerEvent};
-use crate::game::movement::{AttackCriteria, MoveDecisions};
-use crate::game::projectile::Projectile;
+use crate::game::ghost_path::GhostPathPlugin;
+use crate::game::key_bindings::KeyBindings;
+use crate::game::lighting::LightingPlugin;
+use crate::game::map_gen_replay::{MapGenReplay, MapGenReplayPlugin, PendingCellMap};
+use crate::game::movement::{AttackCriteria, EnemyPhaseQueue, MoveDecision, Occupancy};
+use crate::game::objective::{LevelObjective, ObjectivePlugin};
+use crate::game::pacifist::{PacifistPlugin, PlayerStrikes};
+use crate::game::projectile::{Passthrough, PowerAimingIndicator, Projectile, ProjectileFate};
+use crate::game::relax_mode::{RelaxMode, RelaxModePlugin};
+use crate::game::run_timer::{RunTimer, RunTimerPlugin};
+use crate::game::sonar::SonarPlugin;
+use crate::game::sudden_death::{Pursuer, SuddenDeathPlugin};
 use crate::game::turn::GlobalLevelCounter;
+use crate::game::turn_review::{TurnHistory, TurnReview, TurnReviewPlugin};
 use crate::game::ui::GameUiPlugin;
+use crate::game::virtual_cursor::VirtualCursorPlugin;
 use crate::helpers::cleanup::recursive_cleanup;
 use crate::helpers::error_handling::ResultOkLog;
 use crate::map_gen::cell_map::CellMap;
-use crate::profiles::profiles::LoadedUserProfile;
+use crate::profiles::profiles::{LevelPerformance, LoadedUserProfile, UserProfile};
+use crate::profiles::settings::{Settings, StartPlacement};
 
 use bevy::prelude::*;
 use bevy::reflect::Map;
@@ -35,59 +57,21 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         let state = crate::CoreState::GameLevel;
-        app.add_system_set(SystemSet::on_enter(state).with_system(setup))
+        app.add_system_set(SystemSet::on_enter(state).with_system(setup.label("level_setup")))
             .add_system_set(
-                SystemSet::on_update(state)
-                    .with_system(animate_sprite_system)
-                    .with_system(simple_animate_sprite_system)
+                turn_system_set(state)
                     .with_system(input_handle_system.label("input"))
                     .with_system(mouse_click_system.label("input"))
+                    .with_system(mouse_action_system.after("input").before("player_movement"))
                     .with_system(gamepad_input_handle_system.label("input"))
-                    .with_system(player_power_system)
-                    .with_system(player_movement_system.label("player_movement"))
-                    .with_system(camera_follow_system.after("player_movement"))
-                    .with_system(camera_follow_update_system)
-                    .with_system(player_movement_watcher.after("player_movement"))
-                    .with_system(
-                        (super::projectile::phase_watcher_system::<Projectile>)
-                            .label("post_player_movement")
-                            .after("player_movement"),
-                    )
-                    .with_system(
-                        jellyfish_system
-                            .after("post_player_movement")
-                            .label("pre_enemy_movement"),
-                    )
-                    .with_system(
-                        (super::projectile::phase_watcher_system::<JellyfishLightningTile>)
-                            .label("post_pre_enemy_movement")
-                            .after("pre_enemy_movement"),
-                    )
-                    .with_system(
-                        enemy_system
-                            .label("enemy_movement")
-                            .after("post_pre_enemy_movement"),
-                    )
-                    .with_system(animate_move_system.after("enemy_movement"))
-                    .with_system(global_turn_counter_system.after("enemy_movement"))
-                    .with_system(health_watcher_system.after("enemy_movement"))
-                    .with_system(player_damaged_effect_system.after("enemy_movement"))
-                    .with_system(player_death_animation_system.after("enemy_movement"))
+                    .with_system(power_aiming_indicator_system.after("player_movement"))
+                    .with_system(power_charge_system.after("input").before("player_movement"))
+                    .with_system(auto_aim_system.before("player_movement"))
                     .with_system(sfx_system)
-                    .with_system(waggle_system)
-                    .with_system(rotate_system)
-                    .with_system(shrinking_system)
+                    .with_system(super::rumble::rumble_system)
                     .with_system(end_of_game_watcher_system)
-                    .with_system(vortex_spawner_system)
-                    .with_system(vortex_spawn_trigger_system)
                     .with_system(end_of_level_event_system)
-                    .with_system(regular_game_enable_watcher)
-                    .with_system(super::end_game::end_game_hook_system)
-                    .with_system(super::end_game::end_game_vortex_system)
-                    .with_system(super::end_game::hooked_animation_system)
-                    .with_system(super::end_game::vortex_animation_system)
-                    .with_system(super::projectile::projectile_system)
-                    .with_system(super::snails::snail_pickup_system),
+                    .with_system(super::pickups::deposit_chest_system),
             )
             .add_system_set(
                 SystemSet::on_exit(state)
@@ -104,17 +88,44 @@ impl Plugin for GamePlugin {
                     .with_system(game_level_transition),
             )
             .add_plugin(TimedRemovalPlugin)
+            .add_plugin(GameTimePlugin)
+            .add_plugin(RunTimerPlugin)
+            .add_plugin(GhostPathPlugin)
+            .add_plugin(LightingPlugin)
+            .add_plugin(ObjectivePlugin)
+            .add_plugin(SuddenDeathPlugin)
+            .add_plugin(CompassPlugin)
+            .add_plugin(SonarPlugin)
+            .add_plugin(TurnReviewPlugin)
+            .add_plugin(RelaxModePlugin)
+            .add_plugin(VirtualCursorPlugin)
+            .add_plugin(PacifistPlugin)
+            .add_plugin(MapGenReplayPlugin)
             .add_plugin(GameUiPlugin)
             .add_plugin(super::debug::GameDebugPlugin)
+            .add_plugin(super::demo::DemoPlugin)
             .add_event::<super::events::GameEvent>()
             .add_event::<super::events::InputEvent>()
             .add_event::<super::events::InfoEvent>()
             .add_event::<super::events::PowerEvent>()
             .add_event::<MouseClickEvent>()
             .add_event::<VortexSpawnEvent>()
+            .insert_resource(BalanceConfig::load())
             .insert_resource(GlobalTurnCounter::default())
+            .insert_resource(KeyBindings::default())
+            .add_system(super::key_bindings::key_bindings_sync_system)
+            .insert_resource(PowerChargesThisRun::default())
+            .insert_resource(LastStand::default())
+            .insert_resource(RunMode::default())
+            .insert_resource(MapSeed::default())
             .insert_resource(GlobalLevelCounter::default())
             .insert_resource(SnailsCollectedThisRun(0_usize))
+            .insert_resource(TurnsThisRun::default())
+            .insert_resource(DamageTakenThisLevel::default())
+            .insert_resource(PowerAiming::default())
+            .insert_resource(CameraPan::default())
+            .insert_resource(EnemyPhaseQueue::default())
+            .insert_resource(LevelTransition::default())
             .insert_resource(RegularGameEnable {
                 enabled: false,
                 disable_cycle_count: 1,
@@ -122,6 +133,102 @@ impl Plugin for GamePlugin {
     }
 }
 
+/// The systems that play out a level once the player's input is in: movement, enemies, effects
+/// and animation. Shared by the real game and the demo, which add their own input on top
+pub fn turn_system_set(state: crate::CoreState) -> SystemSet {
+    SystemSet::on_update(state)
+        .with_system(animate_sprite_system)
+        .with_system(simple_animate_sprite_system)
+        .with_system(flip_for_facing_system)
+        .with_system(player_power_system)
+        .with_system(player_movement_system.label("player_movement"))
+        .with_system(
+            camera_follow_system
+                .label("camera_follow")
+                .after("player_movement"),
+        )
+        .with_system(camera_follow_update_system)
+        .with_system(camera_follow_mode_system)
+        .with_system(camera_pan_trigger_system)
+        .with_system(
+            camera_pan_system
+                .label("camera_pan")
+                .after("player_movement"),
+        )
+        .with_system(camera_shake_trigger_system)
+        .with_system(
+            camera_shake_reset_system
+                .before("camera_follow")
+                .before("camera_pan"),
+        )
+        .with_system(
+            camera_shake_system
+                .after("camera_follow")
+                .after("camera_pan"),
+        )
+        .with_system(player_movement_watcher.after("player_movement"))
+        .with_system(stray_resident_system)
+        .with_system(
+            (super::projectile::phase_watcher_system::<Projectile>)
+                .label("post_player_movement")
+                .after("player_movement"),
+        )
+        .with_system(
+            jellyfish_system
+                .after("post_player_movement")
+                .label("pre_enemy_movement"),
+        )
+        .with_system(
+            (super::projectile::phase_watcher_system::<JellyfishLightningTile>)
+                .label("post_pre_enemy_movement")
+                .after("pre_enemy_movement"),
+        )
+        .with_system(
+            enemy_system
+                .label("enemy_movement")
+                .after("post_pre_enemy_movement"),
+        )
+        .with_system(animate_move_system.after("enemy_movement"))
+        .with_system(global_turn_counter_system.after("enemy_movement"))
+        .with_system(health_watcher_system.after("enemy_movement"))
+        .with_system(poison_system.before("player_movement"))
+        .with_system(last_stand_charge_system.before("player_movement"))
+        .with_system(current_system.before("player_movement"))
+        .with_system(super::tilemap::current_arrow_system)
+        .with_system(invulnerability_system.before("player_movement"))
+        .with_system(enemy_intention_system.before("player_movement"))
+        .with_system(intention_arrow_system)
+        .with_system(health_pips_system)
+        .with_system(poison_tint_system)
+        .with_system(player_damaged_effect_system.after("enemy_movement"))
+        .with_system(player_death_animation_system.after("enemy_movement"))
+        .with_system(waggle_system)
+        .with_system(rotate_system)
+        .with_system(growing_system)
+        .with_system(vortex_spawner_system)
+        .with_system(vortex_spawn_trigger_system)
+        .with_system(regular_game_enable_watcher)
+        .with_system(super::end_game::end_game_hook_system)
+        .with_system(super::end_game::end_game_vortex_system)
+        .with_system(super::end_game::hooked_animation_system)
+        .with_system(super::end_game::vortex_animation_system)
+        .with_system(super::projectile::projectile_system)
+        .with_system(super::projectile::projectile_trail_fade_system)
+        .with_system(super::snails::snail_pickup_system)
+        .with_system(super::snails::dropped_shell_pickup_system)
+        .with_system(super::pickups::pickup_system)
+}
+
+pub const MAP_MIN_SIZE: usize = 50;
+pub const MAP_MAX_TRIES: i32 = 50;
+/// Further rounds of generation, each halving the minimum size, before giving up and using the
+/// fallback map
+const MAP_RELAXED_RETRIES: usize = 3;
+/// Wall tiles around the generated map
+pub const MAP_BORDER_SIZE: usize = 20;
+/// Enemies (and anything else spawned at level start) keep at least this far from the player
+const SPAWN_PROTECTION_RADIUS: i32 = 3;
+
 /// Resource to indicate regular game process. Serves to be disabled at edges like when animating
 /// end of game so some things can skip processing or ignore changes
 pub struct RegularGameEnable {
@@ -133,6 +240,112 @@ pub struct RegularGameEnable {
 /// exiting (via death or hook)
 pub struct SnailsCollectedThisRun(pub usize);
 
+/// Resource accumulating turns taken across all levels of the current run, as the
+/// [GlobalTurnCounter] only covers the current level
+#[derive(Debug, Default)]
+pub struct TurnsThisRun(pub usize);
+
+/// Resource adding up the hp the player has lost this level, for its [LevelPerformance]. Kept as
+/// the hits land, so healing afterwards doesn't take any back
+#[derive(Debug, Default)]
+pub struct DamageTakenThisLevel(pub usize);
+
+impl TurnsThisRun {
+    pub fn add_level(&mut self, global_turn_counter: &GlobalTurnCounter) {
+        // The counter starts on turn 1, so has completed one fewer than its count
+        self.0 += global_turn_counter.turn_count.saturating_sub(1);
+    }
+}
+
+/// Resource carrying the player's power charges from one level to the next, as the player is
+/// respawned with each level. [None] at the start of a run, meaning full charges
+#[derive(Debug, Default)]
+pub struct PowerChargesThisRun(pub Option<usize>);
+
+/// Resource for whether the run's last stand has been used up, see [Settings::last_stand]. Reset
+/// at the start of each run
+#[derive(Debug, Default)]
+pub struct LastStand {
+    pub used: bool,
+}
+
+/// Power charges given back on clearing a level, up to the profile's max
+const POWER_CHARGES_REFILLED_ON_CLEAR: usize = 1;
+
+impl PowerChargesThisRun {
+    pub fn bank_level(&mut self, charges: usize, max_charges: usize) {
+        self.0 = Some((charges + POWER_CHARGES_REFILLED_ON_CLEAR).min(max_charges));
+    }
+}
+
+/// Resource for the kind of run being played, chosen from the hub
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    Normal,
+    /// No hook to bank shells and leave, just levels to survive for as long as possible. Shells
+    /// are banked as each level is cleared instead, so dying only loses the current level's
+    Endless,
+}
+
+impl Default for RunMode {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// Resource for the seed a run's map layouts are generated from, each level's map being seeded
+/// from it and the level number. Only the layout is seeded, what spawns on it is still random
+#[derive(Debug, Default)]
+pub struct MapSeed {
+    /// Seed of the run in progress, or of the last run once it's over
+    pub seed: Option<u64>,
+    /// When set, the next run uses this seed rather than rolling a new one
+    pub retry: Option<u64>,
+}
+
+impl MapSeed {
+    /// Picks the seed for a new run, using up [MapSeed::retry] if it's set
+    fn start_run(&mut self) -> u64 {
+        let seed = self
+            .retry
+            .take()
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        info!("Run seed: {}", seed);
+        self.seed = Some(seed);
+        seed
+    }
+
+    fn level_seed(run_seed: u64, level: usize) -> u64 {
+        // Any fixed mixing will do, it just keeps neighbouring levels from looking related
+        run_seed ^ (level as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+/// Resource for the optional two-step power. With [Settings::power_aiming] on, the first power
+/// input enters aiming (showing the target line) and a second fires. Off by default, meaning
+/// instant-fire
+#[derive(Debug, Default)]
+pub struct PowerAiming {
+    pub aiming: bool,
+}
+
+/// How long after a move that would attack the same move must be pressed again to confirm it,
+/// when [Settings::confirm_attacks] is on
+const ATTACK_CONFIRM_WINDOW_SECS: f64 = 1.0;
+
+/// An attacking move awaiting its confirming second press
+#[derive(Debug)]
+struct PendingAttack {
+    direction: MapDirection,
+    requested_at: f64,
+}
+
+impl PendingAttack {
+    fn confirmed_by(&self, direction: &MapDirection, now: f64) -> bool {
+        self.direction == *direction && now - self.requested_at <= ATTACK_CONFIRM_WINDOW_SECS
+    }
+}
+
 fn regular_game_enable_watcher(mut regular_game_enable: ResMut<RegularGameEnable>) {
     if regular_game_enable.disable_cycle_count > 0 {
         regular_game_enable.disable_cycle_count -= 1;
@@ -142,24 +355,88 @@ fn regular_game_enable_watcher(mut regular_game_enable: ResMut<RegularGameEnable
     }
 }
 
-fn state_cleanup(mut global_turn_counter: ResMut<GlobalTurnCounter>) {
+pub fn state_cleanup(
+    mut global_turn_counter: ResMut<GlobalTurnCounter>,
+    mut power_aiming: ResMut<PowerAiming>,
+    mut camera_pan: ResMut<CameraPan>,
+    mut enemy_phase_queue: ResMut<EnemyPhaseQueue>,
+    mut damage_taken_this_level: ResMut<DamageTakenThisLevel>,
+    mut camera_query: Query<(&mut Transform, &mut CameraShake)>,
+) {
     global_turn_counter.reset();
+    damage_taken_this_level.0 = 0;
+    power_aiming.aiming = false;
+    camera_pan.0 = None;
+    // The camera outlives the level, so it mustn't carry a shake over into menus or the next one
+    for (mut transform, mut shake) in camera_query.iter_mut() {
+        transform.translation.x -= shake.offset.x;
+        transform.translation.y -= shake.offset.y;
+        shake.offset = Vec2::ZERO;
+        shake.trauma = 0.0;
+    }
+    // Drops a phase left part way through a batch
+    *enemy_phase_queue = EnemyPhaseQueue::default();
 }
 
-fn game_level_transition_enter(mut global_level_counter: ResMut<GlobalLevelCounter>) {
+/// Resource timing the pause between one level and the next, which lasts
+/// [super::balance::LevelTransitionBalance::duration_ms] but never fewer than
+/// [LevelTransition::MIN_TICKS] frames
+#[derive(Default)]
+pub struct LevelTransition {
+    timer: Timer,
+    ticks: usize,
+}
+
+impl LevelTransition {
+    /// However short the duration, so the outgoing level's cleanup has been applied before the
+    /// next level is set up
+    const MIN_TICKS: usize = 2;
+
+    fn start(&mut self, duration: Duration) {
+        self.timer = Timer::new(duration, false);
+        self.ticks = 0;
+    }
+
+    /// Whether the transition is over
+    fn tick(&mut self, delta: Duration) -> bool {
+        self.ticks += 1;
+        let finished = self.timer.tick(delta).finished();
+        finished && self.ticks >= Self::MIN_TICKS
+    }
+}
+
+fn game_level_transition_enter(
+    mut global_level_counter: ResMut<GlobalLevelCounter>,
+    mut level_transition: ResMut<LevelTransition>,
+    balance: Res<BalanceConfig>,
+    loaded_profile: Res<LoadedUserProfile>,
+    settings: Res<Settings>,
+) {
     info!("Game Level Transition enter!");
+    // Saved here, after leaving the level, so the level's ghost path recorded on the way out is
+    // in it. Otherwise shells collected this run are only added to the profile in [end_of_run],
+    // so this never persists them before the run is actually over
+    if settings.autosave {
+        info!("Autosaving profile");
+        loaded_profile.save();
+    }
     global_level_counter.increment();
+    level_transition.start(Duration::from_millis(balance.level_transition.duration_ms));
 }
+
 fn game_level_transition(
     mut state: ResMut<State<crate::CoreState>>,
-    mut local_counter: Local<u32>,
+    mut level_transition: ResMut<LevelTransition>,
+    time: Res<Time>,
+    maybe_replay: Option<Res<MapGenReplay>>,
 ) {
     info!("Game Level Transition!\nState:{:?}", state);
-    if *local_counter > 1 {
+    // The transition only starts counting down once any replay has finished
+    if maybe_replay.map_or(false, |replay| !replay.finished()) {
+        return;
+    }
+    if level_transition.tick(time.delta()) {
         state.set(crate::CoreState::GameLevel).unwrap();
-        *local_counter = 0;
-    } else {
-        *local_counter += 1;
     }
 }
 
@@ -195,14 +472,52 @@ fn end_of_run(
     died: bool,
     global_level_counter: &mut GlobalLevelCounter,
     snail_shells_collected_this_run: &mut SnailsCollectedThisRun,
+    turns_this_run: &mut TurnsThisRun,
+    global_turn_counter: &GlobalTurnCounter,
+    run_timer: &mut RunTimer,
+    power_charges_this_run: &mut PowerChargesThisRun,
+    run_mode: RunMode,
     loaded_profile: &mut LoadedUserProfile,
+    settings: &Settings,
+    relax_mode: &RelaxMode,
 ) {
+    if run_mode == RunMode::Endless {
+        let streak = global_level_counter.levels_cleared();
+        if streak > loaded_profile.user_profile.best_endless_streak {
+            info!("New best endless streak: {} levels", streak);
+            loaded_profile.user_profile.best_endless_streak = streak;
+        }
+    }
+    if global_level_counter.level() > loaded_profile.user_profile.best_level_reached {
+        info!("New best level reached: {}", global_level_counter.level());
+        loaded_profile.user_profile.best_level_reached = global_level_counter.level();
+    }
     global_level_counter.reset();
+    power_charges_this_run.0 = None;
+
+    turns_this_run.add_level(global_turn_counter);
+    let user_profile = &mut loaded_profile.user_profile;
+    if turns_this_run.0 > user_profile.longest_run_turns {
+        info!("New longest run: {} turns", turns_this_run.0);
+        user_profile.longest_run_turns = turns_this_run.0;
+    }
+    turns_this_run.0 = 0;
 
-    if !died {
-        //Only get to keep eggs if didn't die
-        loaded_profile.user_profile.snail_shells += snail_shells_collected_this_run.0
+    run_timer.stop();
+    // Only runs that got hooked out count as completed
+    let is_best_time = user_profile
+        .best_run_time
+        .map_or(true, |best| run_timer.elapsed < best);
+    if !died && is_best_time {
+        info!("New best run time: {:?}", run_timer.elapsed);
+        user_profile.best_run_time = Some(run_timer.elapsed);
     }
+    run_timer.reset();
+
+    // Dying loses the run's shells, or only some of them with [Settings::death_keep_fraction],
+    // unless in [RelaxMode]
+    loaded_profile.user_profile.snail_shells +=
+        relax_mode.shells_banked(settings, snail_shells_collected_this_run.0, died);
     snail_shells_collected_this_run.0 = 0;
     set_state_handle_error(state, crate::CoreState::GameHub);
 }
@@ -212,25 +527,78 @@ fn end_of_level_event_system(
     mut game_event_reader: EventReader<GameEvent>,
     mut global_level_counter: ResMut<GlobalLevelCounter>,
     mut snails_collected_this_run: ResMut<SnailsCollectedThisRun>,
+    mut turns_this_run: ResMut<TurnsThisRun>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut run_timer: ResMut<RunTimer>,
+    mut power_charges_this_run: ResMut<PowerChargesThisRun>,
+    player_query: Query<&PowerCharges, With<Player>>,
+    damage_taken_this_level: Res<DamageTakenThisLevel>,
+    run_mode: Res<RunMode>,
     mut loaded_profile: ResMut<LoadedUserProfile>,
+    settings: Res<Settings>,
+    relax_mode: Res<RelaxMode>,
 ) {
     for event in game_event_reader.iter() {
+        let died = match event {
+            GameEvent::HookCompleted | GameEvent::VortexCompleted => Some(false),
+            GameEvent::PlayerDied => Some(true),
+            _ => None,
+        };
+        // Recorded before the level's turns and player are cleared away below
+        if let Some(died) = died {
+            loaded_profile
+                .user_profile
+                .performance
+                .record(LevelPerformance {
+                    damage_taken: damage_taken_this_level.0,
+                    died,
+                    turns: global_turn_counter.turn_count.saturating_sub(1),
+                });
+        }
         match event {
             GameEvent::HookCompleted => end_of_run(
                 &mut state,
                 false,
                 &mut global_level_counter,
                 &mut snails_collected_this_run,
+                &mut turns_this_run,
+                &global_turn_counter,
+                &mut run_timer,
+                &mut power_charges_this_run,
+                *run_mode,
                 &mut loaded_profile,
+                &settings,
+                &relax_mode,
             ),
             GameEvent::PlayerDied => end_of_run(
                 &mut state,
                 true,
                 &mut global_level_counter,
                 &mut snails_collected_this_run,
+                &mut turns_this_run,
+                &global_turn_counter,
+                &mut run_timer,
+                &mut power_charges_this_run,
+                *run_mode,
                 &mut loaded_profile,
+                &settings,
+                &relax_mode,
             ),
             GameEvent::VortexCompleted => {
+                // The turn counter is reset when leaving the level, so bank this level's turns
+                turns_this_run.add_level(&global_turn_counter);
+                // As is the player, so carry their remaining power over with a little refilled
+                if let Ok(power_charges) = player_query.get_single() {
+                    power_charges_this_run.bank_level(
+                        power_charges.charges,
+                        loaded_profile.user_profile.max_power_charges(),
+                    );
+                }
+                // Endless runs have no hook to bank shells with, so bank each level's here
+                if *run_mode == RunMode::Endless {
+                    loaded_profile.user_profile.snail_shells += snails_collected_this_run.0;
+                    snails_collected_this_run.0 = 0;
+                }
                 set_state_handle_error(&mut state, crate::CoreState::GameLevelTransition);
             }
             GameEvent::PlayerHooked
@@ -242,23 +610,24 @@ fn end_of_level_event_system(
 
 fn vortex_spawn_trigger_system(
     mut event_writer: EventWriter<VortexSpawnEvent>,
-    enemy_query: Query<Entity, With<Enemy>>,
+    // Barnacles only sit in the way, so they don't hold the vortex back
+    enemy_query: Query<Entity, (With<Enemy>, Without<Barnacle>)>,
     global_turn_counter: Res<GlobalTurnCounter>,
     existing_vortex_query: Query<Entity, With<EndGameVortex>>,
+    level_objective: Res<LevelObjective>,
+    balance: Res<BalanceConfig>,
+    relax_mode: Res<RelaxMode>,
 ) {
     let no_vortex_exists = existing_vortex_query.is_empty();
-    let ready_to_spawn = {
-        let enemy_count = enemy_query.iter().count();
-        let turn_past_threshold = global_turn_counter.turn_count > 34;
-        let not_too_many_enemies = enemy_count < 4;
-
-        // Late spawn is dependent on being many turns in and killed *some* enemies
-        let can_late_spawn = turn_past_threshold || not_too_many_enemies;
-
-        // Early spawn is if all enemies are killed. Turn count stops this accidentally triggering
-        // before enemies spawn at start
-        let can_early_spawn = enemy_count == 0 && global_turn_counter.turn_count > 2;
-        can_late_spawn || can_early_spawn
+    // A level with an objective opens its exit as soon as the objective is met, and not before
+    let ready_to_spawn = if level_objective.objective.is_some() {
+        level_objective.exit_unlocked()
+    } else {
+        super::end_game::vortex_spawn_status(
+            global_turn_counter.turn_count,
+            enemy_query.iter().count(),
+            &relax_mode.vortex_balance(&balance.vortex),
+        ) == VortexSpawnStatus::Ready
     };
     if ready_to_spawn && no_vortex_exists {
         event_writer.send(VortexSpawnEvent);
@@ -288,23 +657,54 @@ fn vortex_spawner_system(
             global_turn_counter.turn_count
         );
         let player_pos = player_query.single().as_i32s();
-        let new_cell_map = cell_map.recalculate(player_pos);
-        let spawn_pos = {
-            let (x, y) = new_cell_map
-                .distribute_points_by_cost(1, None)
-                .first()
-                .unwrap()
-                .to_owned();
-            TilePos {
-                x: x as u32,
-                y: y as u32,
-            }
-        };
+        let spawn_pos = exit_spawn_pos(&cell_map.recalculate(player_pos));
         super::end_game::spawn_vortex(&mut commands, spawn_pos, &image_store);
         info_event_writer.send(InfoEvent::VortexSpawned);
     }
 }
 
+/// Tiles the vortex and hook spawn at least this far from the player, so neither can be entered
+/// the same turn it appears
+const EXIT_SPAWN_MIN_DISTANCE: usize = 3;
+/// Picks made before giving up and using the farthest cell
+const EXIT_SPAWN_TRIES: usize = 5;
+
+/// Where the vortex or hook spawns, from a [CellMap] recalculated from the player's position.
+/// Picked towards the middle cost as usual, but re-picked if it lands too near the player
+fn exit_spawn_pos(cell_map_from_player: &CellMap<i32>) -> TilePos {
+    let to_tile_pos = |(x, y): (i32, i32)| TilePos {
+        x: x as u32,
+        y: y as u32,
+    };
+    // The recalculated map's cheapest cell is the one the player is on
+    let player_pos = cell_map_from_player.start_point().map(to_tile_pos);
+    let far_enough = |pos: &TilePos| {
+        player_pos.map_or(true, |player_pos| {
+            pos.distance_to(&player_pos) >= EXIT_SPAWN_MIN_DISTANCE
+        })
+    };
+    for _ in 0..EXIT_SPAWN_TRIES {
+        if let Some(pos) = cell_map_from_player
+            .distribute_points_by_cost(1, None)
+            .first()
+        {
+            let pos = to_tile_pos(*pos);
+            if far_enough(&pos) {
+                return pos;
+            }
+        }
+    }
+    let farthest = cell_map_from_player
+        .end_point()
+        .map(to_tile_pos)
+        .expect("A recalculated cell map holds at least the player's cell");
+    warn!(
+        "No exit spawn at least {} tiles from the player, using the farthest cell {:?}",
+        EXIT_SPAWN_MIN_DISTANCE, farthest
+    );
+    farthest
+}
+
 fn end_of_game_watcher_system(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
@@ -317,6 +717,7 @@ fn end_of_game_watcher_system(
     _enemy_query: Query<Entity, With<Enemy>>,
     global_turn_counter: Res<GlobalTurnCounter>,
     hook_query: Query<Entity, With<EndGameHook>>,
+    run_mode: Res<RunMode>,
 ) {
     let no_hook_exists = hook_query.is_empty();
     let end_of_game = {
@@ -328,24 +729,15 @@ fn end_of_game_watcher_system(
         }
         hook_input
     };
-    if end_of_game && no_hook_exists {
+    if end_of_game && *run_mode == RunMode::Endless {
+        info!("No hook in endless runs");
+    } else if end_of_game && no_hook_exists {
         info!(
             "Spawning end of game hook! Turn: {}",
             global_turn_counter.turn_count
         );
         let player_pos = player_query.single().as_i32s();
-        let new_cell_map = cell_map.recalculate(player_pos);
-        let spawn_pos = {
-            let (x, y) = new_cell_map
-                .distribute_points_by_cost(1, None)
-                .first()
-                .unwrap()
-                .to_owned();
-            TilePos {
-                x: x as u32,
-                y: y as u32,
-            }
-        };
+        let spawn_pos = exit_spawn_pos(&cell_map.recalculate(player_pos));
         super::end_game::spawn_hook(
             &mut meshes,
             &mut materials,
@@ -357,7 +749,7 @@ fn end_of_game_watcher_system(
     }
 }
 fn waggle_system(
-    time: Res<Time>,
+    time: Res<GameTime>,
     mut query: Query<(Entity, &mut Transform, &mut Waggle)>,
     mut commands: Commands,
 ) {
@@ -371,31 +763,44 @@ fn waggle_system(
     }
 }
 
-fn rotate_system(time: Res<Time>, mut query: Query<(&mut Transform, &mut Rotating)>) {
+fn rotate_system(time: Res<GameTime>, mut query: Query<(&mut Transform, &mut Rotating)>) {
     for (mut transform, mut rotating) in query.iter_mut() {
         rotating.update(&mut transform.rotation, &time.delta());
     }
 }
 
-fn shrinking_system(time: Res<Time>, mut query: Query<(&mut Transform, &Shrinking)>) {
-    for (mut transform, shrinking) in query.iter_mut() {
-        let new_scale = transform.scale - (shrinking.factor * time.delta_seconds());
-        transform.scale = new_scale.clamp(Vec3::ZERO, Vec3::ONE);
+fn growing_system(time: Res<GameTime>, mut query: Query<(&mut Transform, &Growing)>) {
+    for (mut transform, growing) in query.iter_mut() {
+        transform.scale += Vec3::splat(growing.factor * time.delta_seconds());
     }
 }
 
 fn player_death_animation_system(
     time: Res<Time>,
+    input: Res<Input<KeyCode>>,
     mut commands: Commands,
     mut query: Query<(Entity, &mut Transform, &mut PlayerDeathAnimation)>,
     mut event_writer: EventWriter<GameEvent>,
+    settings: Res<Settings>,
+    turn_history: Res<TurnHistory>,
 ) {
+    // Any key skips straight to the result. It still goes through the update below, so
+    // [GameEvent::PlayerDied] is sent exactly as if it had played out
+    let skip = input.get_just_pressed().next().is_some();
     for (entity, mut transform, mut player_death_animation) in query.iter_mut() {
+        if skip {
+            player_death_animation.skip();
+        }
         let finished = player_death_animation.update(&mut transform, &time.delta());
         println!("PlayerDeathAnim: {:?}", player_death_animation);
         if finished {
             commands.entity(entity).remove::<PlayerDeathAnimation>();
-            event_writer.send(GameEvent::PlayerDied);
+            // The review sends it instead once it's done with
+            if settings.death_review && !turn_history.is_empty() {
+                commands.insert_resource(TurnReview::default());
+            } else {
+                event_writer.send(GameEvent::PlayerDied);
+            }
         }
     }
 }
@@ -417,6 +822,16 @@ fn simple_animate_sprite_system(
     }
 }
 
+fn flip_for_facing_system(
+    mut query: Query<(&mut TextureAtlasSprite, &Facing, &FlipForFacing), Changed<Facing>>,
+) {
+    for (mut sprite, facing, flip_for_facing) in query.iter_mut() {
+        if let Some(flip_x) = flip_for_facing.flip_x(&facing.0) {
+            sprite.flip_x = flip_x;
+        }
+    }
+}
+
 fn animate_sprite_system(
     time: Res<Time>,
     mut query: Query<(
@@ -447,7 +862,14 @@ fn animate_sprite_system(
     }
 }
 
-fn animate_move_system(mut query: Query<(&mut Transform, &mut MovementAnimate)>) {
+fn animate_move_system(
+    game_time: Res<GameTime>,
+    mut query: Query<(&mut Transform, &mut MovementAnimate)>,
+) {
+    // Movement lerps per frame rather than by delta, so hold it in place while paused
+    if game_time.paused() {
+        return;
+    }
     for (mut transform, mut movement_animate) in query.iter_mut() {
         if movement_animate.active {
             transform.translation = movement_animate.lerp(&transform.translation);
@@ -465,10 +887,12 @@ fn camera_follow_system(
         Query<&mut Transform, With<GameCamera>>,
     )>,
     non_follow_query: Query<Entity, (With<Player>, Or<(With<InVortex>, With<InHook>)>)>,
+    camera_pan: Res<CameraPan>,
+    time: Res<Time>,
 ) {
     // Non_follow_query is populated by the player entity if they are ever in a state where
     // we wouldn't want to follow them
-    if non_follow_query.is_empty() {
+    if non_follow_query.is_empty() && camera_pan.0.is_none() {
         let pos = query
             .p0()
             .get_single()
@@ -479,23 +903,184 @@ fn camera_follow_system(
                     transform.translation.y,
                     follow.x_threshold,
                     follow.y_threshold,
+                    follow.mode,
                 )
             });
 
-        if let Some((x, y, x_threshold, y_threshold)) = pos {
+        if let Some((x, y, x_threshold, y_threshold, mode)) = pos {
             if let Some(mut camera_transform) = query.p1().get_single_mut().ok_log(code_location!())
             {
-                if (x - camera_transform.translation.x).abs() > x_threshold {
-                    camera_transform.translation.x = x
-                }
-                if (y - camera_transform.translation.y).abs() > y_threshold {
-                    camera_transform.translation.y = y
-                }
+                let delta_seconds = time.delta_seconds();
+                let translation = &mut camera_transform.translation;
+                translation.x =
+                    camera_follow_axis(mode, translation.x, x, x_threshold, delta_seconds);
+                translation.y =
+                    camera_follow_axis(mode, translation.y, y, y_threshold, delta_seconds);
             }
         }
     }
 }
 
+/// Resource for briefly taking the camera off the player to show an enemy action happening off
+/// screen, [None] while the camera follows the player as normal
+#[derive(Debug, Default)]
+pub struct CameraPan(Option<CameraPanStage>);
+
+#[derive(Debug)]
+enum CameraPanStage {
+    /// Moving to and lingering on the target until the timer is done
+    ToTarget(Vec2, Timer),
+    /// Heading back to the player, handing back to [camera_follow_system] on arrival
+    Returning,
+}
+
+/// How long the camera lingers on an enemy action before heading back
+const CAMERA_PAN_HOLD_SECS: f32 = 0.8;
+/// Fraction of the remaining distance the camera covers each frame while panning, at 60 frames a
+/// second
+const CAMERA_PAN_FACTOR: f32 = 0.15;
+
+/// Starts a [CameraPan] to enemy actions worth seeing that happen out of view, when
+/// [Settings::spectate_enemies] is on
+fn camera_pan_trigger_system(
+    mut info_event_reader: EventReader<InfoEvent>,
+    mut camera_pan: ResMut<CameraPan>,
+    camera_query: Query<&Transform, With<GameCamera>>,
+    windows: Res<Windows>,
+    settings: Res<Settings>,
+) {
+    for event in info_event_reader.iter() {
+        let target = match event {
+            InfoEvent::JellyLightningFired(tile_pos) => tile_pos.to_world_pos(0.0).truncate(),
+            _ => continue,
+        };
+        let (camera_transform, window) = match (camera_query.get_single(), windows.get_primary()) {
+            (Ok(camera_transform), Some(window)) => (camera_transform, window),
+            _ => continue,
+        };
+        let offset = (target - camera_transform.translation.truncate()).abs();
+        let on_screen = offset.x < window.width() / 2.0 && offset.y < window.height() / 2.0;
+        if settings.spectate_enemies && !on_screen {
+            debug!("Panning camera to off screen {:?}", event);
+            camera_pan.0 = Some(CameraPanStage::ToTarget(
+                target,
+                Timer::from_seconds(CAMERA_PAN_HOLD_SECS, false),
+            ));
+        }
+    }
+}
+
+fn camera_pan_system(
+    time: Res<Time>,
+    mut camera_pan: ResMut<CameraPan>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+    player_query: Query<&Transform, (With<CameraFollow>, Without<GameCamera>)>,
+) {
+    let mut camera_transform = match camera_query.get_single_mut() {
+        Ok(camera_transform) => camera_transform,
+        Err(_) => return,
+    };
+    let camera_pos = camera_transform.translation.truncate();
+    let (target, finished) = match &mut camera_pan.0 {
+        None => return,
+        Some(CameraPanStage::ToTarget(target, timer)) => {
+            timer.tick(time.delta());
+            (*target, timer.finished())
+        }
+        Some(CameraPanStage::Returning) => match player_query.get_single() {
+            Ok(player_transform) => {
+                let player_pos = player_transform.translation.truncate();
+                (player_pos, camera_pos.distance(player_pos) < 1.0)
+            }
+            // Nothing to return to, so leave the camera where it is
+            Err(_) => (camera_pos, true),
+        },
+    };
+    let new_pos = camera_pos.lerp(
+        target,
+        smoothing_fraction(CAMERA_PAN_FACTOR, time.delta_seconds()),
+    );
+    camera_transform.translation.x = new_pos.x;
+    camera_transform.translation.y = new_pos.y;
+    if finished {
+        camera_pan.0 = match camera_pan.0 {
+            Some(CameraPanStage::ToTarget(..)) => Some(CameraPanStage::Returning),
+            _ => None,
+        };
+    }
+}
+
+/// Trauma added to the [CameraShake] by each big event
+const VORTEX_SPAWN_TRAUMA: f32 = 0.4;
+const LAST_STAND_TRAUMA: f32 = 0.5;
+const PLAYER_KILLED_TRAUMA: f32 = 0.8;
+
+fn camera_shake_trigger_system(
+    mut info_event_reader: EventReader<InfoEvent>,
+    settings: Res<Settings>,
+    mut shake_query: Query<&mut CameraShake>,
+) {
+    for event in info_event_reader.iter() {
+        let trauma = match event {
+            InfoEvent::VortexSpawned => VORTEX_SPAWN_TRAUMA,
+            InfoEvent::LastStandUsed => LAST_STAND_TRAUMA,
+            InfoEvent::PlayerKilled => PLAYER_KILLED_TRAUMA,
+            _ => continue,
+        };
+        if !settings.screen_shake || settings.reduce_motion {
+            continue;
+        }
+        for mut shake in shake_query.iter_mut() {
+            shake.add_trauma(trauma);
+        }
+    }
+}
+
+/// Takes last frame's shake back off the camera, so following and panning move it from where it
+/// would be without any shake
+fn camera_shake_reset_system(mut camera_query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut shake) in camera_query.iter_mut() {
+        if shake.offset != Vec2::ZERO {
+            transform.translation.x -= shake.offset.x;
+            transform.translation.y -= shake.offset.y;
+            shake.offset = Vec2::ZERO;
+        }
+    }
+}
+
+/// Puts this frame's shake on top of wherever the camera's been moved to
+fn camera_shake_system(
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &mut CameraShake)>,
+) {
+    let mut rng = rand::thread_rng();
+    for (mut transform, mut shake) in camera_query.iter_mut() {
+        if shake.trauma <= 0.0 {
+            continue;
+        }
+        let noise = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+        shake.offset = shake.offset_for(noise);
+        shake.decay(time.delta_seconds());
+        transform.translation.x += shake.offset.x;
+        transform.translation.y += shake.offset.y;
+    }
+}
+
+/// Keeps every [CameraFollow] on [Settings::camera_follow_mode], including the player's as it's
+/// spawned with each level
+fn camera_follow_mode_system(
+    settings: Res<Settings>,
+    mut camera_follow_query: Query<(&mut CameraFollow, ChangeTrackers<CameraFollow>)>,
+) {
+    for (mut camera_follow, change_tracker) in camera_follow_query.iter_mut() {
+        if (settings.is_changed() || change_tracker.is_added())
+            && camera_follow.mode != settings.camera_follow_mode
+        {
+            camera_follow.mode = settings.camera_follow_mode;
+        }
+    }
+}
+
 fn camera_follow_update_system(
     mut camera_follow_query: Query<&mut CameraFollow>,
     mut window_resize_events: EventReader<WindowResized>,
@@ -531,53 +1116,118 @@ fn mouse_click_system(
 
     if let Some(mouse_button) = mouse_button {
         let window = windows.get_primary().unwrap();
-        if let Some(pos) = window.cursor_position() {
-            let size = Vec2::new(window.width() as f32, window.height() as f32);
-            // the default orthographic projection is in pixels from the center;
-            // just undo the translation
-            let pos = pos - size / 2.0;
-
-            if let Some(camera_transform) = camera_query.get_single().ok_log(code_location!()) {
-                // apply the camera transform
-                let world_position =
-                    camera_transform.compute_matrix() * pos.extend(0.0).extend(1.0);
-
+        if let Some(camera_transform) = camera_query.get_single().ok_log(code_location!()) {
+            if let Some(world_position) = cursor_world_position(window, camera_transform) {
                 debug!("Click at world pos: {:?}", world_position);
                 mouse_event_writer.send(MouseClickEvent {
                     button: mouse_button,
-                    world_position: world_position.truncate(),
+                    world_position,
                 })
             }
         }
     }
 }
 
+/// Where in the world the cursor is pointing, [None] while it's outside the window
+pub fn cursor_world_position(window: &Window, camera_transform: &Transform) -> Option<Vec3> {
+    let pos = window.cursor_position()?;
+    Some(screen_to_world_position(window, camera_transform, pos))
+}
+
+/// Where in the world [pos], in pixels from the bottom left of the window, is
+pub fn screen_to_world_position(window: &Window, camera_transform: &Transform, pos: Vec2) -> Vec3 {
+    let size = Vec2::new(window.width() as f32, window.height() as f32);
+    // the default orthographic projection is in pixels from the center;
+    // just undo the translation
+    let pos = pos - size / 2.0;
+    // apply the camera transform
+    let world_position = camera_transform.compute_matrix() * pos.extend(0.0).extend(1.0);
+    world_position.truncate()
+}
+
+/// Turns clicks on the map into player actions. Left click on a neighbouring tile moves there,
+/// right click on a tile in line with the player turns to face it and fires the power. With
+/// [Settings::power_aiming] on that first right click aims instead, and the next one fires: along
+/// the clicked line if it's in one, otherwise where it's already aimed. Which button does which
+/// can be swapped in [Settings]
+fn mouse_action_system(
+    mut mouse_event_reader: EventReader<MouseClickEvent>,
+    mut input_events: EventWriter<InputEvent>,
+    mut player_query: Query<(&TilePos, &mut Facing), With<Player>>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    regular_game_enable: Res<RegularGameEnable>,
+    settings: Res<Settings>,
+    power_aiming: Res<PowerAiming>,
+) {
+    for MouseClickEvent {
+        button,
+        world_position,
+    } in mouse_event_reader.iter()
+    {
+        let players_turn = global_turn_counter.current_phase == GamePhase::PlayerMovement;
+        if !players_turn || !regular_game_enable.enabled {
+            continue;
+        }
+        let (player_pos, mut facing) = match player_query.get_single_mut() {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        let clicked_pos = TilePos::from_world_pos(world_position.x, world_position.y);
+        let direction = MapDirection::in_line_towards(player_pos, &clicked_pos);
+        let (move_button, power_button) = if settings.swap_mouse_buttons {
+            (MouseButton::Right, MouseButton::Left)
+        } else {
+            (MouseButton::Left, MouseButton::Right)
+        };
+        if *button == power_button {
+            // Whilst aiming any click fires, the line has already been picked
+            if let Some(direction) = direction {
+                facing.0 = direction;
+                input_events.send(InputEvent::Power);
+            } else if power_aiming.aiming {
+                input_events.send(InputEvent::Power);
+            }
+        } else if *button == move_button && player_pos.distance_to(&clicked_pos) == 1 {
+            if let Some(direction) = direction {
+                input_events.send(InputEvent::MoveDirection(direction));
+            }
+        }
+    }
+}
+
 fn input_handle_system(
     input: Res<Input<KeyCode>>,
     mut input_events: EventWriter<InputEvent>,
     regular_game_enable: Res<RegularGameEnable>,
+    power_aiming: Res<PowerAiming>,
+    key_bindings: Res<KeyBindings>,
     mut app_state: ResMut<State<crate::CoreState>>,
 ) {
-    if input.just_pressed(KeyCode::Escape) {
+    if input.just_pressed(key_bindings.pause) {
+        if power_aiming.aiming {
+            // Pausing backs out of aiming rather than opening the overlay
+            input_events.send(InputEvent::CancelPower);
+            return;
+        }
         println!("Starting GameOverlay");
         app_state.push(crate::CoreState::GameOverlay).unwrap();
         return;
     }
-    fn input_to_event(input: &Input<KeyCode>) -> Option<InputEvent> {
+    fn input_to_event(input: &Input<KeyCode>, key_bindings: &KeyBindings) -> Option<InputEvent> {
         let new_direction = {
-            if input.just_pressed(KeyCode::A) {
+            if input.just_pressed(key_bindings.move_left) {
                 Some(MapDirection::Left)
-            } else if input.just_pressed(KeyCode::D) {
+            } else if input.just_pressed(key_bindings.move_right) {
                 Some(MapDirection::Right)
-            } else if input.just_pressed(KeyCode::W) {
+            } else if input.just_pressed(key_bindings.move_up) {
                 Some(MapDirection::Up)
-            } else if input.just_pressed(KeyCode::S) {
+            } else if input.just_pressed(key_bindings.move_down) {
                 Some(MapDirection::Down)
             } else {
                 None
             }
         };
-        let shift_held = input.pressed(KeyCode::LShift);
+        let shift_held = input.pressed(key_bindings.turn_modifier);
         match (new_direction, shift_held) {
             (Some(dir), false) => {
                 return Some(InputEvent::MoveDirection(dir));
@@ -588,21 +1238,21 @@ fn input_handle_system(
             (None, _) => (),
         }
 
-        if input.just_pressed(KeyCode::Space) {
+        if input.just_pressed(key_bindings.wait) {
             return Some(InputEvent::Wait);
         }
 
-        if input.just_pressed(KeyCode::Q) {
+        if input.just_pressed(key_bindings.power) {
             return Some(InputEvent::Power);
         }
 
-        if input.just_pressed(KeyCode::R) {
+        if input.just_pressed(key_bindings.hook) {
             return Some(InputEvent::Hook);
         }
 
         None
     }
-    if let Some(event) = input_to_event(&input) {
+    if let Some(event) = input_to_event(&input, &key_bindings) {
         if regular_game_enable.enabled {
             input_events.send(event);
         }
@@ -652,6 +1302,7 @@ fn player_damaged_effect_system(
     mut info_event_reader: EventReader<InfoEvent>,
     player_query: Query<Entity, With<Player>>,
     mut commands: Commands,
+    settings: Res<Settings>,
 ) {
     for event in info_event_reader.iter() {
         match event {
@@ -659,40 +1310,161 @@ fn player_damaged_effect_system(
                 let player_entity = player_query.single();
                 let timed_removal: TimedRemoval<DirectionalSpriteAnimationSpecial> =
                     TimedRemoval::new(Duration::from_millis(220));
-                let waggle = Waggle::new(8, 0.2, 0.2, 10.0);
-                commands
-                    .entity(player_entity)
+                let mut player_commands = commands.entity(player_entity);
+                player_commands
                     .insert(DirectionalSpriteAnimationSpecial(0))
-                    .insert(timed_removal)
-                    .insert(waggle);
+                    .insert(timed_removal);
+                if settings.screen_shake && !settings.reduce_motion {
+                    player_commands.insert(Waggle::new(8, 0.2, 0.2, 10.0));
+                }
             }
             _ => (),
         }
     }
 }
 
+/// Deals poison damage at the start of each player turn. Any resulting revive or death goes through
+/// the usual [health_watcher_system] flow. Poison keeps counting down while invulnerable, it just
+/// deals nothing for those turns
+fn poison_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    mut poisoned_query: Query<(&mut Poison, &mut Health, Option<&mut Inventory>)>,
+) {
+    if global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        for (mut poison, mut health, maybe_inventory) in poisoned_query.iter_mut() {
+            let damage = poison.tick();
+            // Checked first so the [Health] isn't marked changed by a hit that can't land
+            if damage > 0 && !health.invulnerable() {
+                info!(
+                    "Poison dealt {} damage, {} turns left",
+                    damage, poison.turns
+                );
+                health.decr_by_or_revive(damage, maybe_inventory);
+            }
+        }
+        local_turn_counter.incr();
+    }
+}
+
+/// Pushes the player and enemies stood in a current a tile downstream as the turn ends, unless
+/// they're facing upstream and so swimming against it, or are a [Barnacle]. Runs as the next turn
+/// starts, once every move of the last has resolved, and only onto free water
+fn current_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    current_query: Query<&HasCurrent>,
+    tile_storage_query: TileStorageQuery,
+    tile_type_query: Query<&HasTileType>,
+    mut resident_query: Query<
+        (&mut TilePos, &mut MovementAnimate, &Transform, &Facing),
+        (Or<(With<Player>, With<Enemy>)>, Without<Barnacle>),
+    >,
+) {
+    if !global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        return;
+    }
+    local_turn_counter.incr();
+    // Nothing has had a turn in the current yet at the start of the level
+    if global_turn_counter.turn_count <= 1 {
+        return;
+    }
+    let tile_storage = match tile_storage_query.get_single() {
+        Ok(tile_storage) => tile_storage,
+        Err(_) => return,
+    };
+    let mut occupied: Vec<TilePos> = resident_query.iter().map(|(pos, ..)| *pos).collect();
+    for (mut tile_pos, mut movement_animate, transform, facing) in resident_query.iter_mut() {
+        let direction = match tile_storage
+            .get(&tile_pos)
+            .and_then(|tile| current_query.get(tile).ok())
+        {
+            Some(HasCurrent(direction)) => direction,
+            None => continue,
+        };
+        if facing.0 == direction.opposite() {
+            continue;
+        }
+        // Only the tile downstream itself will do, a current never pushes anywhere else
+        let free_destination = match tile_pos.checked_add(direction.to_pos_move()) {
+            Some(destination) => super::movement::nearest_free_tile(
+                &destination,
+                0,
+                &tile_storage_query,
+                &tile_type_query,
+                &occupied,
+            ),
+            None => None,
+        };
+        if let Some(destination) = free_destination {
+            occupied.retain(|pos| *pos != *tile_pos);
+            occupied.push(destination);
+            *tile_pos = destination;
+            movement_animate.set(destination.to_world_pos(transform.translation.z));
+        }
+    }
+}
+
+/// Tints poisoned entities, removing the poison (and tint) once it has expired
+fn poison_tint_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Poison, &mut TextureAtlasSprite), Changed<Poison>>,
+) {
+    for (entity, poison, mut sprite) in query.iter_mut() {
+        if poison.expired() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<Poison>();
+        } else {
+            sprite.color = Poison::TINT;
+        }
+    }
+}
+
 fn health_watcher_system(
-    enemy_health: Query<(Entity, &Health), (With<Enemy>, Changed<Health>)>,
-    player_health: Query<(Entity, &Health), (With<Player>, Changed<Health>)>,
+    enemy_health: Query<
+        (Entity, &Health, &TilePos, Option<&ShellDropChance>),
+        (With<Enemy>, Changed<Health>),
+    >,
+    mut player_health: Query<
+        (Entity, &mut Health),
+        (With<Player>, Without<Enemy>, Changed<Health>),
+    >,
     mut info_event_writer: EventWriter<InfoEvent>,
     mut commands: Commands,
     mut known_player_hp: Local<Option<usize>>,
     _game_event_writer: EventWriter<GameEvent>,
     mut regular_game_enable: ResMut<RegularGameEnable>,
+    mut loaded_profile: ResMut<LoadedUserProfile>,
+    texture_atlas_store: Res<TextureAtlasStore>,
+    mut last_stand: ResMut<LastStand>,
+    mut player_strikes: ResMut<PlayerStrikes>,
+    mut damage_taken_this_level: ResMut<DamageTakenThisLevel>,
 ) {
-    for (entity, health) in enemy_health.iter() {
+    for (entity, health, tile_pos, shell_drop_chance) in enemy_health.iter() {
         if health.hp == 0 {
-            info_event_writer.send(InfoEvent::EnemyKilled);
+            info_event_writer.send(InfoEvent::EnemyKilled(player_strikes.kill_source(entity)));
             println!("Enemy died {:?}", entity);
-            commands.entity(entity).despawn()
+            if shell_drop_chance.map_or(false, |chance| chance.drops()) {
+                super::snails::spawn_dropped_shell(&mut commands, &texture_atlas_store, *tile_pos);
+            }
+            commands.entity(entity).despawn_recursive()
         }
     }
 
-    for (entity, health) in player_health.iter() {
+    for (entity, mut health) in player_health.iter_mut() {
+        // Revives are spent as the damage is dealt, see [Health::decr_by_shielded], leaving them
+        // to be announced here
+        let revived = health.revived.take();
         // There's a small chance this change triggers even if health aint changed - may need to
         // handle this if it becomes a problem
         match *known_player_hp {
-            Some(known_hp) if known_hp != health.hp => {
+            Some(known_hp) if known_hp != health.hp || revived.is_some() => {
+                // A revive means the hit would have killed, so counts as all the hp they had
+                let lost = match revived {
+                    Some(_) => known_hp.max(1),
+                    None => known_hp.saturating_sub(health.hp),
+                };
+                damage_taken_this_level.0 += lost;
                 if regular_game_enable.enabled {
                     info_event_writer.send(InfoEvent::PlayerHurt);
                 } else {
@@ -701,7 +1473,20 @@ fn health_watcher_system(
             }
             _ => (),
         }
-        *known_player_hp = Some(health.hp);
+        match revived {
+            Some(Revival::LastStand) => {
+                info!("Player would have died, made their last stand instead");
+                last_stand.used = true;
+                info_event_writer.send(InfoEvent::LastStandUsed);
+            }
+            Some(Revival::Starfish) => {
+                info!("Player would have died, used a starfish instead");
+                let profile = &mut loaded_profile.user_profile;
+                profile.starfish = profile.starfish.saturating_sub(1);
+                info_event_writer.send(InfoEvent::SecondChanceUsed);
+            }
+            None => (),
+        }
         if health.hp == 0 {
             println!("Player! died {:?}", entity);
             let delay = Duration::from_millis(500);
@@ -711,45 +1496,139 @@ fn health_watcher_system(
             info_event_writer.send(InfoEvent::PlayerKilled);
             regular_game_enable.enabled = false;
         }
+        *known_player_hp = Some(health.hp);
+    }
+}
+
+/// Gives the player the run's last stand to spend, while [Settings::last_stand] is on and it
+/// hasn't been used yet
+fn last_stand_charge_system(
+    settings: Res<Settings>,
+    last_stand: Res<LastStand>,
+    mut player_health: Query<&mut Health, With<Player>>,
+) {
+    let charged = settings.last_stand && !last_stand.used;
+    for mut health in player_health.iter_mut() {
+        if health.last_stand != charged {
+            health.last_stand = charged;
+        }
+    }
+}
+
+/// Counts down any invulnerability at the start of each player turn
+fn invulnerability_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    mut health_query: Query<&mut Health>,
+) {
+    if global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        for mut health in health_query.iter_mut() {
+            if health.invulnerable() {
+                health.invulnerable_turns -= 1;
+            }
+        }
+        local_turn_counter.incr();
     }
 }
 
 fn sfx_system(
     mut info_event_reader: EventReader<InfoEvent>,
     audio: Res<Audio>,
-    _assets: Res<AssetServer>,
     audio_asset_store: Res<AudioAssetStore>,
 ) {
-    // TODO: Move audio to asset system
     for event in info_event_reader.iter() {
-        match event {
-            InfoEvent::PlayerHurt => {
-                debug!("Playing Audio for Player Hurt");
-                audio.play(audio_asset_store.get(&AudioAsset::Hurt));
-            }
-            InfoEvent::EnemyKilled => {
-                debug!("Playing Audio for Enemy Killed");
-                audio.play(audio_asset_store.get(&AudioAsset::Chomp));
+        let (sfx, description) = match event {
+            InfoEvent::PlayerHurt => (AudioAsset::Hurt, "Player Hurt"),
+            InfoEvent::EnemyKilled(_) => (AudioAsset::Chomp, "Enemy Killed"),
+            InfoEvent::PlayerMoved => (AudioAsset::FishSlap, "Player Moved"),
+            InfoEvent::PlayerKilled => (AudioAsset::Death, "Player Died"),
+            InfoEvent::PlayerPickedUpSnail => (AudioAsset::Pickup, "Player Picked Up Snail"),
+            InfoEvent::PlayerPickedUpItem => (AudioAsset::Pickup, "Player Picked Up Item"),
+            InfoEvent::ShellsDeposited => (AudioAsset::Pickup, "Shells Deposited"),
+            InfoEvent::JellyLightningFired(_) => {
+                (AudioAsset::JellyLightning, "Jellyfish lightning")
             }
-            InfoEvent::PlayerMoved => {
-                debug!("Playing Audio for Player Moved");
-                audio.play(audio_asset_store.get(&AudioAsset::FishSlap));
-            }
-            InfoEvent::PlayerKilled => {
-                debug!("Playing Audio for Player Died");
-                audio.play(audio_asset_store.get(&AudioAsset::Death));
-            }
-            InfoEvent::PlayerPickedUpSnail => {
-                debug!("Playing Audio for Player Picked Up Snail");
-                audio.play(audio_asset_store.get(&AudioAsset::Pickup));
+            InfoEvent::VortexSpawned => (AudioAsset::VortexSpawn, "Vortex Spawned"),
+            InfoEvent::SecondChanceUsed => (AudioAsset::Pickup, "Second Chance Used"),
+            InfoEvent::LastStandUsed => (AudioAsset::LastStand, "Last Stand Used"),
+            InfoEvent::ObjectiveCompleted => (AudioAsset::Pickup, "Objective Completed"),
+            InfoEvent::PowerUnavailable => (AudioAsset::EmptyClick, "Power Unavailable"),
+            InfoEvent::FallbackMapUsed => continue,
+        };
+        debug!("Playing Audio for {}", description);
+        audio.play(audio_asset_store.get(&sfx));
+    }
+}
+
+/// Safety net for anything moving residents around: after each phase, any player or enemy whose
+/// [TilePos] isn't a cell of the map is snapped to the nearest free cell. Enemies with nowhere to
+/// go are despawned
+fn stray_resident_system(
+    mut commands: Commands,
+    mut game_event_reader: EventReader<GameEvent>,
+    cell_map: Option<Res<CellMap<i32>>>,
+    mut queries: ParamSet<(
+        Query<&TilePos, Or<(With<Player>, With<Enemy>)>>,
+        Query<(&mut TilePos, &mut MovementAnimate, &Transform), With<Player>>,
+        Query<(Entity, &mut TilePos, &mut MovementAnimate, &Transform), With<Enemy>>,
+    )>,
+) {
+    let phase_completed = game_event_reader
+        .iter()
+        .any(|event| matches!(event, GameEvent::PhaseComplete(_)));
+    let cell_map = match cell_map {
+        Some(cell_map) if phase_completed => cell_map,
+        _ => return,
+    };
+    let mut occupied: Vec<(i32, i32)> = queries.p0().iter().map(|pos| pos.as_i32s()).collect();
+    if occupied.iter().all(|pos| cell_map.contains(pos)) {
+        return;
+    }
+
+    for (mut tile_pos, mut movement_animate, transform) in queries.p1().iter_mut() {
+        if cell_map.contains(&tile_pos.as_i32s()) {
+            continue;
+        }
+        // The player is never removed, so may share a cell if the map is completely full
+        match cell_map
+            .nearest_cell(tile_pos.as_i32s(), &occupied)
+            .or_else(|| cell_map.nearest_cell(tile_pos.as_i32s(), &[]))
+        {
+            Some((x, y)) => {
+                warn!("Player stranded at {:?}, moving to {:?}", *tile_pos, (x, y));
+                let destination = TilePos {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                movement_animate.set(destination.to_world_pos(transform.translation.z));
+                *tile_pos = destination;
+                occupied.push((x, y));
             }
-            InfoEvent::JellyLightningFired => {
-                debug!("Playing Audio for Jellyfish lightning");
-                audio.play(audio_asset_store.get(&AudioAsset::JellyLightning));
+            None => warn!("Player stranded at {:?} on an empty map", *tile_pos),
+        }
+    }
+
+    for (entity, mut tile_pos, mut movement_animate, transform) in queries.p2().iter_mut() {
+        if cell_map.contains(&tile_pos.as_i32s()) {
+            continue;
+        }
+        match cell_map.nearest_cell(tile_pos.as_i32s(), &occupied) {
+            Some((x, y)) => {
+                warn!("Enemy stranded at {:?}, moving to {:?}", *tile_pos, (x, y));
+                let destination = TilePos {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                movement_animate.set(destination.to_world_pos(transform.translation.z));
+                *tile_pos = destination;
+                occupied.push((x, y));
             }
-            InfoEvent::VortexSpawned => {
-                debug!("Playing Audio for Vortex Spawned");
-                audio.play(audio_asset_store.get(&AudioAsset::VortexSpawn));
+            None => {
+                warn!(
+                    "Enemy stranded at {:?} with nowhere to go, despawning",
+                    *tile_pos
+                );
+                commands.entity(entity).despawn_recursive();
             }
         }
     }
@@ -760,20 +1639,30 @@ fn jellyfish_system(
     mut game_event_writer: EventWriter<GameEvent>,
     global_turn_counter: Res<GlobalTurnCounter>,
     mut local_turn_counter: Local<TurnCounter>,
-    mut jellyfish_query: Query<(&mut Jellyfish, &TilePos)>,
+    mut jellyfish_query: Query<
+        (&mut Jellyfish, &TilePos, Option<&mut MoveIntention>),
+        Without<Player>,
+    >,
     texture_atlas_store: Res<TextureAtlasStore>,
     mut info_event_writer: EventWriter<InfoEvent>,
-    player_query: Query<(Entity, &TilePos), With<Player>>,
-    mut health_query: Query<&mut Health>,
+    mut player_queries: ParamSet<(
+        Query<(Entity, &TilePos), With<Player>>,
+        Query<(&mut TilePos, &mut MovementAnimate, &Transform), With<Player>>,
+    )>,
+    occupied_query: Query<&TilePos, (With<Enemy>, Without<Player>)>,
+    mut health_query: Query<(&mut Health, Option<&mut Inventory>)>,
     mut tile_storage_query: TileStorageQuery,
     tiletype_query: Query<&HasTileType>,
+    balance: Res<BalanceConfig>,
 ) {
+    let recharge_turns = balance.jellyfish.recharge_turns;
     if global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PreEnemyMovement) {
-        for (mut jellyfish, tile_pos) in jellyfish_query.iter_mut() {
+        let mut knockbacks = Vec::new();
+        for (mut jellyfish, tile_pos, maybe_intention) in jellyfish_query.iter_mut() {
             let final_state = match &jellyfish.state {
                 JellyfishState::Normal => {
                     let mut rng = rand::thread_rng();
-                    if rng.gen_bool(Jellyfish::CHARGE_CHANCE) {
+                    if rng.gen_bool(balance.jellyfish.charge_chance) {
                         JellyfishState::Charging(MapDirection::rand_choice())
                     } else {
                         JellyfishState::Normal
@@ -783,7 +1672,7 @@ fn jellyfish_system(
                     let (lightning_length, hit) = super::enemy::jelly_lightning_projection(
                         tile_pos,
                         direction,
-                        &player_query,
+                        &player_queries.p0(),
                         &tile_storage_query,
                         &tiletype_query,
                     );
@@ -797,12 +1686,23 @@ fn jellyfish_system(
                         );
                     }
                     if let Some(player_entity) = hit {
-                        if let Ok(mut health) = health_query.get_mut(player_entity) {
-                            health.decr_by(1);
+                        if let Ok((mut health, maybe_inventory)) =
+                            health_query.get_mut(player_entity)
+                        {
+                            // Poisonous lightning deals its damage over the following turns
+                            let damage = if jellyfish.poisonous { 0 } else { 1 };
+                            let hit_landed =
+                                Health::decr_by_shielded(&mut health, damage, maybe_inventory);
+                            if hit_landed && jellyfish.poisonous {
+                                commands.entity(player_entity).insert(jellyfish.poison());
+                            }
+                            if hit_landed && health.hp > 0 {
+                                knockbacks.push(direction.clone());
+                            }
                         }
                     }
-                    info_event_writer.send(InfoEvent::JellyLightningFired);
-                    JellyfishState::Recharging(Jellyfish::RECHARGE_TURNS)
+                    info_event_writer.send(InfoEvent::JellyLightningFired(*tile_pos));
+                    JellyfishState::Recharging(recharge_turns)
                 }
                 JellyfishState::Recharging(recharge_turns) => match recharge_turns - 1 {
                     0 => JellyfishState::Normal,
@@ -811,6 +1711,30 @@ fn jellyfish_system(
             };
             info!("Jellyfish at {:?} state is {:?}", tile_pos, final_state);
             jellyfish.state = final_state;
+            if !jellyfish.can_move(recharge_turns) {
+                // It won't be moving this turn after all, so drop any previewed move
+                if let Some(mut intention) = maybe_intention {
+                    intention.0 = None;
+                }
+            }
+        }
+        // Lightning pushes the player a tile along its path, unless blocked
+        for direction in knockbacks.iter() {
+            if let Ok((mut player_pos, mut movement_animate, transform)) =
+                player_queries.p1().get_single_mut()
+            {
+                if let Some(destination) = super::movement::knockback_destination(
+                    &player_pos,
+                    direction,
+                    &occupied_query,
+                    &tile_storage_query,
+                    &tiletype_query,
+                ) {
+                    info!("Player knocked back to {:?}", destination);
+                    movement_animate.set(destination.to_world_pos(transform.translation.z));
+                    *player_pos = destination;
+                }
+            }
         }
         local_turn_counter.incr();
         game_event_writer.send(GameEvent::PhaseComplete(GamePhase::PreEnemyMovement));
@@ -820,8 +1744,16 @@ fn enemy_system(
     mut game_event_writer: EventWriter<GameEvent>,
     global_turn_counter: Res<GlobalTurnCounter>,
     mut local_turn_counter: Local<TurnCounter>,
-    enemy_query: Query<(Entity, &Enemy, &CanMoveDistance, &MoveWeighting)>,
-    health_query: Query<&mut Health>,
+    mut enemy_query: Query<(
+        Entity,
+        &Enemy,
+        &CanMoveDistance,
+        Option<&AttackDamage>,
+        Option<&mut MoveIntention>,
+        Option<&Laziness>,
+        Option<&Barnacle>,
+    )>,
+    health_query: Query<(&mut Health, Option<&mut Inventory>)>,
     mut move_query: ParamSet<(
         Query<&TilePos, With<Player>>,
         Query<&TilePos, With<Enemy>>,
@@ -830,20 +1762,81 @@ fn enemy_system(
     )>,
     mut tile_storage_query: TileStorageQuery,
     jellyfish_query: Query<&Jellyfish>,
+    phantom_query: Query<&Phantom>,
     tile_type_query: Query<&HasTileType>,
+    mut commands: Commands,
+    balance: Res<BalanceConfig>,
+    mut enemy_phase_queue: ResMut<EnemyPhaseQueue>,
 ) {
     let player_position = *move_query.p0().get_single().unwrap();
     if global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::EnemyMovement) {
-        let mut move_decisions = MoveDecisions::new();
-        let mut moved_to = Vec::new();
-        for (entity, enemy, can_move_distance, move_weights) in enemy_query.iter() {
-            let attack_criteria = AttackCriteria::for_enemy(enemy.can_attack_directly);
+        let queue = &mut *enemy_phase_queue;
+        if !queue.is_started() {
+            // Nothing moves until every decision is made, so one index does for the whole phase
+            let occupancy = Occupancy::from_query(&move_query.p2());
+            let enemy_positions: Vec<(Entity, TilePos)> = enemy_query
+                .iter()
+                .map(|(entity, ..)| (entity, *move_query.p1().get(entity).unwrap()))
+                .collect();
+            queue.start(
+                super::movement::enemy_move_order(enemy_positions, &player_position),
+                occupancy,
+            );
+        }
+        for entity in queue.next_batch(balance.enemy_phase.batch_size) {
+            // Anything despawned since the phase started has no move to make
+            let (
+                _,
+                enemy,
+                can_move_distance,
+                maybe_damage,
+                maybe_intention,
+                maybe_laziness,
+                maybe_barnacle,
+            ) = match enemy_query.get_mut(entity) {
+                Ok(enemy) => enemy,
+                Err(_) => continue,
+            };
+            // Barnacles never move, they just take up their tile
+            if maybe_barnacle.is_some() {
+                continue;
+            }
+            let damage = maybe_damage.map_or(1, |attack_damage| attack_damage.0);
+            let attack_criteria = AttackCriteria::for_enemy(enemy.can_attack_directly, damage);
             let current_pos = *move_query.p1().get(entity).unwrap();
-            let direction =
-                MapDirection::weighted_rand_choice(&current_pos, &player_position, move_weights);
+            // Every move is the intention chosen by [enemy_intention_system], so it matches any
+            // preview. Those without one can't move this turn, or arrived since it was chosen
+            let direction = match maybe_intention.and_then(|mut intention| intention.0.take()) {
+                Some(direction) => direction,
+                None => continue,
+            };
             if let Ok(jellyfish) = jellyfish_query.get(entity) {
                 // If enemy is a jellyfish, we skip moving if in one of the restricted states
-                if !jellyfish.can_move() {
+                if !jellyfish.can_move(balance.jellyfish.recharge_turns) {
+                    continue;
+                }
+            }
+            // Rolled after the intention is taken, so a dozing enemy's preview is still cleared
+            if maybe_laziness.map_or(false, Laziness::dozes) {
+                debug!("Enemy {:?} dozed off", entity);
+                queue.move_decisions.insert(entity, MoveDecision::Nothing);
+                continue;
+            }
+            // A phantom with nowhere free next to the player just moves as normal
+            if phantom_query.get(entity).map_or(false, Phantom::teleports) {
+                if let Some(decision) = super::movement::teleport_destination(
+                    &player_position,
+                    &queue.occupancy,
+                    &tile_storage_query,
+                    &tile_type_query,
+                    &queue.moved_to,
+                ) {
+                    debug!("Phantom {:?} teleporting: {:?}", entity, decision);
+                    queue.moved_to.extend(decision.to_move_position());
+                    commands
+                        .entity(entity)
+                        .insert(Waggle::new(6, 0.5, 0.5, 20.0));
+                    queue.move_decisions.insert(entity, decision);
                     continue;
                 }
             }
@@ -852,16 +1845,21 @@ fn enemy_system(
                 &direction,
                 can_move_distance.get(&direction),
                 &attack_criteria,
-                move_query.p2(),
+                &queue.occupancy,
                 &tile_storage_query,
                 &tile_type_query,
-                &moved_to,
+                &queue.moved_to,
             );
             if let Some(tilepos) = decision.to_move_position() {
-                moved_to.push(tilepos);
+                queue.moved_to.push(tilepos);
+                commands.entity(entity).insert(LastMove(direction));
             }
-            move_decisions.insert(entity, decision);
+            queue.move_decisions.insert(entity, decision);
         }
+        if !queue.is_finished() {
+            return;
+        }
+        let move_decisions = queue.finish();
         println!("Move Decisions: {:?}", move_decisions);
 
         super::movement::apply_move(move_decisions, move_query.p3(), health_query);
@@ -870,6 +1868,124 @@ fn enemy_system(
     }
 }
 
+/// Chooses every enemy's next move direction up front, each turn. With
+/// [Settings::intention_preview] on that's at the start of the player's turn, so it can be
+/// previewed with the preview guaranteed to match the move actually made. Otherwise it waits for
+/// the enemy phase, so enemies react to where the player has just moved
+fn enemy_intention_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    player_query: Query<&TilePos, With<Player>>,
+    position_query: Query<(Entity, &TilePos), With<Enemy>>,
+    pursuer_query: Query<&Pursuer>,
+    mut enemy_query: Query<
+        (
+            Entity,
+            &TilePos,
+            &MoveWeighting,
+            Option<&LastMove>,
+            Option<&Jellyfish>,
+            &mut MoveIntention,
+        ),
+        (With<Enemy>, Without<Barnacle>),
+    >,
+    balance: Res<BalanceConfig>,
+    settings: Res<Settings>,
+) {
+    let phase = if settings.intention_preview {
+        GamePhase::PlayerMovement
+    } else {
+        GamePhase::EnemyMovement
+    };
+    if global_turn_counter.can_take_turn(&mut local_turn_counter, phase) {
+        if let Ok(player_position) = player_query.get_single() {
+            let enemy_positions: Vec<(Entity, TilePos)> = position_query
+                .iter()
+                .map(|(entity, tile_pos)| (entity, *tile_pos))
+                .collect();
+            // Those that spot the player alert their neighbours, and sudden death's pursuers
+            // always know where it is
+            let mut alerted =
+                super::movement::alerted_enemies(&enemy_positions, player_position, &balance.aggro);
+            alerted.extend(
+                enemy_positions
+                    .iter()
+                    .map(|(entity, _)| *entity)
+                    .filter(|entity| pursuer_query.contains(*entity)),
+            );
+            for (entity, tile_pos, move_weights, maybe_last_move, maybe_jellyfish, mut intention) in
+                enemy_query.iter_mut()
+            {
+                let can_move = maybe_jellyfish.map_or(true, |jellyfish| {
+                    jellyfish.can_move(balance.jellyfish.recharge_turns)
+                });
+                let move_weights = match maybe_last_move {
+                    Some(last_move) => last_move.weighting(move_weights),
+                    None => move_weights.clone(),
+                };
+                intention.0 = match (can_move, alerted.contains(&entity)) {
+                    (false, _) => None,
+                    (true, true) => Some(MapDirection::weighted_rand_choice(
+                        tile_pos,
+                        player_position,
+                        &move_weights,
+                    )),
+                    (true, false) => Some(MapDirection::wander_choice(&move_weights)),
+                };
+            }
+        }
+        local_turn_counter.incr();
+    }
+}
+
+/// Keeps an arrow on each enemy showing its [MoveIntention], while [Settings::intention_preview]
+/// is on
+fn intention_arrow_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    image_store: Res<ImageAssetStore>,
+    intention_query: Query<(Entity, &MoveIntention, ChangeTrackers<MoveIntention>)>,
+    arrow_query: Query<(Entity, &Parent), With<IntentionArrow>>,
+) {
+    for (entity, intention, change_tracker) in intention_query.iter() {
+        if !(change_tracker.is_changed() || settings.is_changed()) {
+            continue;
+        }
+        for (arrow_entity, parent) in arrow_query.iter() {
+            if parent.get() == entity {
+                commands.entity(arrow_entity).despawn_recursive();
+            }
+        }
+        if let (true, Some(direction)) = (settings.intention_preview, &intention.0) {
+            commands.entity(entity).with_children(|parent| {
+                super::enemy::spawn_intention_arrow(parent, &image_store, direction);
+            });
+        }
+    }
+}
+
+/// Respawns the hp pips above any enemy whose health changed, including on spawn
+fn health_pips_system(
+    mut commands: Commands,
+    image_store: Res<ImageAssetStore>,
+    enemy_query: Query<(Entity, &Health), (With<Enemy>, Changed<Health>)>,
+    pips_query: Query<(Entity, &Parent), With<HealthPips>>,
+) {
+    for (entity, health) in enemy_query.iter() {
+        for (pips_entity, parent) in pips_query.iter() {
+            if parent.get() == entity {
+                commands.entity(pips_entity).despawn_recursive();
+            }
+        }
+        // Dead enemies are despawned by [health_watcher_system]
+        if health.hp > 0 {
+            commands.entity(entity).with_children(|parent| {
+                super::enemy::spawn_health_pips(parent, &image_store, health.hp);
+            });
+        }
+    }
+}
+
 fn player_movement_watcher(
     player_position_query: Query<&TilePos, (With<Player>, Changed<TilePos>)>,
     mut known_player_position: Local<Option<TilePos>>,
@@ -893,7 +2009,11 @@ fn player_movement_watcher(
 
 fn player_movement_system(
     mut game_event_writer: EventWriter<GameEvent>,
-    mut power_event_writer: EventWriter<PowerEvent>,
+    // Paired up to keep within the system parameter limit
+    (mut power_event_writer, mut info_event_writer): (
+        EventWriter<PowerEvent>,
+        EventWriter<InfoEvent>,
+    ),
     mut input_events: EventReader<InputEvent>,
     mut move_query: ParamSet<(
         Query<(Entity, &TilePos), With<Player>>,
@@ -902,14 +2022,34 @@ fn player_movement_system(
         Query<&mut Facing, With<Player>>,
     )>,
     mut power_query: Query<&mut PowerCharges, With<Player>>,
-    mut health_query: Query<&mut Health>,
+    mut health_query: Query<(&mut Health, Option<&mut Inventory>)>,
     tile_type_query: Query<&HasTileType>,
     tile_storage_query: TileStorageQuery,
     global_turn_counter: Res<GlobalTurnCounter>,
     mut local_turn_counter: Local<TurnCounter>,
+    mut power_aiming: ResMut<PowerAiming>,
+    mut game_time: ResMut<GameTime>,
+    // Paired up to keep within the system parameter limit
+    (hit_stop_settings, mut player_strikes): (Res<HitStopSettings>, ResMut<PlayerStrikes>),
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut pending_attack: Local<Option<PendingAttack>>,
 ) {
-    for event in input_events.iter() {
+    // At most one action per frame. The first one taking the turn already stops the rest via
+    // the turn counter, but dropping them up front makes sure of it for any that wouldn't
+    let (input_events, dropped) = super::events::first_action_only(input_events.iter());
+    if dropped > 0 {
+        info!("Dropped {} extra inputs read in the same frame", dropped);
+    }
+    for event in input_events {
         match event {
+            InputEvent::MoveDirection(direction) | InputEvent::TurnDirection(direction)
+                if power_aiming.aiming =>
+            {
+                // Whilst aiming, directions only re-aim the power and don't take the turn
+                info!("Player re-aiming power: {:?}", direction);
+                move_query.p3().single_mut().0 = direction.clone();
+            }
             InputEvent::MoveDirection(direction) => {
                 let can_take_turn = global_turn_counter
                     .can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement);
@@ -927,14 +2067,31 @@ fn player_movement_system(
                         direction,
                         1,
                         &AttackCriteria::for_player(),
-                        move_query.p1(),
+                        &Occupancy::from_query(&move_query.p1()),
                         &tile_storage_query,
                         &tile_type_query,
-                        &vec![],
+                        &[],
                     );
 
                     info!("Player move decision: {:?}", move_decision);
 
+                    if settings.confirm_attacks && move_decision.attack_target().is_some() {
+                        let now = time.seconds_since_startup();
+                        let confirmed = pending_attack
+                            .take()
+                            .map_or(false, |pending| pending.confirmed_by(direction, now));
+                        if !confirmed {
+                            // Face the target as a cue, but hold the turn until pressed again
+                            info!("Attack needs confirming, press {:?} again", direction);
+                            move_query.p3().single_mut().0 = direction.clone();
+                            *pending_attack = Some(PendingAttack {
+                                direction: direction.clone(),
+                                requested_at: now,
+                            });
+                            continue;
+                        }
+                    }
+
                     super::movement::apply_move_single(
                         player_entity,
                         &move_decision,
@@ -942,6 +2099,18 @@ fn player_movement_system(
                         &mut health_query,
                     );
 
+                    if let Some(target) = move_decision.attack_target() {
+                        player_strikes.strike(target);
+                    }
+                    let killed = move_decision.attack_target().map_or(false, |target| {
+                        health_query
+                            .get(target)
+                            .map_or(false, |(health, _)| health.hp == 0)
+                    });
+                    if killed && hit_stop_settings.on_melee_kill {
+                        game_time.hit_stop(hit_stop_settings.duration);
+                    }
+
                     local_turn_counter.incr();
                     game_event_writer.send(GameEvent::PhaseComplete(GamePhase::PlayerMovement));
                 } else {
@@ -968,6 +2137,7 @@ fn player_movement_system(
                     .can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement);
                 if can_take_turn {
                     info!("Player Waiting");
+                    power_aiming.aiming = false;
                     local_turn_counter.incr();
                     game_event_writer.send(GameEvent::PhaseComplete(GamePhase::PlayerMovement));
                 }
@@ -978,13 +2148,45 @@ fn player_movement_system(
                 if can_take_turn {
                     let mut power_charges = power_query.single_mut();
                     if power_charges.charges > 0 {
-                        power_event_writer.send(PowerEvent::PowerFired);
+                        if settings.power_aiming && !power_aiming.aiming {
+                            info!("Player aiming power");
+                            power_aiming.aiming = true;
+                        } else {
+                            power_aiming.aiming = false;
+                            power_event_writer.send(PowerEvent::PowerFired { turns_charged: 0 });
+                            local_turn_counter.incr();
+                            game_event_writer
+                                .send(GameEvent::PhaseComplete(GamePhase::PlayerMovement));
+                            power_charges.use_charge();
+                        }
+                    } else {
+                        info!("Player tried to use power with no charges left");
+                        info_event_writer.send(InfoEvent::PowerUnavailable);
+                    }
+                }
+            }
+            InputEvent::ChargedPower(turns_charged) => {
+                let can_take_turn = global_turn_counter
+                    .can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement);
+                if can_take_turn {
+                    let mut power_charges = power_query.single_mut();
+                    if power_charges.use_charge() {
+                        info!("Player firing power charged for {} turns", turns_charged);
+                        power_aiming.aiming = false;
+                        power_event_writer.send(PowerEvent::PowerFired {
+                            turns_charged: *turns_charged,
+                        });
                         local_turn_counter.incr();
                         game_event_writer.send(GameEvent::PhaseComplete(GamePhase::PlayerMovement));
-                        power_charges.use_charge();
                     }
                 }
             }
+            InputEvent::CancelPower => {
+                if power_aiming.aiming {
+                    info!("Player cancelled power");
+                    power_aiming.aiming = false;
+                }
+            }
             InputEvent::Hook => {
                 //Do nothing here, handled in hook spawner system
             }
@@ -992,66 +2194,322 @@ fn player_movement_system(
     }
 }
 
+/// Tracks the charged power: starting it when its key is pressed, adding a turn of charge for
+/// each player turn taken while it's held, and sending it to fire on the player's turn once
+/// released
+fn power_charge_system(
+    input: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    regular_game_enable: Res<RegularGameEnable>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut game_event_reader: EventReader<GameEvent>,
+    mut player_query: Query<(Entity, &PowerCharges, Option<&mut PowerCharge>), With<Player>>,
+    mut input_events: EventWriter<InputEvent>,
+    mut commands: Commands,
+) {
+    let (player_entity, power_charges, maybe_charge) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let turns_taken = game_event_reader
+        .iter()
+        .filter(|event| matches!(event, GameEvent::PhaseComplete(GamePhase::PlayerMovement)))
+        .count();
+    match maybe_charge {
+        None => {
+            if input.just_pressed(key_bindings.charge_power)
+                && power_charges.charges > 0
+                && regular_game_enable.enabled
+            {
+                info!("Player started charging power");
+                commands
+                    .entity(player_entity)
+                    .insert(PowerCharge::default());
+            }
+        }
+        Some(mut charge) => {
+            if !charge.released {
+                charge.turns_held += turns_taken;
+            }
+            if input.just_released(key_bindings.charge_power) {
+                charge.released = true;
+            }
+            let players_turn = global_turn_counter.current_phase == GamePhase::PlayerMovement;
+            if charge.released && players_turn && regular_game_enable.enabled {
+                input_events.send(InputEvent::ChargedPower(charge.turns_held));
+                commands.entity(player_entity).remove::<PowerCharge>();
+            }
+        }
+    }
+}
+
+/// With auto-aim on, turns the player toward the nearest enemy with a clear line along a row or
+/// column, once at the start of each of their turns so they can still turn away manually. Only
+/// changes [Facing], so never takes the turn
+fn auto_aim_system(
+    settings: Res<Settings>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    regular_game_enable: Res<RegularGameEnable>,
+    power_aiming: Res<PowerAiming>,
+    mut local_turn_counter: Local<TurnCounter>,
+    mut player_query: Query<(&TilePos, &mut Facing), With<Player>>,
+    enemy_query: Query<(Entity, &TilePos), (With<Enemy>, Without<ProjectileImmune>)>,
+    tile_storage_query: TileStorageQuery,
+    tile_type_query: Query<&HasTileType>,
+) {
+    if !settings.auto_aim || !regular_game_enable.enabled || power_aiming.aiming {
+        return;
+    }
+    if !global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        return;
+    }
+    local_turn_counter.incr();
+    let (player_pos, mut facing) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let mut nearest: Option<(usize, MapDirection)> = None;
+    for direction in [
+        MapDirection::Up,
+        MapDirection::Right,
+        MapDirection::Down,
+        MapDirection::Left,
+    ] {
+        let fate = super::projectile::scan_to_endpoint(
+            player_pos,
+            &direction,
+            &enemy_query,
+            &tile_storage_query,
+            &tile_type_query,
+            true,
+            None,
+        );
+        if let ProjectileFate::EndHitTarget((target_pos, _)) = fate {
+            let distance = player_pos.distance_to(&target_pos);
+            if nearest.as_ref().map_or(true, |(best, _)| distance < *best) {
+                nearest = Some((distance, direction));
+            }
+        }
+    }
+    if let Some((_, direction)) = nearest {
+        // Only write when turning so Changed<Facing> isn't tripped every turn
+        if facing.0 != direction {
+            debug!("Auto-aiming {:?}", direction);
+            facing.0 = direction;
+        }
+    }
+}
+
+/// Shows the line the power would travel along whilst aiming, respawning it whenever the aim
+/// changes
+fn power_aiming_indicator_system(
+    mut commands: Commands,
+    power_aiming: Res<PowerAiming>,
+    player_query: Query<(&TilePos, &Facing), With<Player>>,
+    enemy_query: Query<(Entity, &TilePos), (With<Enemy>, Without<ProjectileImmune>)>,
+    indicator_query: Query<Entity, With<PowerAimingIndicator>>,
+    atlases: Res<TextureAtlasStore>,
+    tile_storage_query: TileStorageQuery,
+    tile_type_query: Query<&HasTileType>,
+    health_query: Query<&Health>,
+    loaded_profile: Res<LoadedUserProfile>,
+    mut last_aim: Local<Option<(TilePos, MapDirection)>>,
+) {
+    let current_aim = if power_aiming.aiming {
+        player_query
+            .get_single()
+            .ok()
+            .map(|(tilepos, facing)| (*tilepos, facing.0.clone()))
+    } else {
+        None
+    };
+    if *last_aim != current_aim {
+        for entity in indicator_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        if let Some((tilepos, direction)) = &current_aim {
+            // Aimed shots always fire uncharged
+            let target_hp = |entity: Entity| health_query.get(entity).ok().map(|health| health.hp);
+            let mut passthrough = loaded_profile
+                .user_profile
+                .overkill_passthrough
+                .then(|| Passthrough::new(super::projectile::charged_damage(0), &target_hp));
+            let path = super::projectile::scan_path(
+                tilepos,
+                direction,
+                loaded_profile.user_profile.projectile_bounces(),
+                &enemy_query,
+                &tile_storage_query,
+                &tile_type_query,
+                true,
+                passthrough.as_mut(),
+            );
+            super::projectile::spawn_aiming_indicator(
+                &mut commands,
+                &atlases,
+                tilepos,
+                direction,
+                &path,
+            );
+        }
+        *last_aim = current_aim;
+    }
+}
+
 fn player_power_system(
     mut query: ParamSet<(
         Query<(&Transform, &TilePos, &Facing), With<Player>>,
-        Query<(Entity, &TilePos), With<Enemy>>,
+        Query<(Entity, &TilePos), (With<Enemy>, Without<ProjectileImmune>)>,
     )>,
     mut commands: Commands,
     atlases: Res<TextureAtlasStore>,
     mut power_event_reader: EventReader<PowerEvent>,
     tile_storage_query: TileStorageQuery,
     tile_type_query: Query<&HasTileType>,
+    health_query: Query<&Health>,
+    loaded_profile: Res<LoadedUserProfile>,
+    balance: Res<BalanceConfig>,
 ) {
+    let target_hp = |entity: Entity| health_query.get(entity).ok().map(|health| health.hp);
     for event in power_event_reader.iter() {
         match event {
-            PowerEvent::PowerFired => {
+            PowerEvent::PowerFired { turns_charged } => {
                 let (start_pos, tilepos, direction): (Vec3, TilePos, MapDirection) = {
                     let q = query.p0();
                     let (transform, tilepos, facing) = q.single();
                     ((*transform).translation, *tilepos, facing.0.clone())
                 };
-                let fate = super::projectile::scan_to_endpoint(
+                let damage = super::projectile::charged_damage(*turns_charged);
+                let mut passthrough = loaded_profile
+                    .user_profile
+                    .overkill_passthrough
+                    .then(|| Passthrough::new(damage, &target_hp));
+                let path = super::projectile::scan_path(
                     &tilepos,
                     &direction,
+                    loaded_profile.user_profile.projectile_bounces(),
                     &query.p1(),
                     &tile_storage_query,
                     &tile_type_query,
                     true,
+                    passthrough.as_mut(),
                 );
-                let end_point = *fate.tile_pos();
-                let end_target_entity = fate.entity();
                 super::projectile::spawn_projectile(
                     &mut commands,
                     &atlases,
                     direction,
                     start_pos,
-                    end_point,
-                    end_target_entity,
+                    path,
+                    damage,
+                    balance.projectile.speed,
                 );
             }
         }
     }
 }
 
-fn setup(
+/// Spawns the player at full health and charges for their profile
+pub fn spawn_player(
+    commands: &mut Commands,
+    texture_atlas_store: &TextureAtlasStore,
+    tile_pos: TilePos,
+    user_profile: &UserProfile,
+    windows: &Windows,
+) -> Entity {
+    commands
+        .spawn_bundle(TileResidentBundle::new(
+            user_profile.max_health(),
+            tile_pos,
+            texture_atlas_store.get(&TextureAtlasAsset::HaddockSpritesheet),
+            1,
+        ))
+        .insert(CameraFollow::from_window(windows.primary()))
+        .insert(PowerCharges::new(user_profile.max_power_charges()))
+        .insert(Inventory::with_starfish(user_profile.starfish))
+        .insert(Player)
+        .id()
+}
+
+/// Generates the level's map from [seed], relaxing the minimum size on each failed round. [None]
+/// if every round failed
+fn generate_level_map(seed: u64) -> Option<CellMap<i32>> {
+    let mut min_size = MAP_MIN_SIZE;
+    for _ in 0..=MAP_RELAXED_RETRIES {
+        match crate::map_gen::get_cell_map_seeded(min_size, MAP_MAX_TRIES, seed) {
+            Ok(cell_map) => return Some(cell_map),
+            Err(e) => {
+                warn!("{}, retrying with a smaller minimum size", e);
+                min_size /= 2;
+            }
+        }
+    }
+    error!("Map generation failed, falling back to the safe map");
+    None
+}
+
+pub fn setup(
     mut commands: Commands,
     image_assets: Res<ImageAssetStore>,
     texture_atlas_store: Res<TextureAtlasStore>,
-    global_level_counter: Res<GlobalLevelCounter>,
+    mut global_level_counter: ResMut<GlobalLevelCounter>,
     images: Res<Assets<Image>>,
     loaded_profile: Res<LoadedUserProfile>,
     windows: Res<Windows>,
-    _input: ResMut<Input<KeyCode>>,
+    relax_mode: Res<RelaxMode>,
+    pending_cell_map: Option<Res<PendingCellMap>>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+    mut run_timer: ResMut<RunTimer>,
+    power_charges_this_run: Res<PowerChargesThisRun>,
+    settings: Res<Settings>,
+    mut map_seed: ResMut<MapSeed>,
+    mut last_stand: ResMut<LastStand>,
+    state: Res<State<crate::CoreState>>,
 ) {
-    let border_size = 20usize;
+    // Already running from earlier levels if this isn't the first of the run
+    run_timer.start();
+    if global_level_counter.level() == 1 {
+        *last_stand = LastStand::default();
+    }
+    // Set every level rather than once a run, so a run loaded part way through gets it too
+    global_level_counter.set_difficulty_offset(loaded_profile.user_profile.difficulty_offset());
+    // The demo rolls its own maps, leaving the seed of the player's last run there to retry
+    let run_seed = if *state.current() == crate::CoreState::Demo {
+        rand::thread_rng().gen()
+    } else {
+        match map_seed.seed {
+            Some(seed) if global_level_counter.level() > 1 => seed,
+            _ => map_seed.start_run(),
+        }
+    };
+    let border_size = MAP_BORDER_SIZE;
     let cell_map: CellMap<i32> = {
-        let normalised = crate::map_gen::get_cell_map(50, 50);
+        // A map may already have been generated while replaying its generation
+        let normalised = match pending_cell_map {
+            Some(pending) => {
+                commands.remove_resource::<PendingCellMap>();
+                pending.0.clone()
+            }
+            None => match generate_level_map(MapSeed::level_seed(
+                run_seed,
+                global_level_counter.level(),
+            )) {
+                Some(cell_map) => cell_map,
+                None => {
+                    info_event_writer.send(InfoEvent::FallbackMapUsed);
+                    crate::map_gen::fallback_cell_map()
+                }
+            },
+        };
         normalised.offset((border_size as i32, border_size as i32))
     };
+    // Costs are the distance from the start, so starting in the centre recalculates them from
+    // there. Everything after, like keeping enemies away from the start, works off them as usual
+    let cell_map = match (settings.start_placement, cell_map.central_cell()) {
+        (StartPlacement::Centre, Some(centre)) => cell_map.recalculate(centre),
+        _ => cell_map,
+    };
     println!("Final CellMap: {:?}", cell_map);
     super::tilemap::init_tilemap(&mut commands, &image_assets, &cell_map, border_size);
-    let atlas_handle = texture_atlas_store.get(&TextureAtlasAsset::HaddockSpritesheet);
     let start_point = {
         let start_point = cell_map.start_point().unwrap_or((1, 1));
         TilePos {
@@ -1059,47 +2517,97 @@ fn setup(
             y: start_point.1 as u32,
         }
     };
-    let camera_follow = CameraFollow::from_window(windows.primary());
-    commands
-        .spawn_bundle(TileResidentBundle::new(
-            loaded_profile.user_profile.max_health(),
-            start_point,
-            atlas_handle,
-            1,
-        ))
-        .insert(camera_follow)
-        .insert(PowerCharges::new(
-            loaded_profile.user_profile.max_power_charges(),
-        ))
-        .insert(Player);
-    let mut spawned_positions = Vec::new();
-    let shark_positions =
-        super::enemy::add_sharks(&mut commands, &texture_atlas_store, 7, &cell_map, None);
-    spawned_positions.extend_from_slice(&shark_positions[..]);
-    let crab_positions = super::enemy::add_crabs(
+    let player = spawn_player(
         &mut commands,
         &texture_atlas_store,
-        3,
-        &cell_map,
-        Some(&spawned_positions),
+        start_point,
+        &loaded_profile.user_profile,
+        &windows,
     );
-    spawned_positions.extend_from_slice(&crab_positions[..]);
-    let _jelly_positions = super::enemy::add_jellyfish(
+    if let Some(charges) = power_charges_this_run.0 {
+        commands.entity(player).insert(PowerCharges::new(charges));
+    }
+    // Seeding the exclusions with the area around the start keeps everything spawned after out
+    // of it, giving the player a turn or two before combat
+    let mut spawned_positions = cell_map.cells_within(
+        (start_point.x as i32, start_point.y as i32),
+        SPAWN_PROTECTION_RADIUS,
+    );
+    // Adaptive difficulty only moves what spawns, the level shown is still the real one
+    let level = (global_level_counter.difficulty_level() as i32
+        + loaded_profile.user_profile.adaptive_adjustment())
+    .max(0) as usize;
+    for (def, num_enemies) in super::enemy::spawn_table(level) {
+        // Barnacles only go in corridors, everything else is spread across the map
+        let enemy_positions = if def.enemy_type == EnemyType::Barnacle {
+            super::enemy::add_barnacles(
+                &mut commands,
+                &texture_atlas_store,
+                &image_assets,
+                num_enemies,
+                level,
+                &cell_map,
+                Some(&spawned_positions),
+                relax_mode.enemy_timing(&settings),
+            )
+        } else {
+            super::enemy::add_enemies(
+                &mut commands,
+                &texture_atlas_store,
+                &image_assets,
+                def.enemy_type,
+                num_enemies,
+                level,
+                &cell_map,
+                Some(&spawned_positions),
+                relax_mode.enemy_timing(&settings),
+            )
+        };
+        spawned_positions.extend_from_slice(&enemy_positions[..]);
+    }
+    let (snail_num, snail_positions) = super::snails::choose_number_of_and_spawn_snails(
         &mut commands,
         &texture_atlas_store,
-        1,
         &cell_map,
         Some(&spawned_positions),
     );
-    spawned_positions.extend_from_slice(&crab_positions[..]);
-    let (snail_num, _snail_positions) = super::snails::choose_number_of_and_spawn_snails(
+    info!("Spawned {} snails", snail_num);
+    spawned_positions.extend_from_slice(&snail_positions[..]);
+    if let Some(treasure_position) = super::snails::spawn_treasure_snail(
         &mut commands,
         &texture_atlas_store,
         &cell_map,
         Some(&spawned_positions),
+    ) {
+        info!("Spawned treasure snail at {:?}", treasure_position);
+        spawned_positions.push(treasure_position);
+    }
+    let (shield_num, shield_positions) = super::pickups::choose_number_of_and_spawn_shields(
+        &mut commands,
+        &image_assets,
+        &cell_map,
+        Some(&spawned_positions),
     );
-    info!("Spawned {} snails", snail_num);
-    spawned_positions.extend_from_slice(&crab_positions[..]);
+    info!("Spawned {} shields", shield_num);
+    spawned_positions.extend_from_slice(&shield_positions[..]);
+    if let Some(chest_position) = super::pickups::maybe_spawn_deposit_chest(
+        &mut commands,
+        &image_assets,
+        &settings,
+        &cell_map,
+        Some(&spawned_positions),
+    ) {
+        info!("Spawned deposit chest at {:?}", chest_position);
+        spawned_positions.push(chest_position);
+    }
+    if let Some(compass_position) = super::compass::maybe_spawn_compass(
+        &mut commands,
+        &image_assets,
+        &cell_map,
+        Some(&spawned_positions),
+    ) {
+        info!("Spawned compass at {:?}", compass_position);
+    }
     commands.insert_resource(cell_map);
     let regular_game_enable = RegularGameEnable {
         enabled: false,
@@ -1108,7 +2616,8 @@ fn setup(
 
     commands.insert_resource(regular_game_enable);
     info!(
-        "Completed Setup for level :{}",
-        global_level_counter.level()
+        "Completed Setup for level :{} (difficulty {})",
+        global_level_counter.level(),
+        level
     );
 }
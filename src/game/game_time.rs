@@ -0,0 +1,173 @@
+use crate::profiles::settings::{HitStopMode, Settings};
+use bevy::app::{App, CoreStage};
+use bevy::ecs::prelude::{Res, ResMut};
+use bevy::prelude::Plugin;
+use bevy::time::Time;
+use std::time::Duration;
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        // Updated ahead of [CoreStage::Update] so every gameplay system sees this frame's delta
+        app.insert_resource(GameTime::default())
+            .insert_resource(HitStopSettings::default())
+            .add_system_to_stage(CoreStage::PreUpdate, game_time_system)
+            .add_system(hit_stop_settings_sync_system);
+    }
+}
+
+/// Gameplay clock, driven by the real [Time] but able to be paused or scaled independently.
+/// Gameplay animations should tick from this so they freeze along with an in-level pause,
+/// while menus and UI keep using [Time]
+#[derive(Debug)]
+pub struct GameTime {
+    delta: Duration,
+    scale: f32,
+    paused: bool,
+    /// Real time left on the current hit-stop, tracked apart from [paused] so the two can't undo
+    /// each other
+    hit_stop_remaining: Duration,
+}
+
+impl Default for GameTime {
+    fn default() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            scale: 1.0,
+            paused: false,
+            hit_stop_remaining: Duration::ZERO,
+        }
+    }
+}
+
+/// Resource configuring the brief gameplay freeze on impactful kills, which kills freeze is kept in
+/// step with [Settings::hit_stop]
+#[derive(Debug)]
+pub struct HitStopSettings {
+    pub duration: Duration,
+    /// Freeze when the power's projectile kills an enemy
+    pub on_power_kill: bool,
+    /// Freeze when the player kills an enemy by moving into it
+    pub on_melee_kill: bool,
+}
+
+impl Default for HitStopSettings {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(80),
+            on_power_kill: true,
+            on_melee_kill: true,
+        }
+    }
+}
+
+fn hit_stop_settings_sync_system(
+    settings: Res<Settings>,
+    mut hit_stop_settings: ResMut<HitStopSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (on_power_kill, on_melee_kill) = match settings.hit_stop {
+        HitStopMode::Off => (false, false),
+        HitStopMode::PowerKills => (true, false),
+        HitStopMode::AllKills => (true, true),
+    };
+    if hit_stop_settings.on_power_kill != on_power_kill
+        || hit_stop_settings.on_melee_kill != on_melee_kill
+    {
+        hit_stop_settings.on_power_kill = on_power_kill;
+        hit_stop_settings.on_melee_kill = on_melee_kill;
+    }
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+
+    /// Freezes gameplay for [duration] of real time. Overlapping hit-stops don't stack, the
+    /// longer one wins
+    pub fn hit_stop(&mut self, duration: Duration) {
+        self.hit_stop_remaining = self.hit_stop_remaining.max(duration);
+    }
+
+    pub fn in_hit_stop(&self) -> bool {
+        !self.hit_stop_remaining.is_zero()
+    }
+
+    fn update(&mut self, real_delta: Duration) {
+        self.delta = if self.in_hit_stop() {
+            self.hit_stop_remaining = self.hit_stop_remaining.saturating_sub(real_delta);
+            Duration::ZERO
+        } else if self.paused {
+            Duration::ZERO
+        } else {
+            real_delta.mul_f32(self.scale)
+        };
+    }
+}
+
+fn game_time_system(time: Res<Time>, mut game_time: ResMut<GameTime>) {
+    game_time.update(time.delta());
+}
+
+#[cfg(test)]
+mod test {
+    use super::GameTime;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pause_and_scale() {
+        let mut game_time = GameTime::default();
+        game_time.update(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(100), game_time.delta());
+
+        game_time.set_scale(0.5);
+        game_time.update(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(50), game_time.delta());
+
+        game_time.set_paused(true);
+        game_time.update(Duration::from_millis(100));
+        assert_eq!(Duration::ZERO, game_time.delta());
+    }
+
+    #[test]
+    fn test_hit_stop() {
+        let mut game_time = GameTime::default();
+        game_time.hit_stop(Duration::from_millis(80));
+        // A shorter hit-stop whilst one is running doesn't cut it short
+        game_time.hit_stop(Duration::from_millis(20));
+        game_time.update(Duration::from_millis(50));
+        assert_eq!(Duration::ZERO, game_time.delta());
+        assert!(game_time.in_hit_stop());
+
+        game_time.update(Duration::from_millis(50));
+        assert_eq!(Duration::ZERO, game_time.delta());
+        assert!(!game_time.in_hit_stop());
+
+        game_time.update(Duration::from_millis(50));
+        assert_eq!(Duration::from_millis(50), game_time.delta());
+    }
+}
@@ -0,0 +1,149 @@
+//! A faint trail of the tiles the player crossed on a previous attempt at the current map, to
+//! compare routes against. The player's path is recorded through each level and kept on the
+//! profile as its [SeedPaths], so retrying the seed shows the last attempt at each level. In debug
+//! builds quick-loading also sets [GhostPath], to the path taken since the quick-save.
+
+use crate::game::components::Player;
+use crate::game::game::MapSeed;
+use crate::game::tilemap::TilePosExt;
+use crate::game::turn::GlobalLevelCounter;
+use crate::helpers::cleanup::recursive_cleanup;
+use crate::profiles::profiles::{LoadedUserProfile, SeedPaths};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+pub struct GhostPathPlugin;
+
+impl Plugin for GhostPathPlugin {
+    fn build(&self, app: &mut App) {
+        let state = crate::CoreState::GameLevel;
+        app.init_resource::<RecordedPath>()
+            .init_resource::<GhostPath>()
+            // After the level setup, which picks the run's seed
+            .add_system_set(
+                SystemSet::on_enter(state).with_system(ghost_path_setup.after("level_setup")),
+            )
+            .add_system_set(
+                SystemSet::on_update(state)
+                    .with_system(record_path_system)
+                    .with_system(ghost_path_render_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(state)
+                    .with_system(recursive_cleanup::<GhostMarker>)
+                    .with_system(ghost_path_cleanup),
+            );
+    }
+}
+
+/// The tiles the player has stood on this level, in order
+#[derive(Debug, Default)]
+pub struct RecordedPath {
+    pub tiles: Vec<TilePos>,
+    /// The level being recorded, noted on entering it as the run may be over by the time it's
+    /// stored
+    level: usize,
+}
+
+/// A previous attempt's [RecordedPath] on the current map, drawn while set
+#[derive(Debug, Default)]
+pub struct GhostPath(pub Vec<TilePos>);
+
+#[derive(Component)]
+struct GhostMarker;
+
+const GHOST_MARKER_COLOUR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+const GHOST_MARKER_SIZE: f32 = 16.0;
+/// Above the tilemap, below everything standing on it
+const GHOST_MARKER_Z: f32 = 1.0;
+
+fn record_path_system(
+    player_query: Query<&TilePos, (With<Player>, Changed<TilePos>)>,
+    mut recorded_path: ResMut<RecordedPath>,
+) {
+    if let Ok(tile_pos) = player_query.get_single() {
+        if recorded_path.tiles.last() != Some(tile_pos) {
+            recorded_path.tiles.push(*tile_pos);
+        }
+    }
+}
+
+/// Shows the path taken through this level on the last attempt at the run's seed. Only a retry
+/// plays a seed again, so a new run's never matches the one recorded
+fn ghost_path_setup(
+    map_seed: Res<MapSeed>,
+    global_level_counter: Res<GlobalLevelCounter>,
+    loaded_profile: Res<LoadedUserProfile>,
+    mut recorded_path: ResMut<RecordedPath>,
+    mut ghost_path: ResMut<GhostPath>,
+) {
+    recorded_path.level = global_level_counter.level();
+    let seed_paths = loaded_profile.user_profile.seed_paths.as_ref();
+    if let (Some(seed), Some(seed_paths)) = (map_seed.seed, seed_paths) {
+        if seed_paths.seed == seed {
+            ghost_path.0 = seed_paths
+                .level(recorded_path.level)
+                .iter()
+                .map(|(x, y)| TilePos { x: *x, y: *y })
+                .collect();
+        }
+    }
+}
+
+/// Redraws the ghost's markers whenever the [GhostPath] changes
+fn ghost_path_render_system(
+    mut commands: Commands,
+    ghost_path: Res<GhostPath>,
+    marker_query: Query<Entity, With<GhostMarker>>,
+) {
+    if !ghost_path.is_changed() {
+        return;
+    }
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for tile_pos in ghost_path.0.iter() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: GHOST_MARKER_COLOUR,
+                    custom_size: Some(Vec2::splat(GHOST_MARKER_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(tile_pos.to_world_pos(GHOST_MARKER_Z)),
+                ..Default::default()
+            })
+            .insert(GhostMarker);
+    }
+}
+
+/// Keeps the level's path on the profile, for a retry of the seed. The next level is a different
+/// map, so neither path carries over. The profile is saved after this, on entering the hub or the
+/// next level
+fn ghost_path_cleanup(
+    map_seed: Res<MapSeed>,
+    mut loaded_profile: ResMut<LoadedUserProfile>,
+    mut recorded_path: ResMut<RecordedPath>,
+    mut ghost_path: ResMut<GhostPath>,
+) {
+    if let Some(seed) = map_seed.seed {
+        let user_profile = &mut loaded_profile.user_profile;
+        let recorded_seed = user_profile
+            .seed_paths
+            .as_ref()
+            .map(|seed_paths| seed_paths.seed);
+        if recorded_seed != Some(seed) {
+            user_profile.seed_paths = Some(SeedPaths::new(seed));
+        }
+        if let Some(seed_paths) = user_profile.seed_paths.as_mut() {
+            let path = recorded_path
+                .tiles
+                .iter()
+                .map(|tile_pos| (tile_pos.x, tile_pos.y))
+                .collect();
+            seed_paths.record(recorded_path.level, path);
+        }
+    }
+    recorded_path.tiles.clear();
+    ghost_path.0.clear();
+}
@@ -0,0 +1,110 @@
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Resource holding which key triggers each in-game action. Input handling and the overlay's help
+/// view both read from here, so remapping a key only needs changing it here
+///
+/// Saved as [Settings::key_bindings] and kept in step with it. Keys are remapped by editing the
+/// settings file, there's no screen for it in game
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// Opens and closes the overlay, also backs out of aiming the power
+    pub pause: KeyCode,
+    pub move_up: KeyCode,
+    pub move_down: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    /// Held with a move key to turn on the spot instead of moving
+    pub turn_modifier: KeyCode,
+    pub wait: KeyCode,
+    pub power: KeyCode,
+    /// Held over turns to charge the power, firing on release
+    pub charge_power: KeyCode,
+    pub hook: KeyCode,
+    pub toggle_turn_counter: KeyCode,
+    /// From the overlay, asks to quit to the desktop
+    pub quit: KeyCode,
+    /// Accepts whatever's being asked, e.g. quitting once asked to
+    pub confirm: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: KeyCode::Escape,
+            move_up: KeyCode::W,
+            move_down: KeyCode::S,
+            move_left: KeyCode::A,
+            move_right: KeyCode::D,
+            turn_modifier: KeyCode::LShift,
+            wait: KeyCode::Space,
+            power: KeyCode::Q,
+            charge_power: KeyCode::E,
+            hook: KeyCode::R,
+            toggle_turn_counter: KeyCode::T,
+            quit: KeyCode::X,
+            confirm: KeyCode::Return,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Every binding as (action description, key name), in the order shown in the help view
+    pub fn controls(&self) -> Vec<(&'static str, String)> {
+        [
+            ("Move Up", &self.move_up),
+            ("Move Down", &self.move_down),
+            ("Move Left", &self.move_left),
+            ("Move Right", &self.move_right),
+            ("Turn (hold with a move)", &self.turn_modifier),
+            ("Wait", &self.wait),
+            ("Power", &self.power),
+            ("Charge Power (hold)", &self.charge_power),
+            ("Hook", &self.hook),
+            ("Toggle Turn Counter", &self.toggle_turn_counter),
+            ("Pause", &self.pause),
+            ("Quit (when paused)", &self.quit),
+            ("Confirm", &self.confirm),
+        ]
+        .into_iter()
+        .map(|(action, key)| (action, format!("{:?}", key)))
+        .collect()
+    }
+}
+
+pub fn key_bindings_sync_system(settings: Res<Settings>, mut key_bindings: ResMut<KeyBindings>) {
+    if settings.is_changed() && *key_bindings != settings.key_bindings {
+        *key_bindings = settings.key_bindings.clone();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyBindings;
+    use bevy::prelude::KeyCode;
+
+    #[test]
+    fn test_missing_bindings_default() {
+        // As in a settings file written before a binding was added, or only remapping some
+        let key_bindings: KeyBindings = ron::de::from_str("(pause: P)").unwrap();
+        assert_eq!(KeyCode::P, key_bindings.pause);
+        assert_eq!(KeyBindings::default().move_up, key_bindings.move_up);
+    }
+
+    #[test]
+    fn test_controls_follow_remapping() {
+        let mut key_bindings = KeyBindings::default();
+        let pause_key = |key_bindings: &KeyBindings| {
+            key_bindings
+                .controls()
+                .into_iter()
+                .find(|(action, _)| *action == "Pause")
+                .map(|(_, key)| key)
+        };
+        assert_eq!(Some("Escape".to_string()), pause_key(&key_bindings));
+        key_bindings.pause = KeyCode::P;
+        assert_eq!(Some("P".to_string()), pause_key(&key_bindings));
+    }
+}
@@ -0,0 +1,65 @@
+//! A soft radial light around the player, darkening map tiles with distance from them. Only the
+//! tiles are tinted, so enemies and pickups stay in plain view. The strength comes from
+//! [Settings::lighting_intensity], where 0.0 leaves the map evenly lit
+
+use crate::game::components::Player;
+use crate::game::tilemap::{HasTileType, TilePosExt};
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::{TileColor, TilePos};
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        // The tiles, and so their tint, are despawned with the map on leaving the level
+        app.add_system_set(
+            SystemSet::on_update(crate::CoreState::GameLevel).with_system(tile_lighting_system),
+        );
+    }
+}
+
+/// Distance in tiles at which the light is at its dimmest
+const LIGHT_RADIUS: f32 = 6.0;
+
+/// Brightness of a tile [distance] tiles from the player, from 1.0 under the player down to
+/// 1.0 - [intensity] at [LIGHT_RADIUS] and beyond
+pub fn light_level(distance: usize, intensity: f32) -> f32 {
+    let falloff = (distance as f32 / LIGHT_RADIUS).min(1.0);
+    1.0 - intensity.clamp(0.0, 1.0) * falloff
+}
+
+fn tile_lighting_system(
+    settings: Res<Settings>,
+    player_query: Query<(&TilePos, ChangeTrackers<TilePos>), With<Player>>,
+    mut tile_query: Query<(&TilePos, &mut TileColor), With<HasTileType>>,
+) {
+    let (player_pos, change_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    if !(change_tracker.is_changed() || settings.is_changed()) {
+        return;
+    }
+    for (tile_pos, mut tile_colour) in tile_query.iter_mut() {
+        let level = light_level(
+            tile_pos.distance_to(player_pos),
+            settings.lighting_intensity,
+        );
+        tile_colour.0 = Color::rgb(level, level, level);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{light_level, LIGHT_RADIUS};
+
+    #[test]
+    fn test_light_level() {
+        assert_eq!(1.0, light_level(0, 0.7));
+        assert_eq!(0.5, light_level(LIGHT_RADIUS as usize / 2, 1.0));
+        assert_eq!(0.5, light_level(100, 0.5));
+        // No intensity is flat lighting
+        assert_eq!(1.0, light_level(100, 0.0));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::asset_handling::asset::ImageAsset;
+use crate::asset_handling::ImageAssetStore;
+use crate::game::components::GameCamera;
+use crate::helpers::cleanup::recursive_cleanup;
+use crate::map_gen::cell_map::CellMap;
+use crate::map_gen::Grid;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+pub struct MapGenReplayPlugin;
+
+impl Plugin for MapGenReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let state = crate::CoreState::GameLevelTransition;
+        app.add_system_set(SystemSet::on_enter(state).with_system(replay_setup))
+            .add_system_set(SystemSet::on_update(state).with_system(replay_system))
+            .add_system_set(
+                SystemSet::on_exit(state)
+                    .with_system(recursive_cleanup::<MapGenReplayOnly>)
+                    .with_system(replay_cleanup),
+            );
+    }
+}
+
+/// A map generated ahead of the level starting, to be used by the level setup in place of
+/// generating a fresh one
+pub struct PendingCellMap(pub CellMap<i32>);
+
+/// Steps through the grids recorded while generating the next level's map, one per tick
+pub struct MapGenReplay {
+    frames: Vec<Grid>,
+    index: usize,
+    timer: Timer,
+}
+
+impl MapGenReplay {
+    pub fn finished(&self) -> bool {
+        self.index >= self.frames.len()
+    }
+}
+
+#[derive(Component)]
+struct MapGenReplayOnly;
+
+const FRAME_DURATION_S: f32 = 0.3;
+const REPLAY_SCALE: f32 = 0.5;
+const TILE_SIZE: f32 = 64.0;
+
+fn replay_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    image_assets: Res<ImageAssetStore>,
+    camera_query: Query<&Transform, With<GameCamera>>,
+) {
+    if !settings.map_gen_replay {
+        return;
+    }
+    // Without a replay the level setup generates (or falls back) by itself
+    let (cell_map, frames) = match crate::map_gen::get_cell_map_with_history(
+        super::game::MAP_MIN_SIZE,
+        super::game::MAP_MAX_TRIES,
+    ) {
+        Ok(generated) => generated,
+        Err(e) => {
+            warn!("Skipping map generation replay: {}", e);
+            return;
+        }
+    };
+
+    // Centre the replay on the camera, wherever the last level left it
+    let grid_size = frames[0].grid_size;
+    let camera_pos = camera_query.single().translation;
+    let transform = Transform {
+        translation: Vec3::new(
+            camera_pos.x - (grid_size.0 as f32 * TILE_SIZE * REPLAY_SCALE) / 2.0,
+            camera_pos.y - (grid_size.1 as f32 * TILE_SIZE * REPLAY_SCALE) / 2.0,
+            0.0,
+        ),
+        scale: Vec3::splat(REPLAY_SCALE),
+        ..Default::default()
+    };
+
+    let tilemap_size = TilemapSize {
+        x: grid_size.0 as u32,
+        y: grid_size.1 as u32,
+    };
+    let mut tile_storage = TileStorage::empty(tilemap_size);
+    let tilemap_entity = commands.spawn().id();
+    for y in 0..grid_size.1 {
+        for x in 0..grid_size.0 {
+            let tile_pos = TilePos {
+                x: x as u32,
+                y: y as u32,
+            };
+            let tile_entity = commands
+                .spawn_bundle(TileBundle {
+                    position: tile_pos,
+                    texture: frames[0].tile_type((x, y)).to_raw_tile(),
+                    tilemap_id: TilemapId(tilemap_entity),
+                    ..Default::default()
+                })
+                .insert(MapGenReplayOnly)
+                .id();
+            tile_storage.set(&tile_pos, Some(tile_entity));
+        }
+    }
+    commands
+        .entity(tilemap_entity)
+        .insert_bundle(TilemapBundle {
+            grid_size: TilemapGridSize { x: 8.0, y: 8.0 },
+            size: tilemap_size,
+            storage: tile_storage,
+            texture: TilemapTexture(image_assets.get(&ImageAsset::TileMapSpritesheet)),
+            tile_size: TilemapTileSize {
+                x: TILE_SIZE,
+                y: TILE_SIZE,
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(MapGenReplayOnly);
+
+    commands.insert_resource(PendingCellMap(cell_map));
+    commands.insert_resource(MapGenReplay {
+        frames,
+        index: 0,
+        timer: Timer::from_seconds(FRAME_DURATION_S, true),
+    });
+}
+
+/// Advances the replay a frame each tick, redrawing the tiles. Any key or click skips to the end
+fn replay_system(
+    maybe_replay: Option<ResMut<MapGenReplay>>,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut tile_query: Query<(&TilePos, &mut TileTexture), With<MapGenReplayOnly>>,
+) {
+    if let Some(mut replay) = maybe_replay {
+        if replay.finished() {
+            return;
+        }
+        let skip = keyboard_input.get_just_pressed().next().is_some()
+            || mouse_input.get_just_pressed().next().is_some();
+        if skip {
+            info!("Map generation replay skipped");
+            replay.index = replay.frames.len();
+            return;
+        }
+        replay.timer.tick(time.delta());
+        if replay.timer.just_finished() {
+            replay.index += 1;
+            if let Some(frame) = replay.frames.get(replay.index) {
+                for (tile_pos, mut texture) in tile_query.iter_mut() {
+                    *texture = frame
+                        .tile_type((tile_pos.x as i32, tile_pos.y as i32))
+                        .to_raw_tile();
+                }
+            }
+        }
+    }
+}
+
+fn replay_cleanup(mut commands: Commands) {
+    commands.remove_resource::<MapGenReplay>();
+}
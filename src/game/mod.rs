@@ -1,17 +1,38 @@
+mod balance;
+mod compass;
 pub mod components;
 mod debug;
+mod demo;
+mod dev_console;
 mod end_game;
 mod enemy;
 mod events;
 mod game;
+mod game_time;
+mod ghost_path;
+pub mod key_bindings;
+mod lighting;
+mod map_gen_replay;
 mod movement;
+mod objective;
+mod pacifist;
+mod pickups;
 mod projectile;
+mod quick_save;
+mod relax_mode;
+mod rumble;
+pub mod run_timer;
 mod snails;
+mod sonar;
+mod sudden_death;
 mod tilemap;
 mod timed_removal;
 mod turn;
+mod turn_review;
 mod ui;
 mod ui_overlay;
+mod virtual_cursor;
 
 pub use game::GamePlugin as Plugin;
+pub use game::{MapSeed, RunMode};
 pub use ui_overlay::GameOverlayPlugin;
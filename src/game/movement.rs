@@ -1,24 +1,26 @@
-use crate::game::components::{Facing, Health, MapDirection, MovementAnimate, Player};
+use crate::game::balance::AggroBalance;
+use crate::game::components::{Facing, Health, Inventory, MapDirection, MovementAnimate, Player};
 use crate::game::enemy::Enemy;
 use crate::game::tilemap::{HasTileType, TilePosExt, TileStorageQuery};
 use bevy::prelude::*;
 use bevy_ecs_tilemap::tiles::TilePos;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct AttackAndMaybeMove {
     attack_target_pos: TilePos,
     attack_target_entity: Entity,
     direction: MapDirection,
     position_before_enemy: Option<TilePos>,
+    damage: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum MoveDecision {
     Move((TilePos, MapDirection)),
     Nothing,
     AttackAndMaybeMove(AttackAndMaybeMove),
-    AttackAndDontMove((Entity, MapDirection)),
+    AttackAndDontMove((Entity, MapDirection, usize)),
     Turn(MapDirection),
 }
 
@@ -33,6 +35,17 @@ impl MoveDecision {
             }) => Some(*tilepos),
         }
     }
+
+    pub fn attack_target(&self) -> Option<Entity> {
+        match self {
+            Self::Nothing | Self::Turn(_) | Self::Move(_) => None,
+            Self::AttackAndDontMove((entity, _, _))
+            | Self::AttackAndMaybeMove(AttackAndMaybeMove {
+                attack_target_entity: entity,
+                ..
+            }) => Some(*entity),
+        }
+    }
 }
 
 pub type MoveDecisions = HashMap<Entity, MoveDecision>;
@@ -52,9 +65,9 @@ impl AttackCriteria {
             move_on_attack: true,
         }
     }
-    pub fn for_enemy(can_attack_directly: bool) -> Self {
+    pub fn for_enemy(can_attack_directly: bool, damage: usize) -> Self {
         Self {
-            damage: 1,
+            damage,
             can_attack_enemy: false,
             can_attack_player: can_attack_directly,
             move_on_attack: false,
@@ -75,8 +88,61 @@ fn attack_decision(
             attack_target_entity: target_entity,
             direction: move_direction,
             position_before_enemy: previous_tilepos.to_owned().cloned(),
+            damage: attack_criteria.damage,
         }),
-        (false, None) => MoveDecision::AttackAndDontMove((target_entity, move_direction)),
+        (false, None) => {
+            MoveDecision::AttackAndDontMove((target_entity, move_direction, attack_criteria.damage))
+        }
+    }
+}
+
+/// What a resident standing on a tile is, as far as moving onto it goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccupantKind {
+    Player,
+    Enemy,
+}
+
+/// Index of which player or enemy stands on each tile, built once per phase so move decisions
+/// look tiles up rather than scanning every resident for each one
+#[derive(Debug, Default)]
+pub struct Occupancy(HashMap<TilePos, (Entity, OccupantKind)>);
+
+impl Occupancy {
+    pub fn from_query(
+        move_query: &Query<(Entity, &TilePos, Option<&Player>, Option<&Enemy>)>,
+    ) -> Self {
+        Self::from_residents(move_query.iter().filter_map(
+            |(entity, tilepos, maybe_player, maybe_enemy)| {
+                let kind = if maybe_player.is_some() {
+                    OccupantKind::Player
+                } else if maybe_enemy.is_some() {
+                    OccupantKind::Enemy
+                } else {
+                    return None;
+                };
+                Some((*tilepos, entity, kind))
+            },
+        ))
+    }
+
+    /// Where two share a tile the first listed is kept, as a scan in the same order would find
+    pub fn from_residents(
+        residents: impl IntoIterator<Item = (TilePos, Entity, OccupantKind)>,
+    ) -> Self {
+        let mut occupants = HashMap::new();
+        for (tilepos, entity, kind) in residents {
+            occupants.entry(tilepos).or_insert((entity, kind));
+        }
+        Self(occupants)
+    }
+
+    pub fn get(&self, tilepos: &TilePos) -> Option<(Entity, OccupantKind)> {
+        self.0.get(tilepos).copied()
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = &TilePos> {
+        self.0.keys()
     }
 }
 
@@ -85,10 +151,38 @@ pub fn decide_move(
     move_direction: &MapDirection,
     max_move_distance: usize,
     attack_criteria: &AttackCriteria,
-    move_query: Query<(Entity, &TilePos, Option<&Player>, Option<&Enemy>)>,
+    occupancy: &Occupancy,
     tile_storage_query: &TileStorageQuery,
     tile_type_query: &Query<&HasTileType>,
-    additional_ignore_tilepos: &Vec<TilePos>,
+    additional_ignore_tilepos: &[TilePos],
+) -> MoveDecision {
+    let tile_storage = tile_storage_query.single();
+    decide_move_on(
+        current_pos,
+        move_direction,
+        max_move_distance,
+        attack_criteria,
+        occupancy,
+        |tilepos| {
+            let new_tile_entity = tile_storage.get(tilepos).unwrap();
+            match tile_type_query.get(new_tile_entity) {
+                Ok(HasTileType(tt)) => tt.can_enter(),
+                Err(_) => false,
+            }
+        },
+        additional_ignore_tilepos,
+    )
+}
+
+/// [decide_move] with the map abstracted to [can_enter], whether a tile can be moved onto at all
+fn decide_move_on(
+    current_pos: &TilePos,
+    move_direction: &MapDirection,
+    max_move_distance: usize,
+    attack_criteria: &AttackCriteria,
+    occupancy: &Occupancy,
+    can_enter: impl Fn(&TilePos) -> bool,
+    additional_ignore_tilepos: &[TilePos],
 ) -> MoveDecision {
     let destination_tilepos_list = {
         let mut v = Vec::new();
@@ -102,27 +196,14 @@ pub fn decide_move(
     };
 
     let mut decision = MoveDecision::Turn(move_direction.clone());
-    let mut stopped_early = false;
 
     // This target is used for an enemy hitting but not killing when moving >1 square
     // - they should still move next to where they attacked
     let mut previous_move_target = None;
 
     for destination_tilepos in destination_tilepos_list.iter() {
-        if stopped_early {
-            break;
-        }
-
-        let new_tile_entity = tile_storage_query
-            .single()
-            .get(destination_tilepos)
-            .unwrap();
-        let can_move = match tile_type_query.get(new_tile_entity) {
-            Ok(HasTileType(tt)) => tt.can_enter(),
-            Err(_) => false,
-        };
-
-        let can_move = can_move && !additional_ignore_tilepos.contains(destination_tilepos);
+        let can_move = can_enter(destination_tilepos)
+            && !additional_ignore_tilepos.contains(destination_tilepos);
 
         if !can_move {
             break;
@@ -130,38 +211,23 @@ pub fn decide_move(
             decision = MoveDecision::Move((*destination_tilepos, move_direction.clone()))
         }
 
-        for (target_entity, tilepos, maybe_player, maybe_enemy) in move_query.iter() {
-            if tilepos.eq(destination_tilepos) {
-                if maybe_player.is_some() {
-                    if attack_criteria.can_attack_player {
-                        decision = attack_decision(
-                            attack_criteria,
-                            *destination_tilepos,
-                            &previous_move_target,
-                            move_direction.clone(),
-                            target_entity,
-                        );
-                    } else {
-                        decision = MoveDecision::Turn(move_direction.clone());
-                    }
-                    stopped_early = true;
-                    break;
-                } else if maybe_enemy.is_some() {
-                    if attack_criteria.can_attack_enemy {
-                        decision = attack_decision(
-                            attack_criteria,
-                            *destination_tilepos,
-                            &previous_move_target,
-                            move_direction.clone(),
-                            target_entity,
-                        );
-                    } else {
-                        decision = MoveDecision::Turn(move_direction.clone());
-                    }
-                    stopped_early = true;
-                    break;
-                }
-            }
+        if let Some((target_entity, kind)) = occupancy.get(destination_tilepos) {
+            let can_attack = match kind {
+                OccupantKind::Player => attack_criteria.can_attack_player,
+                OccupantKind::Enemy => attack_criteria.can_attack_enemy,
+            };
+            decision = if can_attack {
+                attack_decision(
+                    attack_criteria,
+                    *destination_tilepos,
+                    &previous_move_target,
+                    move_direction.clone(),
+                    target_entity,
+                )
+            } else {
+                MoveDecision::Turn(move_direction.clone())
+            };
+            break;
         }
 
         previous_move_target = Some(destination_tilepos)
@@ -173,17 +239,17 @@ pub fn apply_move_single(
     entity: Entity,
     move_decision: &MoveDecision,
     move_query: &mut Query<(&mut TilePos, &mut MovementAnimate, &Transform, &mut Facing)>,
-    health_query: &mut Query<&mut Health>,
+    health_query: &mut Query<(&mut Health, Option<&mut Inventory>)>,
 ) {
     let (maybe_tilepos, maybe_facing) = match move_decision {
         MoveDecision::Nothing => (None, None),
         MoveDecision::Turn(facing) => (None, Some(facing)),
         MoveDecision::Move((tilepos, facing)) => (Some(tilepos), Some(facing)),
-        MoveDecision::AttackAndDontMove((target, facing)) => {
+        MoveDecision::AttackAndDontMove((target, facing, damage)) => {
             let target_health = health_query.get_mut(*target);
             match target_health {
-                Ok(mut health) => {
-                    health.decr_by(1);
+                Ok((mut health, maybe_inventory)) => {
+                    Health::decr_by_shielded(&mut health, *damage, maybe_inventory);
                 }
                 Err(e) => warn!("Error getting health to attack: {:?}", e),
             }
@@ -194,11 +260,13 @@ pub fn apply_move_single(
             attack_target_entity,
             direction,
             position_before_enemy,
+            damage,
         }) => {
             let target_health = health_query.get_mut(*attack_target_entity);
             let result_tilepos = match target_health {
-                Ok(mut health) => {
-                    health.decr_by(1);
+                Ok((mut health, maybe_inventory)) => {
+                    Health::decr_by_shielded(&mut health, *damage, maybe_inventory);
+                    // Only move onto the target's tile if this hit killed it
                     if health.hp == 0 {
                         Some(attack_target_pos)
                     } else if let Some(previous_tilepos) = position_before_enemy {
@@ -230,13 +298,482 @@ pub fn apply_move_single(
     }
 }
 
+/// The tile [pos] would be pushed onto by a knockback in [direction], if it can be entered and
+/// nothing is already there
+pub fn knockback_destination(
+    pos: &TilePos,
+    direction: &MapDirection,
+    occupied_query: &Query<&TilePos, (With<Enemy>, Without<Player>)>,
+    tile_storage_query: &TileStorageQuery,
+    tile_type_query: &Query<&HasTileType>,
+) -> Option<TilePos> {
+    let destination = pos.checked_add(direction.to_pos_move())?;
+    let occupied: Vec<TilePos> = occupied_query.iter().copied().collect();
+    // Only the destination itself will do, a knockback never lands anywhere else
+    nearest_free_tile(
+        &destination,
+        0,
+        tile_storage_query,
+        tile_type_query,
+        &occupied,
+    )
+}
+
+/// Tiles within [radius] steps of [center] that can be entered and aren't in [occupied], nearest
+/// first with ties broken by position so the order never depends on the caller
+pub fn free_tiles_near(
+    center: &TilePos,
+    radius: usize,
+    tile_storage_query: &TileStorageQuery,
+    tile_type_query: &Query<&HasTileType>,
+    occupied: &[TilePos],
+) -> Vec<TilePos> {
+    let tile_storage = tile_storage_query.single();
+    free_tiles_near_on(
+        center,
+        radius,
+        |tilepos| match tile_storage.get(tilepos) {
+            Some(tile_entity) => match tile_type_query.get(tile_entity) {
+                Ok(HasTileType(tt)) => tt.can_enter(),
+                Err(_) => false,
+            },
+            None => false,
+        },
+        occupied,
+    )
+}
+
+/// The closest of [free_tiles_near], if there are any
+pub fn nearest_free_tile(
+    center: &TilePos,
+    radius: usize,
+    tile_storage_query: &TileStorageQuery,
+    tile_type_query: &Query<&HasTileType>,
+    occupied: &[TilePos],
+) -> Option<TilePos> {
+    free_tiles_near(
+        center,
+        radius,
+        tile_storage_query,
+        tile_type_query,
+        occupied,
+    )
+    .into_iter()
+    .next()
+}
+
+/// [free_tiles_near] against any map, [can_enter] saying which tiles can be entered
+fn free_tiles_near_on(
+    center: &TilePos,
+    radius: usize,
+    can_enter: impl Fn(&TilePos) -> bool,
+    occupied: &[TilePos],
+) -> Vec<TilePos> {
+    let radius = radius as i32;
+    let mut tiles: Vec<TilePos> = (-radius..=radius)
+        .flat_map(|dx| (-radius..=radius).map(move |dy| (dx, dy)))
+        .filter(|(dx, dy)| dx.abs() + dy.abs() <= radius)
+        .filter_map(|offset| center.checked_add(offset))
+        .filter(|tilepos| can_enter(tilepos) && !occupied.contains(tilepos))
+        .collect();
+    tiles.sort_by_key(|tilepos| (tilepos.distance_to(center), tilepos.x, tilepos.y));
+    tiles
+}
+
 pub fn apply_move(
     move_decisions: MoveDecisions,
     mut move_query: Query<(&mut TilePos, &mut MovementAnimate, &Transform, &mut Facing)>,
-    mut health_query: Query<&mut Health>,
+    mut health_query: Query<(&mut Health, Option<&mut Inventory>)>,
 ) {
     //Apply decisions:
     for (entity, decision) in move_decisions.iter() {
         apply_move_single(*entity, decision, &mut move_query, &mut health_query)
     }
 }
+
+/// A random free tile orthogonally next to [target] for a teleporting enemy to land on, facing
+/// [target]. Free means it can be entered, nobody in [occupancy] stands on it and it isn't in
+/// [additional_ignore_tilepos]
+pub fn teleport_destination(
+    target: &TilePos,
+    occupancy: &Occupancy,
+    tile_storage_query: &TileStorageQuery,
+    tile_type_query: &Query<&HasTileType>,
+    additional_ignore_tilepos: &[TilePos],
+) -> Option<MoveDecision> {
+    use rand::seq::SliceRandom;
+    let occupied: Vec<TilePos> = occupancy
+        .tiles()
+        .chain(additional_ignore_tilepos.iter())
+        .copied()
+        .collect();
+    let free_tiles = free_tiles_near(target, 1, tile_storage_query, tile_type_query, &occupied);
+    let candidates: Vec<(TilePos, MapDirection)> = MapDirection::ALL
+        .iter()
+        .filter_map(|direction| {
+            let destination = target.checked_add(direction.to_pos_move())?;
+            Some((destination, direction.opposite()))
+        })
+        .filter(|(destination, _)| free_tiles.contains(destination))
+        .collect();
+    candidates
+        .choose(&mut rand::thread_rng())
+        .cloned()
+        .map(MoveDecision::Move)
+}
+
+/// Resource of the enemies still to decide their move this enemy phase, along with what's been
+/// decided so far, so the decisions can be spread across a few frames. Every decision is made
+/// against [EnemyPhaseQueue::occupancy] from the start of the phase and nothing moves until all
+/// are made, so how they're batched never changes the outcome
+#[derive(Debug, Default)]
+pub struct EnemyPhaseQueue {
+    pending: Option<VecDeque<Entity>>,
+    pub occupancy: Occupancy,
+    pub move_decisions: MoveDecisions,
+    /// Tiles already claimed by decisions this phase
+    pub moved_to: Vec<TilePos>,
+}
+
+impl EnemyPhaseQueue {
+    pub fn is_started(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Queues up [order], see [enemy_move_order]
+    pub fn start(&mut self, order: Vec<Entity>, occupancy: Occupancy) {
+        *self = Self {
+            pending: Some(order.into()),
+            occupancy,
+            ..Default::default()
+        };
+    }
+
+    /// The next [batch_size] enemies to decide in order, or all those left for a [batch_size] of 0
+    pub fn next_batch(&mut self, batch_size: usize) -> Vec<Entity> {
+        let pending = match self.pending.as_mut() {
+            Some(pending) => pending,
+            None => return Vec::new(),
+        };
+        let batch_size = if batch_size == 0 {
+            pending.len()
+        } else {
+            batch_size.min(pending.len())
+        };
+        pending.drain(..batch_size).collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.as_ref().map_or(true, VecDeque::is_empty)
+    }
+
+    /// Takes the phase's decisions, leaving the queue empty for the next phase
+    pub fn finish(&mut self) -> MoveDecisions {
+        std::mem::take(self).move_decisions
+    }
+}
+
+/// The order enemies decide their moves in: nearest the player first, ties broken by entity.
+/// Earlier movers claim their tiles first, so this keeps contested tiles going to the same enemy
+/// however the query happens to iterate
+pub fn enemy_move_order(mut enemies: Vec<(Entity, TilePos)>, player_pos: &TilePos) -> Vec<Entity> {
+    enemies.sort_by_key(|(entity, tile_pos)| (tile_pos.distance_to(player_pos), *entity));
+    enemies.into_iter().map(|(entity, _)| entity).collect()
+}
+
+/// The enemies chasing the player. Those within [AggroBalance::radius] of it spot it first, then
+/// alert any others within [AggroBalance::alert_radius] of them. Only the spotters pass the alert
+/// on, so it doesn't chain right across the map
+pub fn alerted_enemies(
+    enemies: &[(Entity, TilePos)],
+    player_pos: &TilePos,
+    aggro: &AggroBalance,
+) -> HashSet<Entity> {
+    if aggro.radius == 0 {
+        return enemies.iter().map(|(entity, _)| *entity).collect();
+    }
+    let spotters: Vec<&TilePos> = enemies
+        .iter()
+        .map(|(_, tile_pos)| tile_pos)
+        .filter(|tile_pos| tile_pos.distance_to(player_pos) <= aggro.radius)
+        .collect();
+    enemies
+        .iter()
+        .filter(|(_, tile_pos)| {
+            spotters
+                .iter()
+                .any(|spotter| spotter.distance_to(tile_pos) <= aggro.alert_radius)
+        })
+        .map(|(entity, _)| *entity)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        alerted_enemies, apply_move_single, decide_move_on, enemy_move_order, free_tiles_near_on,
+        AttackAndMaybeMove, AttackCriteria, EnemyPhaseQueue, MoveDecision, Occupancy, OccupantKind,
+    };
+    use crate::game::balance::AggroBalance;
+    use crate::game::components::{
+        Facing, Health, Inventory, InventoryItem, MapDirection, MovementAnimate, Revival,
+    };
+    use bevy::ecs::system::SystemState;
+    use bevy::prelude::*;
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    #[test]
+    fn test_enemy_move_order_is_deterministic() {
+        let player_pos = TilePos { x: 5, y: 5 };
+        // Two enemies equally far from the player, both wanting the tile (4, 4) between them
+        let below = (Entity::from_raw(7), TilePos { x: 4, y: 3 });
+        let beside = (Entity::from_raw(2), TilePos { x: 3, y: 4 });
+        let near = (Entity::from_raw(9), TilePos { x: 5, y: 6 });
+        assert_eq!(
+            below.1.distance_to(&player_pos),
+            beside.1.distance_to(&player_pos)
+        );
+        // The tie goes to the lower entity, whatever order they're found in
+        let expected = vec![near.0, beside.0, below.0];
+        assert_eq!(
+            expected,
+            enemy_move_order(vec![below, beside, near], &player_pos)
+        );
+        assert_eq!(
+            expected,
+            enemy_move_order(vec![near, below, beside], &player_pos)
+        );
+        assert_eq!(
+            expected,
+            enemy_move_order(vec![beside, near, below], &player_pos)
+        );
+
+        // Deciding in that order, the first of the two claims the contested tile and the second,
+        // seeing it claimed, stays put
+        let contested = TilePos { x: 4, y: 4 };
+        let occupancy = Occupancy::from_residents(vec![
+            (player_pos, Entity::from_raw(1), OccupantKind::Player),
+            (beside.1, beside.0, OccupantKind::Enemy),
+            (below.1, below.0, OccupantKind::Enemy),
+        ]);
+        let decide = |tile_pos: &TilePos, direction: MapDirection, claimed: &[TilePos]| {
+            decide_move_on(
+                tile_pos,
+                &direction,
+                1,
+                &AttackCriteria::for_enemy(true, 1),
+                &occupancy,
+                |_| true,
+                claimed,
+            )
+        };
+        let first = decide(&beside.1, MapDirection::Right, &[]);
+        assert_eq!(Some(contested), first.to_move_position());
+        let second = decide(&below.1, MapDirection::Up, &[contested]);
+        assert_eq!(None, second.to_move_position());
+    }
+
+    #[test]
+    fn test_decide_move_with_occupancy() {
+        let player = Entity::from_raw(1);
+        let shark = Entity::from_raw(2);
+        let crab = Entity::from_raw(3);
+        // A row of open water along y = 5, walled off past x = 8
+        let can_enter = |tilepos: &TilePos| tilepos.y == 5 && tilepos.x <= 8;
+        let occupancy = Occupancy::from_residents(vec![
+            (TilePos { x: 2, y: 5 }, player, OccupantKind::Player),
+            (TilePos { x: 5, y: 5 }, shark, OccupantKind::Enemy),
+            (TilePos { x: 8, y: 5 }, crab, OccupantKind::Enemy),
+            // Sharing the shark's tile, which a scan would have found second
+            (TilePos { x: 5, y: 5 }, crab, OccupantKind::Enemy),
+        ]);
+        let decide = |x, direction: MapDirection, distance, criteria: AttackCriteria| {
+            decide_move_on(
+                &TilePos { x, y: 5 },
+                &direction,
+                distance,
+                &criteria,
+                &occupancy,
+                can_enter,
+                &[],
+            )
+        };
+
+        // The player attacks the shark next to them, moving onto its tile if it dies
+        assert_eq!(
+            MoveDecision::AttackAndMaybeMove(AttackAndMaybeMove {
+                attack_target_pos: TilePos { x: 5, y: 5 },
+                attack_target_entity: shark,
+                direction: MapDirection::Right,
+                position_before_enemy: None,
+                damage: 1,
+            }),
+            decide(4, MapDirection::Right, 1, AttackCriteria::for_player())
+        );
+        // Enemies can't attack each other, so just turn
+        assert_eq!(
+            MoveDecision::Turn(MapDirection::Left),
+            decide(6, MapDirection::Left, 1, AttackCriteria::for_enemy(true, 1))
+        );
+        // Moving two tiles onto the player attacks from the tile before them
+        assert_eq!(
+            MoveDecision::AttackAndMaybeMove(AttackAndMaybeMove {
+                attack_target_pos: TilePos { x: 2, y: 5 },
+                attack_target_entity: player,
+                direction: MapDirection::Left,
+                position_before_enemy: Some(TilePos { x: 3, y: 5 }),
+                damage: 2,
+            }),
+            decide(4, MapDirection::Left, 2, AttackCriteria::for_enemy(true, 2))
+        );
+        assert_eq!(
+            MoveDecision::Move((TilePos { x: 7, y: 5 }, MapDirection::Right)),
+            decide(6, MapDirection::Right, 1, AttackCriteria::for_player())
+        );
+        // Off the row is wall
+        assert_eq!(
+            MoveDecision::Turn(MapDirection::Up),
+            decide(6, MapDirection::Up, 1, AttackCriteria::for_player())
+        );
+    }
+
+    #[test]
+    fn test_free_tiles_near() {
+        // Open water from (0, 0) to (4, 4) with a wall at (2, 1), the player on (1, 2)
+        let can_enter = |tilepos: &TilePos| {
+            tilepos.x <= 4 && tilepos.y <= 4 && *tilepos != TilePos { x: 2, y: 1 }
+        };
+        let occupied = vec![TilePos { x: 1, y: 2 }];
+        let center = TilePos { x: 2, y: 2 };
+        assert_eq!(
+            vec![
+                TilePos { x: 2, y: 2 },
+                TilePos { x: 2, y: 3 },
+                TilePos { x: 3, y: 2 },
+            ],
+            free_tiles_near_on(&center, 1, can_enter, &occupied)
+        );
+        // Nearest first, ties by position
+        let free_tiles = free_tiles_near_on(&center, 2, can_enter, &occupied);
+        assert_eq!(11, free_tiles.len());
+        assert_eq!(Some(&TilePos { x: 0, y: 2 }), free_tiles.get(3));
+        // Clipped at the map edge rather than wrapping or panicking
+        assert_eq!(
+            vec![
+                TilePos { x: 0, y: 0 },
+                TilePos { x: 0, y: 1 },
+                TilePos { x: 1, y: 0 }
+            ],
+            free_tiles_near_on(&TilePos { x: 0, y: 0 }, 1, can_enter, &[])
+        );
+        assert!(free_tiles_near_on(&TilePos { x: 2, y: 1 }, 0, can_enter, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_enemy_phase_queue_batches_in_order() {
+        let order: Vec<Entity> = (0..5).map(Entity::from_raw).collect();
+        let mut queue = EnemyPhaseQueue::default();
+        assert!(!queue.is_started());
+        queue.start(order.clone(), Occupancy::default());
+        let mut batched = Vec::new();
+        while !queue.is_finished() {
+            batched.push(queue.next_batch(2));
+        }
+        assert_eq!(
+            vec![2, 2, 1],
+            batched.iter().map(Vec::len).collect::<Vec<_>>()
+        );
+        assert_eq!(order, batched.concat());
+        queue.finish();
+        assert!(!queue.is_started());
+
+        // No batch size does the lot at once
+        queue.start(order.clone(), Occupancy::default());
+        assert_eq!(order, queue.next_batch(0));
+        assert!(queue.is_finished());
+    }
+
+    #[test]
+    fn test_alert_spreads_from_spotters() {
+        let aggro = AggroBalance {
+            radius: 3,
+            alert_radius: 2,
+        };
+        let player_pos = TilePos { x: 0, y: 0 };
+        let spotter = (Entity::from_raw(0), TilePos { x: 3, y: 0 });
+        let neighbour = (Entity::from_raw(1), TilePos { x: 5, y: 0 });
+        // Near the neighbour, but too far from the spotter
+        let beyond = (Entity::from_raw(2), TilePos { x: 7, y: 0 });
+        let loner = (Entity::from_raw(3), TilePos { x: 0, y: 9 });
+        let enemies = vec![spotter, neighbour, beyond, loner];
+
+        let alerted = alerted_enemies(&enemies, &player_pos, &aggro);
+        assert_eq!(2, alerted.len());
+        assert!(alerted.contains(&spotter.0) && alerted.contains(&neighbour.0));
+
+        // No radius has everyone chasing
+        let aggro = AggroBalance { radius: 0, ..aggro };
+        assert_eq!(4, alerted_enemies(&enemies, &player_pos, &aggro).len());
+    }
+
+    /// Sets up [player_health] and an enemy about to attack the player from the left, applies the
+    /// attack and returns where the enemy ends up along with the player's health
+    fn apply_attack_on(player_health: Health, inventory: Inventory) -> (TilePos, Health) {
+        let mut world = World::new();
+        let player_pos = TilePos { x: 2, y: 0 };
+        let enemy_pos = TilePos { x: 1, y: 0 };
+        let player = world.spawn().insert_bundle((player_health, inventory)).id();
+        let enemy = world
+            .spawn()
+            .insert_bundle((
+                enemy_pos,
+                MovementAnimate::default(),
+                Transform::default(),
+                Facing(MapDirection::Right),
+            ))
+            .id();
+        let decision = MoveDecision::AttackAndMaybeMove(AttackAndMaybeMove {
+            attack_target_pos: player_pos,
+            attack_target_entity: player,
+            direction: MapDirection::Right,
+            position_before_enemy: None,
+            damage: 1,
+        });
+        let mut system_state: SystemState<(
+            Query<(&mut TilePos, &mut MovementAnimate, &Transform, &mut Facing)>,
+            Query<(&mut Health, Option<&mut Inventory>)>,
+        )> = SystemState::new(&mut world);
+        let (mut move_query, mut health_query) = system_state.get_mut(&mut world);
+        apply_move_single(enemy, &decision, &mut move_query, &mut health_query);
+        let enemy_pos = *world.get::<TilePos>(enemy).unwrap();
+        let health = world.entity_mut(player).remove::<Health>().unwrap();
+        (enemy_pos, health)
+    }
+
+    #[test]
+    fn test_revived_player_not_moved_onto() {
+        let mut inventory = Inventory::default();
+        inventory.add(InventoryItem::Starfish);
+        let (enemy_pos, health) = apply_attack_on(Health::new(1), inventory);
+        assert_eq!(TilePos { x: 1, y: 0 }, enemy_pos);
+        assert_eq!(1, health.hp);
+        assert_eq!(Some(Revival::Starfish), health.revived);
+
+        // The free last stand is spent first, keeping the starfish
+        let mut inventory = Inventory::default();
+        inventory.add(InventoryItem::Starfish);
+        let player_health = Health {
+            last_stand: true,
+            ..Health::new(1)
+        };
+        let (enemy_pos, health) = apply_attack_on(player_health, inventory);
+        assert_eq!(TilePos { x: 1, y: 0 }, enemy_pos);
+        assert_eq!(Some(Revival::LastStand), health.revived);
+        assert!(!health.last_stand);
+
+        // Without a revive the kill is moved onto as usual
+        let (enemy_pos, health) = apply_attack_on(Health::new(1), Inventory::default());
+        assert_eq!(TilePos { x: 2, y: 0 }, enemy_pos);
+        assert_eq!(0, health.hp);
+    }
+}
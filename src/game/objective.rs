@@ -0,0 +1,157 @@
+//! Optional goals for a level beyond reaching the exit. A level with an [Objective] holds its
+//! vortex back until the objective is met, then pays out bonus shells and spawns the vortex
+//! straight away
+
+use crate::game::enemy::{Barnacle, Enemy};
+use crate::game::events::InfoEvent;
+use crate::game::game::SnailsCollectedThisRun;
+use crate::game::snails::Snail;
+use crate::game::turn::GlobalTurnCounter;
+use bevy::prelude::*;
+use rand::Rng;
+
+pub struct ObjectivePlugin;
+
+impl Plugin for ObjectivePlugin {
+    fn build(&self, app: &mut App) {
+        let state = crate::CoreState::GameLevel;
+        app.insert_resource(LevelObjective::default())
+            .add_system_set(SystemSet::on_enter(state).with_system(objective_setup))
+            // The demo shares the vortex trigger, so mustn't be held back by a real level's
+            .add_system_set(
+                SystemSet::on_enter(crate::CoreState::Demo).with_system(objective_clear),
+            )
+            .add_system_set(SystemSet::on_update(state).with_system(objective_progress_system));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    CollectAllSnails,
+    KillAllEnemies,
+}
+
+/// Chance any given level has an objective
+const OBJECTIVE_CHANCE: f64 = 0.3;
+
+impl Objective {
+    /// Shells added to the run's haul on completing the objective
+    pub const BONUS_SHELLS: usize = 3;
+
+    pub fn roll(rng: &mut impl Rng) -> Option<Self> {
+        if !rng.gen_bool(OBJECTIVE_CHANCE) {
+            None
+        } else if rng.gen_bool(0.5) {
+            Some(Self::CollectAllSnails)
+        } else {
+            Some(Self::KillAllEnemies)
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::CollectAllSnails => "Collect all snails",
+            Self::KillAllEnemies => "Kill all enemies",
+        }
+    }
+
+    /// How many snails or enemies are still in the way
+    pub fn remaining(&self, snail_count: usize, enemy_count: usize) -> usize {
+        match self {
+            Self::CollectAllSnails => snail_count,
+            Self::KillAllEnemies => enemy_count,
+        }
+    }
+}
+
+/// Resource for the current level's [Objective], if it has one, and how it's going
+#[derive(Debug, Default)]
+pub struct LevelObjective {
+    pub objective: Option<Objective>,
+    pub remaining: usize,
+    pub completed: bool,
+}
+
+impl LevelObjective {
+    /// Whether the vortex is allowed to spawn, always true for levels without an objective
+    pub fn exit_unlocked(&self) -> bool {
+        self.objective.is_none() || self.completed
+    }
+
+    /// e.g. "Kill all enemies: 3 left", [None] without an objective
+    pub fn progress_text(&self) -> Option<String> {
+        let objective = self.objective?;
+        Some(if self.completed {
+            format!("{}: Done!", objective.description())
+        } else {
+            format!("{}: {} left", objective.description(), self.remaining)
+        })
+    }
+}
+
+fn objective_setup(mut level_objective: ResMut<LevelObjective>) {
+    let objective = Objective::roll(&mut rand::thread_rng());
+    info!("Level objective: {:?}", objective);
+    *level_objective = LevelObjective {
+        objective,
+        ..Default::default()
+    };
+}
+
+fn objective_clear(mut level_objective: ResMut<LevelObjective>) {
+    *level_objective = LevelObjective::default();
+}
+
+fn objective_progress_system(
+    mut level_objective: ResMut<LevelObjective>,
+    snail_query: Query<Entity, With<Snail>>,
+    // Barnacles only sit in the way, so they don't need clearing
+    enemy_query: Query<Entity, (With<Enemy>, Without<Barnacle>)>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut snails_collected_this_run: ResMut<SnailsCollectedThisRun>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+) {
+    let objective = match level_objective.objective {
+        Some(objective) if !level_objective.completed => objective,
+        _ => return,
+    };
+    let remaining = objective.remaining(snail_query.iter().count(), enemy_query.iter().count());
+    // Only written on change so the UI can watch for it
+    if remaining != level_objective.remaining {
+        level_objective.remaining = remaining;
+    }
+    // Nothing counts before the first turn, so the level's spawns have had time to land
+    if remaining == 0 && global_turn_counter.turn_count > 0 {
+        info!("Objective complete: {}", objective.description());
+        level_objective.completed = true;
+        snails_collected_this_run.0 += Objective::BONUS_SHELLS;
+        info_event_writer.send(InfoEvent::ObjectiveCompleted);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LevelObjective, Objective};
+
+    #[test]
+    fn test_objective_gates_exit() {
+        let mut level_objective = LevelObjective::default();
+        assert!(level_objective.exit_unlocked());
+        assert_eq!(None, level_objective.progress_text());
+
+        level_objective.objective = Some(Objective::KillAllEnemies);
+        level_objective.remaining = 3;
+        assert!(!level_objective.exit_unlocked());
+        assert_eq!(
+            Some("Kill all enemies: 3 left".to_string()),
+            level_objective.progress_text()
+        );
+
+        level_objective.completed = true;
+        assert!(level_objective.exit_unlocked());
+        assert_eq!(
+            Some("Kill all enemies: Done!".to_string()),
+            level_objective.progress_text()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Tracks whether the player has killed anything this run, for runs hooked out without doing so.
+//! Enemies the player hurts, in melee or with the power, are noted in [PlayerStrikes] so their
+//! deaths can be told apart from those the player had no hand in, see [KillSource]. A run ending
+//! in the hook with no kills of the player's counts towards [UserProfile::pacifist_runs]
+
+use crate::game::events::{GameEvent, InfoEvent, KillSource};
+use crate::profiles::profiles::LoadedUserProfile;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+pub struct PacifistPlugin;
+
+impl Plugin for PacifistPlugin {
+    fn build(&self, app: &mut App) {
+        let state = crate::CoreState::GameLevel;
+        app.init_resource::<PlayerStrikes>()
+            .init_resource::<PacifistTracker>()
+            .add_system_set(SystemSet::on_update(state).with_system(pacifist_tracking_system))
+            .add_system_set(SystemSet::on_exit(state).with_system(player_strikes_cleanup));
+    }
+}
+
+/// Enemies the player has hurt this level
+#[derive(Debug, Default)]
+pub struct PlayerStrikes(HashSet<Entity>);
+
+impl PlayerStrikes {
+    pub fn strike(&mut self, entity: Entity) {
+        self.0.insert(entity);
+    }
+
+    /// Who's to blame for [entity] dying, forgetting it as it's gone
+    pub fn kill_source(&mut self, entity: Entity) -> KillSource {
+        if self.0.remove(&entity) {
+            KillSource::Player
+        } else {
+            KillSource::Other
+        }
+    }
+}
+
+/// Kills of the player's this run, cleared as each run ends. Quick-saves keep their own count
+#[derive(Debug, Default)]
+pub struct PacifistTracker {
+    pub kills: usize,
+}
+
+impl PacifistTracker {
+    pub fn record(&mut self, source: &KillSource) {
+        if *source == KillSource::Player {
+            self.kills += 1;
+        }
+    }
+
+    pub fn is_pacifist(&self) -> bool {
+        self.kills == 0
+    }
+}
+
+fn pacifist_tracking_system(
+    mut info_event_reader: EventReader<InfoEvent>,
+    mut game_event_reader: EventReader<GameEvent>,
+    mut pacifist_tracker: ResMut<PacifistTracker>,
+    mut loaded_profile: ResMut<LoadedUserProfile>,
+) {
+    for event in info_event_reader.iter() {
+        if let InfoEvent::EnemyKilled(source) = event {
+            pacifist_tracker.record(source);
+        }
+    }
+    for event in game_event_reader.iter() {
+        match event {
+            GameEvent::HookCompleted => {
+                if pacifist_tracker.is_pacifist() {
+                    info!("Pacifist run completed");
+                    loaded_profile.user_profile.pacifist_runs += 1;
+                }
+                *pacifist_tracker = PacifistTracker::default();
+            }
+            GameEvent::PlayerDied => *pacifist_tracker = PacifistTracker::default(),
+            _ => (),
+        }
+    }
+}
+
+fn player_strikes_cleanup(mut player_strikes: ResMut<PlayerStrikes>) {
+    player_strikes.0.clear();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PacifistTracker, PlayerStrikes};
+    use crate::game::events::KillSource;
+    use bevy::prelude::Entity;
+
+    #[test]
+    fn test_only_player_kills_count() {
+        let mut player_strikes = PlayerStrikes::default();
+        let mut pacifist_tracker = PacifistTracker::default();
+        player_strikes.strike(Entity::from_raw(1));
+
+        pacifist_tracker.record(&player_strikes.kill_source(Entity::from_raw(2)));
+        assert!(pacifist_tracker.is_pacifist());
+        pacifist_tracker.record(&player_strikes.kill_source(Entity::from_raw(1)));
+        assert!(!pacifist_tracker.is_pacifist());
+        // Forgotten once dead
+        assert_eq!(
+            KillSource::Other,
+            player_strikes.kill_source(Entity::from_raw(1))
+        );
+    }
+}
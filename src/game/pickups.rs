@@ -0,0 +1,167 @@
+use crate::asset_handling::asset::ImageAsset;
+use crate::asset_handling::ImageAssetStore;
+use crate::game::components::{GameOnly, Inventory, InventoryItem, Player};
+use crate::game::events::InfoEvent;
+use crate::game::game::SnailsCollectedThisRun;
+use crate::game::tilemap::TilePosExt;
+use crate::map_gen::cell_map::CellMap;
+use crate::profiles::profiles::LoadedUserProfile;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use rand::Rng;
+
+/// An item lying on the map, moved into the player's [Inventory] when they swim over it
+#[derive(Debug, Component)]
+pub struct Pickup(pub InventoryItem);
+
+const SHIELD_SPAWN_CHANCE: f64 = 0.5;
+
+/// Chest which, when swum over, banks the shells collected so far this run straight into the
+/// profile, so they're kept even if the player goes on to die. Used up once opened
+#[derive(Debug, Component)]
+pub struct DepositChest;
+
+const DEPOSIT_CHEST_SPAWN_CHANCE: f64 = 0.15;
+
+fn add_pickups(
+    item: InventoryItem,
+    num_pickups: usize,
+    commands: &mut Commands,
+    image_assets: &Res<ImageAssetStore>,
+    cell_map: &CellMap<i32>,
+    exclude_positions: Option<&Vec<(i32, i32)>>,
+) -> Vec<(i32, i32)> {
+    let spawn_positions = cell_map.distribute_points_by_cost(num_pickups, exclude_positions);
+    for (x, y) in spawn_positions.iter() {
+        let tile_pos = TilePos {
+            x: *x as u32,
+            y: *y as u32,
+        };
+        spawn_pickup(commands, image_assets, item, tile_pos);
+    }
+    spawn_positions
+}
+
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    image_assets: &ImageAssetStore,
+    item: InventoryItem,
+    tile_pos: TilePos,
+) {
+    let mut transform = Transform::from_translation(tile_pos.to_world_pos(9.0));
+    transform.scale = Vec3::splat(0.6);
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: image_assets.get(&item.to_image_asset()),
+            transform,
+            ..Default::default()
+        })
+        .insert(tile_pos)
+        .insert(GameOnly)
+        .insert(Pickup(item));
+}
+
+pub fn choose_number_of_and_spawn_shields(
+    commands: &mut Commands,
+    image_assets: &Res<ImageAssetStore>,
+    cell_map: &CellMap<i32>,
+    exclude_positions: Option<&Vec<(i32, i32)>>,
+) -> (usize, Vec<(i32, i32)>) {
+    let mut rng = rand::thread_rng();
+    let num_shields = if rng.gen_bool(SHIELD_SPAWN_CHANCE) {
+        1
+    } else {
+        0
+    };
+
+    let spawned_positions = if num_shields > 0 {
+        add_pickups(
+            InventoryItem::Shield,
+            num_shields,
+            commands,
+            image_assets,
+            cell_map,
+            exclude_positions,
+        )
+    } else {
+        Vec::new()
+    };
+    (num_shields, spawned_positions)
+}
+
+/// Rarely spawns a [DepositChest], if [Settings::deposit_chests] allows them, returning where it
+/// was put. They can be turned off as they take much of the risk out of a long run
+pub fn maybe_spawn_deposit_chest(
+    commands: &mut Commands,
+    image_assets: &ImageAssetStore,
+    settings: &Settings,
+    cell_map: &CellMap<i32>,
+    exclude_positions: Option<&Vec<(i32, i32)>>,
+) -> Option<(i32, i32)> {
+    let mut rng = rand::thread_rng();
+    if !settings.deposit_chests || !rng.gen_bool(DEPOSIT_CHEST_SPAWN_CHANCE) {
+        return None;
+    }
+    let (x, y) = *cell_map
+        .distribute_points_by_cost(1, exclude_positions)
+        .first()?;
+    let tile_pos = TilePos {
+        x: x as u32,
+        y: y as u32,
+    };
+    let mut transform = Transform::from_translation(tile_pos.to_world_pos(9.0));
+    transform.scale = Vec3::splat(0.6);
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: image_assets.get(&ImageAsset::DepositChest),
+            transform,
+            ..Default::default()
+        })
+        .insert(tile_pos)
+        .insert(GameOnly)
+        .insert(DepositChest);
+    Some((x, y))
+}
+
+/// Banks the run's shells into the profile when the player reaches a [DepositChest]. Only run in
+/// the real game, never the demo, as it saves the profile
+pub fn deposit_chest_system(
+    mut commands: Commands,
+    chest_query: Query<(Entity, &TilePos), With<DepositChest>>,
+    player_query: Query<&TilePos, With<Player>>,
+    mut snails_collected_this_run: ResMut<SnailsCollectedThisRun>,
+    mut loaded_profile: ResMut<LoadedUserProfile>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+) {
+    for player_pos in player_query.iter() {
+        for (chest_entity, chest_pos) in chest_query.iter() {
+            if chest_pos == player_pos {
+                info!("Player deposited {} shells", snails_collected_this_run.0);
+                loaded_profile.user_profile.snail_shells += snails_collected_this_run.0;
+                snails_collected_this_run.0 = 0;
+                loaded_profile.save();
+                commands.entity(chest_entity).despawn();
+                info_event_writer.send(InfoEvent::ShellsDeposited);
+            }
+        }
+    }
+}
+
+pub fn pickup_system(
+    mut commands: Commands,
+    pickup_query: Query<(Entity, &TilePos, &Pickup)>,
+    mut player_query: Query<(&TilePos, &mut Inventory), With<Player>>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+) {
+    for (player_pos, mut inventory) in player_query.iter_mut() {
+        for (pickup_entity, pickup_pos, pickup) in pickup_query.iter() {
+            if pickup_pos == player_pos {
+                info!("Player picked up {:?}", pickup.0);
+                inventory.add(pickup.0);
+                commands.entity(pickup_entity).despawn();
+                info_event_writer.send(InfoEvent::PlayerPickedUpItem);
+            }
+        }
+    }
+}
@@ -2,25 +2,32 @@ use super::tilemap::TilePosExt;
 use crate::asset_handling::asset::TextureAtlasAsset;
 use crate::asset_handling::TextureAtlasStore;
 use crate::game::components::{
-    AnimationTimer, DirectionalSpriteAnimation, Facing, Health, MapDirection, TileType,
+    AnimationTimer, DirectionalSpriteAnimation, Facing, GameOnly, Health, MapDirection, TileType,
 };
-use crate::game::enemy::JellyfishLightningTile;
+use crate::game::enemy::{JellyfishLightningTile, ProjectileImmune};
 use crate::game::events::GameEvent;
+use crate::game::game_time::{GameTime, HitStopSettings};
+use crate::game::pacifist::PlayerStrikes;
 use crate::game::tilemap::{HasTileType, TileStorageQuery};
+use crate::game::timed_removal::TimedDespawn;
 use crate::game::turn::{GamePhase, GlobalTurnCounter, TurnCounter};
 
-use bevy::time::{Time, Timer};
+use bevy::time::Timer;
 
 use bevy::ecs::entity::Entity;
 use bevy::ecs::event::EventWriter;
-use bevy::ecs::prelude::{Commands, Local, Query, Res, With};
-use bevy::math::{Vec2, Vec3};
+use bevy::ecs::prelude::{Commands, Local, Query, Res, ResMut, With, Without};
+use bevy::ecs::query::WorldQuery;
+use bevy::math::{Quat, Vec2, Vec3};
 use bevy::prelude::Component;
-use bevy::prelude::{SpriteSheetBundle, Transform};
+use bevy::prelude::{
+    Color, Handle, SpriteSheetBundle, TextureAtlas, TextureAtlasSprite, Transform,
+};
 use bevy_ecs_tilemap::tiles::TilePos;
 use log::debug;
 use num::Signed;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
 
 pub enum ProjectileEvent {
     ProjectileLaunched,
@@ -29,25 +36,78 @@ pub enum ProjectileEvent {
 
 #[derive(Component)]
 pub struct Projectile {
+    /// Bounce points still to reach before heading to [end_point], with the direction after each
+    waypoints: VecDeque<(TilePos, MapDirection)>,
     end_point: TilePos,
     speed: f32,
     finish_point_threshold: f32,
     damage: usize,
     end_target_entity: Option<Entity>,
+    /// Targets punched through on the way, damaged as the projectile reaches them, see
+    /// [Passthrough]
+    passed_through: VecDeque<PassedTarget>,
+    /// Legs finished so far, i.e. bounces made
+    leg: usize,
+    /// Drops a [ProjectileTrail] sprite each time it finishes
+    trail_timer: Timer,
 }
 
 impl Projectile {
-    fn new(end_point: TilePos, speed: f32, end_target_entity: Option<Entity>) -> Self {
+    fn new(
+        waypoints: VecDeque<(TilePos, MapDirection)>,
+        end_point: TilePos,
+        speed: f32,
+        damage: usize,
+        end_target_entity: Option<Entity>,
+        passed_through: VecDeque<PassedTarget>,
+    ) -> Self {
         Self {
+            waypoints,
             end_point,
             speed,
             finish_point_threshold: 32.0,
-            damage: 1usize,
+            damage,
             end_target_entity,
+            passed_through,
+            leg: 0,
+            trail_timer: Timer::new(TRAIL_INTERVAL, true),
+        }
+    }
+
+    fn next_target(&self) -> &TilePos {
+        match self.waypoints.front() {
+            Some((waypoint, _direction)) => waypoint,
+            None => &self.end_point,
+        }
+    }
+
+    /// Takes the passed through targets the projectile at [position] has now reached. That's by
+    /// how far along the current leg it's got, rather than how close it is to each, so a long
+    /// frame carrying it well past one still hits it
+    fn take_reached_passes(&mut self, position: Vec2) -> Vec<PassedTarget> {
+        let target = self.next_target().to_world_pos(1f32).truncate();
+        let remaining = (target - position).length();
+        let mut reached = Vec::new();
+        while let Some(passed) = self.passed_through.front() {
+            // Nothing moves backwards along a leg, so a pass nearer the leg's end than the
+            // projectile (give or take the threshold) is behind it
+            let pass_remaining = (target - passed.tile_pos.to_world_pos(1f32).truncate()).length();
+            let is_reached = passed.leg < self.leg
+                || (passed.leg == self.leg
+                    && remaining < pass_remaining + self.finish_point_threshold);
+            if !is_reached {
+                break;
+            }
+            reached.extend(self.passed_through.pop_front());
         }
+        reached
     }
 }
 
+/// Marker for the tiles shown along the power's path whilst aiming
+#[derive(Component)]
+pub struct PowerAimingIndicator;
+
 /// This system only progresses turn phase if all projectiles have ceased to exist
 pub fn _projectile_watcher_system(
     projectile_query: Query<Entity, With<Projectile>>,
@@ -115,30 +175,147 @@ pub fn phase_watcher_system<Effect: Component + HasWatcherPhase>(
     }
 }
 
+/// Fading copy of the projectile left behind it as it flies, so it can be followed by eye
+#[derive(Component)]
+pub struct ProjectileTrail;
+
+const TRAIL_INTERVAL: Duration = Duration::from_millis(20);
+const TRAIL_LIFETIME: Duration = Duration::from_millis(150);
+const TRAIL_ALPHA: f32 = 0.6;
+const TRAIL_SCALE: f32 = 0.7;
+
+fn spawn_trail(
+    commands: &mut Commands,
+    atlas_handle: &Handle<TextureAtlas>,
+    index: usize,
+    transform: &Transform,
+) {
+    let mut transform = transform.with_scale(transform.scale * TRAIL_SCALE);
+    // Just below the projectile itself
+    transform.translation.z -= 0.1;
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                index,
+                color: Color::rgba(1.0, 1.0, 1.0, TRAIL_ALPHA),
+                ..Default::default()
+            },
+            texture_atlas: atlas_handle.clone(),
+            transform,
+            ..Default::default()
+        })
+        .insert(TimedDespawn::new(TRAIL_LIFETIME))
+        .insert(ProjectileTrail)
+        .insert(GameOnly);
+}
+
+/// Fades [ProjectileTrail] sprites out over their lifetime
+pub fn projectile_trail_fade_system(
+    mut query: Query<(&TimedDespawn, &mut TextureAtlasSprite), With<ProjectileTrail>>,
+) {
+    for (timed_despawn, mut sprite) in query.iter_mut() {
+        sprite
+            .color
+            .set_a(TRAIL_ALPHA * timed_despawn.timer.percent_left());
+    }
+}
+
+/// Deals [damage] to [entity], if it can be hurt, pausing for a moment if that kills it
+fn projectile_hit(
+    health_query: &mut Query<&mut Health, Without<ProjectileImmune>>,
+    game_time: &mut GameTime,
+    hit_stop_settings: &HitStopSettings,
+    player_strikes: &mut PlayerStrikes,
+    entity: Entity,
+    damage: usize,
+) {
+    if let Ok(mut health) = health_query.get_mut(entity) {
+        health.decr_by(damage);
+        // Only the player fires projectiles
+        player_strikes.strike(entity);
+        if health.hp == 0 && hit_stop_settings.on_power_kill {
+            game_time.hit_stop(hit_stop_settings.duration);
+        }
+    }
+}
+
 pub fn projectile_system(
-    mut query: Query<(Entity, &mut Transform, &mut Projectile)>,
-    mut health_query: Query<&mut Health>,
-    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Projectile,
+        &TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+    )>,
+    mut health_query: Query<&mut Health, Without<ProjectileImmune>>,
+    mut game_time: ResMut<GameTime>,
+    hit_stop_settings: Res<HitStopSettings>,
+    mut player_strikes: ResMut<PlayerStrikes>,
     mut commands: Commands,
 ) {
-    for (entity, mut transform, projectile) in query.iter_mut() {
-        let target_pos = projectile.end_point.to_world_pos(1f32).truncate();
+    for (entity, mut transform, mut projectile, sprite, atlas_handle) in query.iter_mut() {
+        if projectile
+            .trail_timer
+            .tick(game_time.delta())
+            .just_finished()
+        {
+            spawn_trail(&mut commands, atlas_handle, sprite.index, &transform);
+        }
+        let target_pos = projectile.next_target().to_world_pos(1f32).truncate();
         let distance_to_travel = target_pos - transform.translation.truncate();
         let direction: Vec2 = distance_to_travel.normalize();
+        // The sprite is drawn facing right, point it along the way it's actually travelling
+        transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
 
-        let distance_this_step = direction * projectile.speed * time.delta().as_secs_f32();
+        // Never past the target, however long the frame, so it can't overshoot and miss it
+        let distance_this_step = direction
+            * (projectile.speed * game_time.delta_seconds()).min(distance_to_travel.length());
 
         transform.translation += distance_this_step.extend(0f32);
 
-        if (transform.translation.truncate() - target_pos).length()
-            < projectile.finish_point_threshold
-        {
+        for passed in projectile.take_reached_passes(transform.translation.truncate()) {
+            projectile_hit(
+                &mut health_query,
+                &mut game_time,
+                &hit_stop_settings,
+                &mut player_strikes,
+                passed.entity,
+                passed.damage,
+            );
+        }
+
+        let reached_target = (transform.translation.truncate() - target_pos).length()
+            < projectile.finish_point_threshold;
+        if !reached_target {
+            continue;
+        }
+        if projectile.waypoints.pop_front().is_some() {
+            // Snap to the bounce point so the next leg stays in line with the tiles
+            transform.translation = target_pos.extend(transform.translation.z);
+            projectile.leg += 1;
+        } else {
             debug!("Despawning projectile: {:?}", entity);
             commands.entity(entity).despawn();
+            // Anything somehow still waiting is hit regardless, rather than left unhurt
+            for passed in std::mem::take(&mut projectile.passed_through) {
+                projectile_hit(
+                    &mut health_query,
+                    &mut game_time,
+                    &hit_stop_settings,
+                    &mut player_strikes,
+                    passed.entity,
+                    passed.damage,
+                );
+            }
             if let Some(damage_entity) = projectile.end_target_entity {
-                if let Ok(mut health) = health_query.get_mut(damage_entity) {
-                    health.decr_by(projectile.damage);
-                }
+                projectile_hit(
+                    &mut health_query,
+                    &mut game_time,
+                    &hit_stop_settings,
+                    &mut player_strikes,
+                    damage_entity,
+                    projectile.damage,
+                );
             }
         }
     }
@@ -183,13 +360,131 @@ impl ProjectileFate {
     }
 }
 
-pub fn scan_to_endpoint<T: Component>(
+/// A projectile's full route: the points it bounces at (with the direction it leaves each in),
+/// then where it finally ends up
+pub struct ProjectilePath {
+    pub waypoints: VecDeque<(TilePos, MapDirection)>,
+    pub fate: ProjectileFate,
+    /// Targets killed and passed through before the end, see [Passthrough]
+    pub passed_through: VecDeque<PassedTarget>,
+}
+
+/// A target killed and passed through by the power, see [Passthrough]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassedTarget {
+    /// Which leg of the path it's on, counting from 0 before any bounce
+    pub leg: usize,
+    pub tile_pos: TilePos,
+    pub entity: Entity,
+    /// Damage it takes, its remaining hp
+    pub damage: usize,
+}
+
+/// Carried along a scan when the power punches through what it kills, see
+/// [crate::profiles::profiles::UserProfile::overkill_passthrough]. A target the damage kills with
+/// some to spare is passed through, and what's left over carries on to the next
+pub struct Passthrough<'a> {
+    /// Damage still carried
+    pub damage: usize,
+    target_hp: &'a dyn Fn(Entity) -> Option<usize>,
+    /// The leg of the path being scanned, set by [scan_path]
+    leg: usize,
+    /// Targets passed through so far in the order they're reached
+    pub killed: VecDeque<PassedTarget>,
+}
+
+impl<'a> Passthrough<'a> {
+    pub fn new(damage: usize, target_hp: &'a dyn Fn(Entity) -> Option<usize>) -> Self {
+        Self {
+            damage,
+            target_hp,
+            leg: 0,
+            killed: VecDeque::new(),
+        }
+    }
+
+    /// Whether the projectile carries on past [entity], noting it as killed if so. A target that
+    /// takes all the damage left to kill stops it as usual
+    fn pass(&mut self, tile_pos: TilePos, entity: Entity) -> bool {
+        match (self.target_hp)(entity) {
+            Some(hp) if hp < self.damage => {
+                self.killed.push_back(PassedTarget {
+                    leg: self.leg,
+                    tile_pos,
+                    entity,
+                    damage: hp,
+                });
+                self.damage -= hp;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// As [scan_to_endpoint], but on reaching a wall without hitting a target the projectile bounces
+/// back the way it came, up to [max_bounces] times. Movement is only ever orthogonal, so
+/// reflecting off a wall reverses the direction entirely
+pub fn scan_path<F: WorldQuery>(
+    from: &TilePos,
+    direction: &MapDirection,
+    max_bounces: usize,
+    query: &Query<(Entity, &TilePos), F>,
+    tile_storage_query: &TileStorageQuery,
+    tiletype_query: &Query<&HasTileType>,
+    return_early_on_target_hit: bool,
+    mut passthrough: Option<&mut Passthrough>,
+) -> ProjectilePath {
+    let mut waypoints = VecDeque::new();
+    let mut leg_start = *from;
+    let mut leg_direction = direction.clone();
+    loop {
+        if let Some(passthrough) = passthrough.as_deref_mut() {
+            passthrough.leg = waypoints.len();
+        }
+        let fate = scan_to_endpoint(
+            &leg_start,
+            &leg_direction,
+            query,
+            tile_storage_query,
+            tiletype_query,
+            return_early_on_target_hit,
+            passthrough.as_deref_mut(),
+        );
+        match fate {
+            ProjectileFate::EndNoTarget(wall_pos) if waypoints.len() < max_bounces => {
+                // Bounce off the last open tile before the wall
+                let bounce_pos = wall_pos.add(leg_direction.opposite().to_pos_move());
+                leg_direction = leg_direction.opposite();
+                waypoints.push_back((bounce_pos, leg_direction.clone()));
+                leg_start = bounce_pos;
+            }
+            fate => {
+                let passed_through = passthrough
+                    .map(|passthrough| std::mem::take(&mut passthrough.killed))
+                    .unwrap_or_default();
+                return ProjectilePath {
+                    waypoints,
+                    fate,
+                    passed_through,
+                };
+            }
+        }
+    }
+}
+
+/// Walks from [from] in [direction] until hitting a wall, noting any targets matched by [query]
+/// along the way. Filter out anything that shouldn't stop the projectile, e.g.
+/// [ProjectileImmune] enemies, so it passes over them. With a [passthrough] it also carries on
+/// past targets it would kill
+pub fn scan_to_endpoint<F: WorldQuery>(
     from: &TilePos,
     direction: &MapDirection,
-    query: &Query<(Entity, &TilePos), With<T>>,
+    query: &Query<(Entity, &TilePos), F>,
     tile_storage_query: &TileStorageQuery,
     tiletype_query: &Query<&HasTileType>,
     return_early_on_target_hit: bool,
+    mut passthrough: Option<&mut Passthrough>,
 ) -> ProjectileFate {
     let targets_on_same_row_or_column: HashMap<TilePos, Entity> = {
         let mut targets = HashMap::with_capacity(5);
@@ -219,6 +514,12 @@ pub fn scan_to_endpoint<T: Component>(
         if tile_type.can_enter() {
             match targets_on_same_row_or_column.get(&test_pos) {
                 Some(entity) => {
+                    let passes = passthrough
+                        .as_deref_mut()
+                        .map_or(false, |passthrough| passthrough.pass(test_pos, *entity));
+                    if passes {
+                        continue;
+                    }
                     if return_early_on_target_hit {
                         return ProjectileFate::EndHitTarget((test_pos, *entity));
                     } else {
@@ -254,23 +555,187 @@ fn tilepos_add_vec(tilepos: &mut TilePos, vec: &Vec2) {
     }
 }
 
+/// Turns of charge past which holding the charged power stops making it any stronger
+pub const MAX_CHARGE_TURNS: usize = 3;
+
+/// Damage of a power charged for [turns_charged], one more for each turn up to
+/// [MAX_CHARGE_TURNS]. An uncharged power does the usual single point
+pub fn charged_damage(turns_charged: usize) -> usize {
+    1 + turns_charged.min(MAX_CHARGE_TURNS)
+}
+
+/// Spawns the power's projectile, drawn bigger the more [damage] it does
 pub fn spawn_projectile(
     commands: &mut Commands,
     atlases: &Res<TextureAtlasStore>,
     direction: MapDirection,
     start_pos: Vec3,
-    end_point: TilePos,
-    end_target_entity: Option<Entity>,
+    path: ProjectilePath,
+    damage: usize,
+    speed: f32,
 ) {
+    let end_point = *path.fate.tile_pos();
+    let end_target_entity = path.fate.entity();
+    // Whatever was passed through took its share on the way
+    let passed_damage: usize = path.passed_through.iter().map(|passed| passed.damage).sum();
     let atlas_handle = atlases.get(&TextureAtlasAsset::ProjectileSpritesheet);
+    // Only the right facing frames are used, the transform is rotated to the direction of travel
+    // instead, see [projectile_system]
+    let transform = Transform::from_translation(start_pos)
+        .with_rotation(Quat::from_rotation_z(
+            direction.to_rotation_from_right_zero(),
+        ))
+        .with_scale(Vec3::splat(1.0 + 0.25 * (damage - 1) as f32));
     commands
         .spawn_bundle(SpriteSheetBundle {
             texture_atlas: atlas_handle,
-            transform: Transform::from_translation(start_pos),
+            transform,
             ..Default::default()
         })
         .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
-        .insert(Facing(direction))
+        .insert(Facing(MapDirection::Right))
         .insert(DirectionalSpriteAnimation::new(4, 0, 0))
-        .insert(Projectile::new(end_point, 500., end_target_entity));
+        .insert(Projectile::new(
+            path.waypoints,
+            end_point,
+            speed,
+            damage - passed_damage,
+            end_target_entity,
+            path.passed_through,
+        ));
+}
+
+pub fn spawn_aiming_indicator(
+    commands: &mut Commands,
+    atlases: &TextureAtlasStore,
+    from: &TilePos,
+    direction: &MapDirection,
+    path: &ProjectilePath,
+) {
+    let atlas_handle = atlases.get(&TextureAtlasAsset::ProjectileSpritesheet);
+    let mut leg_start = *from;
+    let mut leg_direction = direction.clone();
+    for (waypoint, new_direction) in path.waypoints.iter() {
+        spawn_aiming_indicator_leg(
+            commands,
+            &atlas_handle,
+            &leg_start,
+            &leg_direction,
+            leg_start.distance_to(waypoint),
+        );
+        leg_start = *waypoint;
+        leg_direction = new_direction.clone();
+    }
+    // Show up to the wall, or up to and including the target that would be hit
+    let length = match &path.fate {
+        ProjectileFate::EndNoTarget(end) => leg_start.distance_to(end).saturating_sub(1),
+        ProjectileFate::EndHitTarget((end, _entity)) => leg_start.distance_to(end),
+    };
+    spawn_aiming_indicator_leg(commands, &atlas_handle, &leg_start, &leg_direction, length);
+}
+
+fn spawn_aiming_indicator_leg(
+    commands: &mut Commands,
+    atlas_handle: &Handle<TextureAtlas>,
+    from: &TilePos,
+    direction: &MapDirection,
+    length: usize,
+) {
+    let index = DirectionalSpriteAnimation::direction_to_order_index(direction) * 4;
+    let mut tilepos = *from;
+    for _ in 0..length {
+        tilepos = tilepos.add(direction.to_pos_move());
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index,
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                    ..Default::default()
+                },
+                texture_atlas: atlas_handle.clone(),
+                transform: Transform::from_translation(tilepos.to_world_pos(11.0)),
+                ..Default::default()
+            })
+            .insert(GameOnly)
+            .insert(PowerAimingIndicator);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PassedTarget, Passthrough, Projectile};
+    use crate::game::components::MapDirection;
+    use crate::game::tilemap::TilePosExt;
+    use bevy::ecs::entity::Entity;
+    use bevy_ecs_tilemap::tiles::TilePos;
+    use std::collections::{HashMap, VecDeque};
+
+    #[test]
+    fn test_passthrough_carries_overkill() {
+        let (weak, middling, tough) = (
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+        );
+        let hps = HashMap::from([(weak, 1), (middling, 2), (tough, 3)]);
+        let target_hp = |entity| hps.get(&entity).copied();
+        let tile = |x| TilePos { x, y: 0 };
+
+        let mut passthrough = Passthrough::new(4, &target_hp);
+        assert!(passthrough.pass(tile(1), weak));
+        assert!(passthrough.pass(tile(2), middling));
+        // Only 1 damage left, which doesn't kill
+        assert!(!passthrough.pass(tile(3), tough));
+        assert_eq!(1, passthrough.damage);
+        let passed = |tile_pos, entity, damage| PassedTarget {
+            leg: 0,
+            tile_pos,
+            entity,
+            damage,
+        };
+        assert_eq!(
+            vec![passed(tile(1), weak, 1), passed(tile(2), middling, 2)],
+            passthrough.killed.into_iter().collect::<Vec<_>>()
+        );
+
+        // A kill with nothing to spare stops the projectile
+        let mut passthrough = Passthrough::new(2, &target_hp);
+        assert!(!passthrough.pass(tile(1), middling));
+        assert!(passthrough.killed.is_empty());
+    }
+
+    #[test]
+    fn test_passes_reached_by_progress_along_leg() {
+        let tile = |x| TilePos { x, y: 0 };
+        let passed = |leg, x| PassedTarget {
+            leg,
+            tile_pos: tile(x),
+            entity: Entity::from_raw(x),
+            damage: 1,
+        };
+        // Out to a bounce at 6 then back to 1, passing through 3 and 5 on the way out and 4 on
+        // the way back
+        let mut projectile = Projectile::new(
+            VecDeque::from([(tile(6), MapDirection::Left)]),
+            tile(1),
+            500.0,
+            1,
+            None,
+            VecDeque::from([passed(0, 3), passed(0, 5), passed(1, 4)]),
+        );
+        let at = |x| tile(x).to_world_pos(1.0).truncate();
+
+        assert!(projectile.take_reached_passes(at(1)).is_empty());
+        // A long frame carries it from before 3 right to the bounce, without stopping near either
+        // pass, and the pass on the way back isn't hit early despite being behind it
+        assert_eq!(
+            vec![passed(0, 3), passed(0, 5)],
+            projectile.take_reached_passes(at(6))
+        );
+
+        projectile.waypoints.pop_front();
+        projectile.leg += 1;
+        assert!(projectile.take_reached_passes(at(6)).is_empty());
+        assert_eq!(vec![passed(1, 4)], projectile.take_reached_passes(at(2)));
+    }
 }
@@ -0,0 +1,308 @@
+//! Dev-only quick-save and quick-load of the current level, for setting up and retrying tricky
+//! situations. A [RunSnapshot] holds enough to respawn the level: the map, every resident's
+//! position, health and facing, snails, dropped shells, pickups and the run counters.
+//!
+//! Transient state is *not* preserved, and is reset as if freshly spawned on load:
+//! - sprite/movement animations, waggles and death animations
+//! - enemy move intentions (re-chosen at the start of the next turn) and jellyfish charge state
+//! - projectiles and jellyfish lightning in flight, poison, and any vortex or hook
+//! - water currents, which are re-rolled along with the rest of the tilemap
+//!
+//! Snapshots are only taken and restored at the start of the player's turn so the turn phase is
+//! always consistent with the entities.
+//!
+//! The player's path from quick-save to quick-load is shown as a [GhostPath] on the restored
+//! level, to compare the next attempt against.
+
+use crate::asset_handling::{ImageAssetStore, TextureAtlasStore};
+use crate::game::components::{
+    Facing, GameOnly, Health, Inventory, InventoryItem, MapDirection, Player, PowerCharges,
+};
+use crate::game::enemy::{Barnacle, Crab, Enemy, EnemyKind, Jellyfish, Phantom, ProjectileImmune};
+use crate::game::game::{MapSeed, SnailsCollectedThisRun, MAP_BORDER_SIZE};
+use crate::game::ghost_path::{GhostPath, RecordedPath};
+use crate::game::pacifist::PacifistTracker;
+use crate::game::pickups::Pickup;
+use crate::game::snails::{DroppedShell, Snail};
+use crate::game::turn::{GamePhase, GlobalLevelCounter, GlobalTurnCounter};
+use crate::map_gen::cell_map::CellMap;
+use crate::profiles::profiles::LoadedUserProfile;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{TileStorage, TileTexture};
+use bevy_ecs_tilemap::tiles::TilePos;
+use serde::{Deserialize, Serialize};
+
+/// The single quick-save slot, held in memory for the session
+#[derive(Default)]
+pub struct QuickSave(pub Option<RunSnapshot>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSnapshot {
+    /// Flattened as tuple keys don't serialise as map keys in most formats
+    cell_map: Vec<((i32, i32), i32)>,
+    player: PlayerSnapshot,
+    enemies: Vec<EnemySnapshot>,
+    snails: Vec<((u32, u32), usize)>,
+    dropped_shells: Vec<(u32, u32)>,
+    pickups: Vec<((u32, u32), InventoryItem)>,
+    turn_count: usize,
+    level: usize,
+    snails_collected_this_run: usize,
+    /// The run's [MapSeed::seed], so loading it after another run has started keeps its own maps
+    #[serde(default)]
+    seed: Option<u64>,
+    /// [PacifistTracker::kills] so far this run
+    #[serde(default)]
+    kills: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ResidentSnapshot {
+    pos: (u32, u32),
+    hp: usize,
+    facing: MapDirection,
+}
+
+impl ResidentSnapshot {
+    fn new(tile_pos: &TilePos, health: &Health, facing: &Facing) -> Self {
+        Self {
+            pos: (tile_pos.x, tile_pos.y),
+            hp: health.hp,
+            facing: facing.0.clone(),
+        }
+    }
+
+    fn tile_pos(&self) -> TilePos {
+        TilePos {
+            x: self.pos.0,
+            y: self.pos.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerSnapshot {
+    resident: ResidentSnapshot,
+    power_charges: usize,
+    inventory: Vec<InventoryItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EnemySnapshot {
+    kind: EnemyKind,
+    resident: ResidentSnapshot,
+}
+
+fn to_tile_pos((x, y): (u32, u32)) -> TilePos {
+    TilePos { x, y }
+}
+
+pub fn quick_save_system(
+    input: Res<Input<KeyCode>>,
+    mut quick_save: ResMut<QuickSave>,
+    player_query: Query<(&TilePos, &Health, &Facing, &PowerCharges, &Inventory), With<Player>>,
+    enemy_query: Query<
+        (
+            &TilePos,
+            &Health,
+            &Facing,
+            Option<&Crab>,
+            Option<&Jellyfish>,
+            Option<&Phantom>,
+            Option<&Barnacle>,
+            Option<&ProjectileImmune>,
+        ),
+        With<Enemy>,
+    >,
+    snail_query: Query<(&TilePos, &Snail)>,
+    dropped_shell_query: Query<&TilePos, With<DroppedShell>>,
+    pickup_query: Query<(&TilePos, &Pickup)>,
+    cell_map: Res<CellMap<i32>>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    global_level_counter: Res<GlobalLevelCounter>,
+    snails_collected_this_run: Res<SnailsCollectedThisRun>,
+    mut recorded_path: ResMut<RecordedPath>,
+    map_seed: Res<MapSeed>,
+    pacifist_tracker: Res<PacifistTracker>,
+) {
+    if !input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    if global_turn_counter.current_phase != GamePhase::PlayerMovement {
+        info!("Can only quick-save at the start of the player's turn");
+        return;
+    }
+    let (tile_pos, health, facing, power_charges, inventory) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => {
+            info!("No player to quick-save");
+            return;
+        }
+    };
+    let player = PlayerSnapshot {
+        resident: ResidentSnapshot::new(tile_pos, health, facing),
+        power_charges: power_charges.charges,
+        inventory: inventory.items().to_vec(),
+    };
+    let enemies = enemy_query
+        .iter()
+        .map(
+            |(tile_pos, health, facing, crab, jellyfish, phantom, barnacle, immune)| {
+                // Anything not a crab, jellyfish, phantom or barnacle is a shark
+                let kind = match (crab, jellyfish, phantom, barnacle) {
+                    (Some(_), _, _, _) => EnemyKind::Crab {
+                        armoured: immune.is_some(),
+                    },
+                    (_, Some(jellyfish), _, _) => EnemyKind::Jellyfish {
+                        poisonous: jellyfish.poisonous,
+                    },
+                    (_, _, Some(_), _) => EnemyKind::Phantom,
+                    (_, _, _, Some(_)) => EnemyKind::Barnacle,
+                    (None, None, None, None) => EnemyKind::Shark,
+                };
+                EnemySnapshot {
+                    kind,
+                    resident: ResidentSnapshot::new(tile_pos, health, facing),
+                }
+            },
+        )
+        .collect();
+    let snapshot = RunSnapshot {
+        cell_map: cell_map.0.iter().map(|(k, v)| (*k, *v)).collect(),
+        player,
+        enemies,
+        snails: snail_query
+            .iter()
+            .map(|(tile_pos, snail)| ((tile_pos.x, tile_pos.y), snail.value))
+            .collect(),
+        dropped_shells: dropped_shell_query
+            .iter()
+            .map(|tile_pos| (tile_pos.x, tile_pos.y))
+            .collect(),
+        pickups: pickup_query
+            .iter()
+            .map(|(tile_pos, pickup)| ((tile_pos.x, tile_pos.y), pickup.0))
+            .collect(),
+        turn_count: global_turn_counter.turn_count,
+        level: global_level_counter.level(),
+        snails_collected_this_run: snails_collected_this_run.0,
+        seed: map_seed.seed,
+        kills: pacifist_tracker.kills,
+    };
+    info!(
+        "Quick-saved turn {} with {} enemies",
+        snapshot.turn_count,
+        snapshot.enemies.len()
+    );
+    quick_save.0 = Some(snapshot);
+    // Recording afresh from here, for the ghost shown on loading it
+    recorded_path.tiles = vec![*tile_pos];
+}
+
+pub fn quick_load_system(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    quick_save: Res<QuickSave>,
+    game_only_query: Query<Entity, With<GameOnly>>,
+    tilemap_query: Query<Entity, Or<(With<TileStorage>, With<TileTexture>)>>,
+    image_assets: Res<ImageAssetStore>,
+    texture_atlas_store: Res<TextureAtlasStore>,
+    loaded_profile: Res<LoadedUserProfile>,
+    windows: Res<Windows>,
+    mut global_turn_counter: ResMut<GlobalTurnCounter>,
+    mut global_level_counter: ResMut<GlobalLevelCounter>,
+    // Paired up to keep within the system parameter limit
+    (mut snails_collected_this_run, mut pacifist_tracker): (
+        ResMut<SnailsCollectedThisRun>,
+        ResMut<PacifistTracker>,
+    ),
+    settings: Res<Settings>,
+    mut recorded_path: ResMut<RecordedPath>,
+    mut ghost_path: ResMut<GhostPath>,
+    mut map_seed: ResMut<MapSeed>,
+) {
+    if !input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let snapshot = match &quick_save.0 {
+        Some(snapshot) => snapshot,
+        None => {
+            info!("Nothing quick-saved to load");
+            return;
+        }
+    };
+    if global_turn_counter.current_phase != GamePhase::PlayerMovement {
+        info!("Can only quick-load at the start of the player's turn");
+        return;
+    }
+
+    for entity in game_only_query.iter().chain(tilemap_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let cell_map = CellMap::new(snapshot.cell_map.iter().cloned().collect());
+    super::tilemap::init_tilemap(&mut commands, &image_assets, &cell_map, MAP_BORDER_SIZE);
+    commands.insert_resource(cell_map);
+
+    let player = &snapshot.player;
+    let mut inventory = Inventory::default();
+    for item in player.inventory.iter() {
+        inventory.add(*item);
+    }
+    let player_entity = super::game::spawn_player(
+        &mut commands,
+        &texture_atlas_store,
+        player.resident.tile_pos(),
+        &loaded_profile.user_profile,
+        &windows,
+    );
+    commands
+        .entity(player_entity)
+        .insert(Health::new(player.resident.hp))
+        .insert(Facing(player.resident.facing.clone()))
+        .insert(PowerCharges::new(player.power_charges))
+        .insert(inventory);
+
+    for enemy in snapshot.enemies.iter() {
+        let tile_pos = enemy.resident.tile_pos();
+        let entity = super::enemy::spawn_enemy(
+            &mut commands,
+            &texture_atlas_store,
+            &image_assets,
+            &enemy.kind,
+            tile_pos,
+            snapshot.level,
+            settings.enemy_timing,
+        );
+        commands
+            .entity(entity)
+            .insert(Health::new(enemy.resident.hp))
+            .insert(Facing(enemy.resident.facing.clone()));
+    }
+    for (pos, value) in snapshot.snails.iter() {
+        super::snails::spawn_snail(
+            &mut commands,
+            &texture_atlas_store,
+            to_tile_pos(*pos),
+            *value,
+        );
+    }
+    for pos in snapshot.dropped_shells.iter() {
+        super::snails::spawn_dropped_shell(&mut commands, &texture_atlas_store, to_tile_pos(*pos));
+    }
+    for (pos, item) in snapshot.pickups.iter() {
+        super::pickups::spawn_pickup(&mut commands, &image_assets, *item, to_tile_pos(*pos));
+    }
+
+    // Flagging a reset lets every system's local turn counter resync to the restored turn
+    global_turn_counter.reset();
+    global_turn_counter.turn_count = snapshot.turn_count;
+    global_level_counter.set(snapshot.level);
+    snails_collected_this_run.0 = snapshot.snails_collected_this_run;
+    map_seed.seed = snapshot.seed;
+    pacifist_tracker.kills = snapshot.kills;
+    // The respawned player starts the recording again
+    ghost_path.0 = std::mem::take(&mut recorded_path.tiles);
+    info!("Quick-loaded turn {}", snapshot.turn_count);
+}
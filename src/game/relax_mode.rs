@@ -0,0 +1,96 @@
+//! A single switch for players who just want to explore. With [Settings::relax_mode] on, the
+//! vortex no longer opens after a set number of turns, sudden death never starts, shells are kept
+//! on death and enemies move at least as slowly as [RelaxMode::ENEMY_TIMING]. Systems consult the
+//! [RelaxMode] resource rather than the individual settings it overrides
+
+use crate::game::balance::VortexBalance;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+
+pub struct RelaxModePlugin;
+
+impl Plugin for RelaxModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RelaxMode>()
+            .add_system(relax_mode_sync_system);
+    }
+}
+
+/// Mirrors [Settings::relax_mode], with how each overridden setting is affected
+#[derive(Debug, Default)]
+pub struct RelaxMode {
+    pub enabled: bool,
+}
+
+impl RelaxMode {
+    /// The slowest the enemies are allowed to move in relax mode
+    pub const ENEMY_TIMING: f32 = 1.5;
+
+    /// [balance] with the late spawn pushed out of reach, leaving the vortex to open only once
+    /// enough enemies are cleared
+    pub fn vortex_balance(&self, balance: &VortexBalance) -> VortexBalance {
+        let mut balance = balance.clone();
+        if self.enabled {
+            // Never reached, but kept clear of overflowing the turns remaining
+            balance.late_spawn_turn = usize::MAX / 2;
+        }
+        balance
+    }
+
+    pub fn sudden_death(&self) -> bool {
+        !self.enabled
+    }
+
+    /// Shells banked from [collected] at the end of a run, keeping all of them on death
+    pub fn shells_banked(&self, settings: &Settings, collected: usize, died: bool) -> usize {
+        settings.shells_banked(collected, died && !self.enabled)
+    }
+
+    pub fn enemy_timing(&self, settings: &Settings) -> f32 {
+        if self.enabled {
+            settings.enemy_timing.max(Self::ENEMY_TIMING)
+        } else {
+            settings.enemy_timing
+        }
+    }
+}
+
+fn relax_mode_sync_system(settings: Res<Settings>, mut relax_mode: ResMut<RelaxMode>) {
+    if settings.is_changed() && relax_mode.enabled != settings.relax_mode {
+        relax_mode.enabled = settings.relax_mode;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RelaxMode;
+    use crate::game::balance::VortexBalance;
+    use crate::game::end_game::{vortex_spawn_status, VortexSpawnStatus};
+    use crate::profiles::settings::Settings;
+
+    #[test]
+    fn test_relax_mode_overrides() {
+        let settings = Settings::default();
+        let relax_mode = RelaxMode { enabled: true };
+        assert_eq!(7, relax_mode.shells_banked(&settings, 7, true));
+        assert_eq!(RelaxMode::ENEMY_TIMING, relax_mode.enemy_timing(&settings));
+
+        // Long past the usual late spawn, the vortex still waits on enemies being cleared
+        let balance = relax_mode.vortex_balance(&VortexBalance::default());
+        assert!(matches!(
+            vortex_spawn_status(100, 6, &balance),
+            VortexSpawnStatus::Pending { .. }
+        ));
+        assert_eq!(
+            VortexSpawnStatus::Ready,
+            vortex_spawn_status(100, 3, &balance)
+        );
+
+        let relax_mode = RelaxMode::default();
+        assert_eq!(0, relax_mode.shells_banked(&settings, 7, true));
+        assert_eq!(
+            VortexBalance::default(),
+            relax_mode.vortex_balance(&VortexBalance::default())
+        );
+    }
+}
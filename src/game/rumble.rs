@@ -0,0 +1,86 @@
+//! Controller rumble on the player being hurt or killed. Bevy doesn't wrap force feedback yet, so
+//! this drives the [Gilrs] instance inserted by bevy's gilrs plugin directly. Every connected
+//! gamepad that supports it rumbles, and with none (or on platforms without force feedback) it's
+//! a no-op
+
+use crate::game::events::InfoEvent;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks};
+use gilrs::{GamepadId, Gilrs};
+
+#[derive(Debug, Clone, Copy)]
+struct Rumble {
+    /// 0 to [u16::MAX]
+    magnitude: u16,
+    duration_ms: u32,
+}
+
+const HURT_RUMBLE: Rumble = Rumble {
+    magnitude: u16::MAX / 3,
+    duration_ms: 150,
+};
+const KILLED_RUMBLE: Rumble = Rumble {
+    magnitude: u16::MAX,
+    duration_ms: 500,
+};
+
+impl Rumble {
+    fn for_event(event: &InfoEvent) -> Option<Self> {
+        match event {
+            InfoEvent::PlayerHurt => Some(HURT_RUMBLE),
+            InfoEvent::PlayerKilled => Some(KILLED_RUMBLE),
+            _ => None,
+        }
+    }
+
+    fn play(&self, gilrs: &mut Gilrs) -> Result<Option<Effect>, gilrs::ff::Error> {
+        let gamepads: Vec<GamepadId> = gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect();
+        if gamepads.is_empty() {
+            return Ok(None);
+        }
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: self.magnitude,
+                },
+                scheduling: Replay {
+                    play_for: Ticks::from_ms(self.duration_ms),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .gamepads(&gamepads)
+            .finish(gilrs)?;
+        effect.play()?;
+        Ok(Some(effect))
+    }
+}
+
+/// Rumbles on [InfoEvent::PlayerHurt] and [InfoEvent::PlayerKilled], when
+/// [Settings::vibration] is on. The strongest of a frame's events wins
+pub fn rumble_system(
+    mut info_event_reader: EventReader<InfoEvent>,
+    settings: Res<Settings>,
+    gilrs: Option<NonSendMut<Gilrs>>,
+    // Effects stop when dropped, so the latest is held until replaced by the next
+    mut playing: Local<Option<Effect>>,
+) {
+    let rumble = info_event_reader
+        .iter()
+        .filter_map(Rumble::for_event)
+        .max_by_key(|rumble| rumble.magnitude);
+    let (rumble, mut gilrs) = match (rumble, gilrs) {
+        (Some(rumble), Some(gilrs)) if settings.vibration => (rumble, gilrs),
+        _ => return,
+    };
+    match rumble.play(&mut gilrs) {
+        Ok(Some(effect)) => *playing = Some(effect),
+        Ok(None) => (),
+        Err(e) => debug!("Unable to rumble: {}", e),
+    }
+}
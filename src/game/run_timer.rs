@@ -0,0 +1,83 @@
+use crate::game::events::GameEvent;
+use bevy::app::App;
+use bevy::ecs::prelude::{EventReader, Res, ResMut};
+use bevy::prelude::{Plugin, SystemSet};
+use bevy::time::Time;
+use std::time::Duration;
+
+pub struct RunTimerPlugin;
+
+impl Plugin for RunTimerPlugin {
+    fn build(&self, app: &mut App) {
+        // Only ticks while in a level, so the overlay (pushed over it) and level transitions
+        // don't count towards the time
+        app.insert_resource(RunTimer::default()).add_system_set(
+            SystemSet::on_update(crate::CoreState::GameLevel).with_system(run_timer_system),
+        );
+    }
+}
+
+/// Real time spent in the current run, across all its levels. Started by the level setup and
+/// stopped as soon as the player dies or is hooked out
+#[derive(Debug, Default)]
+pub struct RunTimer {
+    pub elapsed: Duration,
+    pub running: bool,
+}
+
+impl RunTimer {
+    pub fn start(&mut self) {
+        self.running = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Formats as mm:ss.mmm, minutes carrying on past an hour rather than wrapping
+pub fn format_run_time(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let seconds = duration.as_secs() % 60;
+    format!(
+        "{:02}:{:02}.{:03}",
+        minutes,
+        seconds,
+        duration.subsec_millis()
+    )
+}
+
+fn run_timer_system(
+    time: Res<Time>,
+    mut run_timer: ResMut<RunTimer>,
+    mut game_event_reader: EventReader<GameEvent>,
+) {
+    for event in game_event_reader.iter() {
+        if let GameEvent::PlayerDied | GameEvent::PlayerHooked = event {
+            run_timer.stop();
+        }
+    }
+    if run_timer.running {
+        run_timer.elapsed += time.delta();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::format_run_time;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_run_time() {
+        assert_eq!("00:00.000", format_run_time(Duration::ZERO));
+        assert_eq!("01:05.042", format_run_time(Duration::from_millis(65_042)));
+        assert_eq!(
+            "75:00.999",
+            format_run_time(Duration::from_millis(4_500_999))
+        );
+    }
+}
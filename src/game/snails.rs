@@ -7,10 +7,76 @@ use crate::game::tilemap::TilePosExt;
 use crate::map_gen::cell_map::CellMap;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::tiles::TilePos;
+use rand::prelude::SliceRandom;
 
 /// Snails serve as the collectable resource, are not a moving tile resident/enemy
 #[derive(Debug, Component)]
-pub struct Snail;
+pub struct Snail {
+    /// Shells given when picked up
+    pub value: usize,
+}
+
+const TREASURE_SNAIL_VALUE: usize = 5;
+const TREASURE_SNAIL_TINT: Color = Color::rgb(1.0, 0.85, 0.2);
+
+/// Spawns a snail worth [value] shells, anything worth more than one looks like treasure
+pub fn spawn_snail(
+    commands: &mut Commands,
+    atlases: &TextureAtlasStore,
+    tile_pos: TilePos,
+    value: usize,
+) {
+    let (colour, scale) = if value > 1 {
+        (TREASURE_SNAIL_TINT, 0.9)
+    } else {
+        (Color::WHITE, 0.7)
+    };
+    let start_pos = tile_pos.to_world_pos(9.0);
+    let mut transform = Transform::from_translation(start_pos);
+    transform.scale = Vec3::splat(scale);
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlases.get(&TextureAtlasAsset::SnailSpritesheet),
+            sprite: TextureAtlasSprite {
+                color: colour,
+                ..Default::default()
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(tile_pos)
+        .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
+        .insert(SimpleSpriteAnimation::new(0, 4))
+        .insert(GameOnly {})
+        .insert(Snail { value });
+}
+
+/// A single shell left behind by a killed enemy, see [crate::game::enemy::ShellDropChance].
+/// Picked up like a snail, but isn't one, so doesn't count towards collecting all the snails
+#[derive(Debug, Component)]
+pub struct DroppedShell;
+
+impl DroppedShell {
+    pub const VALUE: usize = 1;
+}
+
+pub fn spawn_dropped_shell(
+    commands: &mut Commands,
+    atlases: &TextureAtlasStore,
+    tile_pos: TilePos,
+) {
+    let mut transform = Transform::from_translation(tile_pos.to_world_pos(9.0));
+    transform.scale = Vec3::splat(0.5);
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlases.get(&TextureAtlasAsset::SnailSpritesheet),
+            transform,
+            ..Default::default()
+        })
+        .insert(tile_pos)
+        .insert(GameOnly {})
+        .insert(DroppedShell);
+}
 
 fn add_snails(
     num_snails: usize,
@@ -19,31 +85,39 @@ fn add_snails(
     cell_map: &CellMap<i32>,
     exclude_positions: Option<&Vec<(i32, i32)>>,
 ) -> Vec<(i32, i32)> {
-    let atlas_handle = atlases.get(&TextureAtlasAsset::SnailSpritesheet);
     let spawn_positions = cell_map.distribute_points_by_cost(num_snails, exclude_positions);
     for (x, y) in spawn_positions.iter() {
         let tile_pos = TilePos {
             x: *x as u32,
             y: *y as u32,
         };
-        let start_pos = tile_pos.to_world_pos(9.0);
-        let mut transform = Transform::from_translation(start_pos);
-        transform.scale = Vec3::splat(0.7);
-        commands
-            .spawn_bundle(SpriteSheetBundle {
-                texture_atlas: atlas_handle.clone(),
-                transform,
-                ..Default::default()
-            })
-            .insert(tile_pos)
-            .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
-            .insert(SimpleSpriteAnimation::new(0, 4))
-            .insert(GameOnly {})
-            .insert(Snail {});
+        spawn_snail(commands, atlases, tile_pos, 1);
     }
     spawn_positions
 }
 
+/// Places a single, more valuable, snail at a random dead end of the map to reward exploring
+/// its extremities. Returns where it was placed, if there was a free dead end
+pub fn spawn_treasure_snail(
+    commands: &mut Commands,
+    texture_atlases: &Res<TextureAtlasStore>,
+    cell_map: &CellMap<i32>,
+    exclude_positions: Option<&Vec<(i32, i32)>>,
+) -> Option<(i32, i32)> {
+    let dead_ends: Vec<(i32, i32)> = cell_map
+        .dead_ends()
+        .into_iter()
+        .filter(|pos| exclude_positions.map_or(true, |excluded| !excluded.contains(pos)))
+        .collect();
+    let (x, y) = *dead_ends.choose(&mut rand::thread_rng())?;
+    let tile_pos = TilePos {
+        x: x as u32,
+        y: y as u32,
+    };
+    spawn_snail(commands, texture_atlases, tile_pos, TREASURE_SNAIL_VALUE);
+    Some((x, y))
+}
+
 pub fn choose_number_of_and_spawn_snails(
     commands: &mut Commands,
     texture_atlases: &Res<TextureAtlasStore>,
@@ -68,18 +142,36 @@ pub fn choose_number_of_and_spawn_snails(
 
 pub fn snail_pickup_system(
     mut commands: Commands,
-    snail_query: Query<(Entity, &TilePos), With<Snail>>,
+    snail_query: Query<(Entity, &TilePos, &Snail)>,
     player_query: Query<&TilePos, With<Player>>,
     mut snail_shells_collected_this_run: ResMut<SnailsCollectedThisRun>,
     mut info_event_writer: EventWriter<InfoEvent>,
 ) {
     for player_pos in player_query.iter() {
-        for (snail_entity, snail_pos) in snail_query.iter() {
+        for (snail_entity, snail_pos, snail) in snail_query.iter() {
             if snail_pos == player_pos {
-                snail_shells_collected_this_run.0 += 1;
+                snail_shells_collected_this_run.0 += snail.value;
                 commands.entity(snail_entity).despawn();
                 info_event_writer.send(InfoEvent::PlayerPickedUpSnail);
             }
         }
     }
 }
+
+pub fn dropped_shell_pickup_system(
+    mut commands: Commands,
+    shell_query: Query<(Entity, &TilePos), With<DroppedShell>>,
+    player_query: Query<&TilePos, With<Player>>,
+    mut snail_shells_collected_this_run: ResMut<SnailsCollectedThisRun>,
+    mut info_event_writer: EventWriter<InfoEvent>,
+) {
+    for player_pos in player_query.iter() {
+        for (shell_entity, shell_pos) in shell_query.iter() {
+            if shell_pos == player_pos {
+                snail_shells_collected_this_run.0 += DroppedShell::VALUE;
+                commands.entity(shell_entity).despawn();
+                info_event_writer.send(InfoEvent::PlayerPickedUpSnail);
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! A periodic sonar ping from the player. Every [SonarBalance::interval] turns a ring grows out
+//! from the player and a blip marks each enemy within [SonarBalance::radius] tiles, giving a feel
+//! for what's close by without showing the whole map
+
+use crate::asset_handling::asset::ImageAsset;
+use crate::asset_handling::ImageAssetStore;
+use crate::game::balance::{BalanceConfig, SonarBalance};
+use crate::game::components::{GameOnly, Growing, Player};
+use crate::game::enemy::Enemy;
+use crate::game::tilemap::TilePosExt;
+use crate::game::timed_removal::TimedDespawn;
+use crate::game::turn::{GamePhase, GlobalTurnCounter, TurnCounter};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use std::time::Duration;
+
+pub struct SonarPlugin;
+
+impl Plugin for SonarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(crate::CoreState::GameLevel).with_system(sonar_ping_system),
+        );
+    }
+}
+
+const RING_COLOUR: Color = Color::rgba(0.4, 0.9, 1.0, 0.6);
+const BLIP_COLOUR: Color = Color::rgba(1.0, 0.3, 0.3, 0.8);
+const PING_DURATION: Duration = Duration::from_millis(800);
+
+/// Whether the sonar pings at the start of [turn_count]
+pub fn pings_on(turn_count: usize, balance: &SonarBalance) -> bool {
+    balance.interval > 0 && turn_count > 0 && turn_count % balance.interval == 0
+}
+
+/// Whether [pos] is within [radius] tiles of [centre] in a straight line, so inside the round ring
+/// rather than the diamond [TilePosExt::distance_to] would give
+pub fn within_ring(centre: &TilePos, pos: &TilePos, radius: usize) -> bool {
+    let (dx, dy) = (
+        centre.x.abs_diff(pos.x) as usize,
+        centre.y.abs_diff(pos.y) as usize,
+    );
+    dx * dx + dy * dy <= radius * radius
+}
+
+fn sonar_ping_system(
+    mut commands: Commands,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    balance: Res<BalanceConfig>,
+    image_assets: Res<ImageAssetStore>,
+    player_query: Query<&TilePos, With<Player>>,
+    enemy_query: Query<&TilePos, With<Enemy>>,
+) {
+    if !global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        return;
+    }
+    local_turn_counter.incr();
+    if !pings_on(global_turn_counter.turn_count, &balance.sonar) {
+        return;
+    }
+    let player_pos = match player_query.get_single() {
+        Ok(player_pos) => player_pos,
+        Err(_) => return,
+    };
+    // Reaches the edge of the radius as it despawns
+    let ring_growth =
+        (balance.sonar.radius * 2).saturating_sub(1) as f32 / PING_DURATION.as_secs_f32();
+    spawn_ping(
+        &mut commands,
+        &image_assets,
+        player_pos,
+        RING_COLOUR,
+        ring_growth,
+    );
+    let mut blips = 0;
+    for enemy_pos in enemy_query.iter() {
+        if within_ring(player_pos, enemy_pos, balance.sonar.radius) {
+            spawn_ping(&mut commands, &image_assets, enemy_pos, BLIP_COLOUR, 1.0);
+            blips += 1;
+        }
+    }
+    debug!("Sonar ping found {} enemies", blips);
+}
+
+/// A tile sized ring at [tile_pos] that grows by [growth] tiles across per second until it's gone
+fn spawn_ping(
+    commands: &mut Commands,
+    image_assets: &ImageAssetStore,
+    tile_pos: &TilePos,
+    colour: Color,
+    growth: f32,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: image_assets.get(&ImageAsset::SonarRing),
+            sprite: Sprite {
+                color: colour,
+                custom_size: Some(Vec2::splat(64.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(tile_pos.to_world_pos(8.0)),
+            ..Default::default()
+        })
+        .insert(Growing { factor: growth })
+        .insert(TimedDespawn::new(PING_DURATION))
+        .insert(GameOnly);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{pings_on, within_ring};
+    use crate::game::balance::SonarBalance;
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    #[test]
+    fn test_within_ring() {
+        let centre = TilePos { x: 5, y: 5 };
+        assert!(within_ring(&centre, &TilePos { x: 9, y: 5 }, 4));
+        assert!(!within_ring(&centre, &TilePos { x: 10, y: 5 }, 4));
+        // Out of a diamond of the same radius, but inside the ring
+        assert!(within_ring(&centre, &TilePos { x: 7, y: 8 }, 4));
+        assert!(!within_ring(&centre, &TilePos { x: 8, y: 8 }, 4));
+    }
+
+    #[test]
+    fn test_pings_on() {
+        let balance = SonarBalance {
+            interval: 4,
+            radius: 5,
+        };
+        assert!(!pings_on(0, &balance));
+        assert!(!pings_on(3, &balance));
+        assert!(pings_on(4, &balance));
+        assert!(pings_on(8, &balance));
+        let off = SonarBalance {
+            interval: 0,
+            radius: 5,
+        };
+        assert!(!pings_on(4, &off));
+    }
+}
@@ -0,0 +1,177 @@
+//! Pressure on levels that drag on: once the vortex is out and the level has gone past
+//! [SuddenDeathBalance::start_turn], a [Pursuer] spawns every few turns and heads for the player.
+//! Waiting on the vortex means there's always a way out, including on levels whose objective
+//! holds the vortex back
+
+use crate::asset_handling::{ImageAssetStore, TextureAtlasStore};
+use crate::game::balance::{BalanceConfig, SuddenDeathBalance};
+use crate::game::components::{MapDirection, MoveWeighting, Player};
+use crate::game::end_game::EndGameVortex;
+use crate::game::enemy::{Enemy, EnemyKind};
+use crate::game::relax_mode::RelaxMode;
+use crate::game::tilemap::TilePosExt;
+use crate::game::turn::{GamePhase, GlobalLevelCounter, GlobalTurnCounter, TurnCounter};
+use crate::map_gen::cell_map::CellMap;
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+pub struct SuddenDeathPlugin;
+
+impl Plugin for SuddenDeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(crate::CoreState::GameLevel)
+                .with_system(pursuer_spawn_system)
+                .with_system(pursuer_weighting_system),
+        );
+    }
+}
+
+/// An enemy spawned by sudden death, its [MoveWeighting] kept pointing at the player
+#[derive(Debug, Component)]
+pub struct Pursuer;
+
+impl Pursuer {
+    pub const TINT: Color = Color::rgb(1.0, 0.5, 0.5);
+}
+
+/// Whether a pursuer spawns at the start of [turn_count]
+pub fn pursuer_due(turn_count: usize, balance: &SuddenDeathBalance) -> bool {
+    turn_count >= balance.start_turn
+        && (turn_count - balance.start_turn) % balance.spawn_interval.max(1) == 0
+}
+
+/// Weights moves towards [target] by [SuddenDeathBalance::pursuit_weighting], leaving the rest
+/// at 1.0
+pub fn pursuit_weighting(
+    from: &TilePos,
+    target: &TilePos,
+    pursuit_weighting: f32,
+) -> MoveWeighting {
+    let mut weights = MoveWeighting::all(1.0);
+    if target.x > from.x {
+        weights.set(&MapDirection::Right, pursuit_weighting);
+    } else if target.x < from.x {
+        weights.set(&MapDirection::Left, pursuit_weighting);
+    }
+    if target.y > from.y {
+        weights.set(&MapDirection::Up, pursuit_weighting);
+    } else if target.y < from.y {
+        weights.set(&MapDirection::Down, pursuit_weighting);
+    }
+    weights
+}
+
+fn pursuer_spawn_system(
+    mut commands: Commands,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    balance: Res<BalanceConfig>,
+    vortex_query: Query<Entity, With<EndGameVortex>>,
+    player_query: Query<&TilePos, With<Player>>,
+    enemy_query: Query<&TilePos, With<Enemy>>,
+    cell_map: Res<CellMap<i32>>,
+    atlases: Res<TextureAtlasStore>,
+    image_store: Res<ImageAssetStore>,
+    global_level_counter: Res<GlobalLevelCounter>,
+    settings: Res<Settings>,
+    relax_mode: Res<RelaxMode>,
+) {
+    if !global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        return;
+    }
+    local_turn_counter.incr();
+    if !relax_mode.sudden_death()
+        || vortex_query.is_empty()
+        || !pursuer_due(global_turn_counter.turn_count, &balance.sudden_death)
+    {
+        return;
+    }
+    let player_pos = match player_query.get_single() {
+        Ok(player_pos) => player_pos.as_i32s(),
+        Err(_) => return,
+    };
+    let mut exclude_positions: Vec<(i32, i32)> = enemy_query
+        .iter()
+        .map(|tile_pos| tile_pos.as_i32s())
+        .collect();
+    exclude_positions.push(player_pos);
+    let spawn_pos = match cell_map
+        .recalculate(player_pos)
+        .distribute_points_by_cost(1, Some(&exclude_positions))
+        .first()
+    {
+        Some((x, y)) => TilePos {
+            x: *x as u32,
+            y: *y as u32,
+        },
+        None => return,
+    };
+    info!(
+        "Sudden death, spawning a pursuer at {:?} on turn {}",
+        spawn_pos, global_turn_counter.turn_count
+    );
+    let pursuer = super::enemy::spawn_enemy(
+        &mut commands,
+        &atlases,
+        &image_store,
+        &EnemyKind::Shark,
+        spawn_pos,
+        global_level_counter.difficulty_level(),
+        settings.enemy_timing,
+    );
+    commands
+        .entity(pursuer)
+        .insert(TextureAtlasSprite {
+            color: Pursuer::TINT,
+            ..Default::default()
+        })
+        .insert(Pursuer);
+}
+
+/// Re-points every pursuer's [MoveWeighting] at the player whenever either moves
+fn pursuer_weighting_system(
+    balance: Res<BalanceConfig>,
+    player_query: Query<&TilePos, (With<Player>, Without<Pursuer>)>,
+    mut pursuer_query: Query<(&TilePos, &mut MoveWeighting), With<Pursuer>>,
+) {
+    let player_pos = match player_query.get_single() {
+        Ok(player_pos) => player_pos,
+        Err(_) => return,
+    };
+    for (tile_pos, mut move_weighting) in pursuer_query.iter_mut() {
+        *move_weighting =
+            pursuit_weighting(tile_pos, player_pos, balance.sudden_death.pursuit_weighting);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{pursuer_due, pursuit_weighting};
+    use crate::game::balance::SuddenDeathBalance;
+    use crate::game::components::MapDirection;
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    #[test]
+    fn test_pursuer_due() {
+        let balance = SuddenDeathBalance {
+            start_turn: 40,
+            spawn_interval: 3,
+            pursuit_weighting: 5.0,
+        };
+        assert!(!pursuer_due(39, &balance));
+        assert!(pursuer_due(40, &balance));
+        assert!(!pursuer_due(41, &balance));
+        assert!(pursuer_due(43, &balance));
+    }
+
+    #[test]
+    fn test_pursuit_weighting() {
+        let weights = pursuit_weighting(&TilePos { x: 5, y: 5 }, &TilePos { x: 2, y: 5 }, 5.0);
+        assert_eq!(5.0, weights.get(&MapDirection::Left));
+        assert_eq!(1.0, weights.get(&MapDirection::Right));
+        assert_eq!(1.0, weights.get(&MapDirection::Up));
+        assert_eq!(1.0, weights.get(&MapDirection::Down));
+    }
+}
@@ -2,10 +2,13 @@ use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use log::info;
 use num::Integer;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
 
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
-use crate::game::components::TileType;
+use crate::game::components::{GameOnly, MapDirection, TileType};
 use crate::map_gen::cell_map::CellMap;
 
 pub type TileStorageQuery<'w, 's> = Query<'w, 's, &'static TileStorage, ()>;
@@ -15,6 +18,10 @@ pub struct TileMapOnly;
 
 pub trait TilePosExt {
     fn add(&self, add: (i32, i32)) -> Self;
+    /// As [TilePosExt::add], but [None] rather than panicking off the bottom or left of the map
+    fn checked_add(&self, add: (i32, i32)) -> Option<Self>
+    where
+        Self: Sized;
 
     ///non-euclidean distance between two tileposes
     fn distance_to(&self, other: &Self) -> usize;
@@ -34,6 +41,13 @@ impl TilePosExt for TilePos {
         }
     }
 
+    fn checked_add(&self, add: (i32, i32)) -> Option<Self> {
+        Some(Self {
+            x: helpers::add(self.x, add.0)?,
+            y: helpers::add(self.y, add.1)?,
+        })
+    }
+
     fn distance_to(&self, other: &Self) -> usize {
         let dist = self.x.abs_diff(other.x) + self.y.abs_diff(other.y);
         dist as usize
@@ -85,6 +99,68 @@ impl TilePosExt for TilePos {
 #[derive(Debug, Component)]
 pub struct HasTileType(pub TileType);
 
+/// Water that pushes whatever is stood in it a tile downstream as each turn ends, see
+/// [generate_currents]
+#[derive(Debug, Component)]
+pub struct HasCurrent(pub MapDirection);
+
+/// Currents started per map, each a straight run of up to [CURRENT_LENGTH] tiles
+const CURRENT_RUNS: usize = 2;
+const CURRENT_LENGTH: usize = 4;
+
+/// Picks a few straight runs of water in [cell_map], each flowing along its length. A run starts
+/// on a random cell and stops early at anything that isn't water
+pub fn generate_currents(
+    cell_map: &CellMap<i32>,
+    rng: &mut impl Rng,
+) -> HashMap<(i32, i32), MapDirection> {
+    let mut cells = cell_map.get_all_cells();
+    // Sorted so the picks only depend on the rng, not on hash order
+    cells.sort();
+    let mut currents = HashMap::new();
+    for _ in 0..CURRENT_RUNS {
+        let (mut cell, direction) = match (cells.choose(rng), MapDirection::ALL.choose(rng)) {
+            (Some(cell), Some(direction)) => (*cell, direction),
+            _ => break,
+        };
+        let step = direction.to_pos_move();
+        for _ in 0..CURRENT_LENGTH {
+            if !cell_map.contains(&cell) {
+                break;
+            }
+            currents.insert(cell, direction.clone());
+            cell = (cell.0 + step.0, cell.1 + step.1);
+        }
+    }
+    currents
+}
+
+/// Marks each new current with a faint arrow pointing downstream
+pub fn current_arrow_system(
+    mut commands: Commands,
+    image_assets: Res<ImageAssetStore>,
+    current_query: Query<(&TilePos, &HasCurrent), Added<HasCurrent>>,
+) {
+    for (tile_pos, HasCurrent(direction)) in current_query.iter() {
+        // Over the tiles but under everything stood on them
+        let mut transform = Transform::from_translation(tile_pos.to_world_pos(5.0));
+        transform.rotate(Quat::from_rotation_z(
+            direction.to_rotation_from_right_zero(),
+        ));
+        commands
+            .spawn_bundle(SpriteBundle {
+                texture: image_assets.get(&ImageAsset::IntentionArrow),
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.25),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            })
+            .insert(GameOnly {});
+    }
+}
+
 pub fn cleanup(
     mut commands: Commands,
     tilemap_query: Query<Entity, With<TileStorage>>,
@@ -138,6 +214,7 @@ pub fn init_tilemap(
     };
     let mut tile_storage = TileStorage::empty(tilemap_size);
     let tilemap_entity = commands.spawn().id();
+    let currents = generate_currents(cell_map, &mut rand::thread_rng());
 
     println!(
         "Map_tile_dims: {:?}\nMap_chunk_dims: {:?}",
@@ -154,16 +231,19 @@ pub fn init_tilemap(
                 x: i as u32,
                 y: j as u32,
             };
-            let tile_entity = commands
-                .spawn_bundle(TileBundle {
-                    position: tile_pos,
-                    texture: tile_type.to_raw_tile(),
-                    tilemap_id: TilemapId(tilemap_entity.clone()),
-                    ..Default::default()
-                })
+            let mut tile_commands = commands.spawn_bundle(TileBundle {
+                position: tile_pos,
+                texture: tile_type.to_raw_tile(),
+                tilemap_id: TilemapId(tilemap_entity.clone()),
+                ..Default::default()
+            });
+            tile_commands
                 .insert(HasTileType(tile_type))
-                .insert(TileMapOnly)
-                .id();
+                .insert(TileMapOnly);
+            if let Some(direction) = currents.get(&(i as i32, j as i32)) {
+                tile_commands.insert(HasCurrent(direction.clone()));
+            }
+            let tile_entity = tile_commands.id();
             tile_storage.set(&tile_pos, Some(tile_entity));
         }
     }
@@ -195,7 +275,7 @@ mod helpers {
 mod test {
     use crate::asset_handling::asset::ImageAsset;
     use crate::asset_handling::ImageAssetStore;
-    use crate::game::tilemap::init_tilemap;
+    use crate::game::tilemap::{generate_currents, init_tilemap};
     use crate::map_gen::cell_map::CellMap;
     use bevy::prelude::*;
     use bevy_ecs_tilemap::prelude::*;
@@ -290,6 +370,21 @@ mod test {
         assert_eq!(true, true);
     }
 
+    #[test]
+    fn test_currents_only_on_water() {
+        let mut m = HashMap::new();
+        for i in 0..10 {
+            m.insert((i, 0), 0);
+            m.insert((i, 1), 0);
+        }
+        let cell_map = CellMap::new(m);
+        for _ in 0..20 {
+            let currents = generate_currents(&cell_map, &mut rand::thread_rng());
+            assert!(!currents.is_empty());
+            assert!(currents.keys().all(|cell| cell_map.contains(cell)));
+        }
+    }
+
     fn test_startup_system(mut commands: Commands, images: Res<ImageAssetStore>) {
         let mut m = HashMap::new();
         for i in 0..10 {
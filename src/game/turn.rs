@@ -97,11 +97,17 @@ impl GamePhase {
 
 pub struct GlobalLevelCounter {
     level_count: usize,
+    /// Added to the level for anything that scales with difficulty, see
+    /// [crate::profiles::profiles::UserProfile::difficulty_offset]
+    difficulty_offset: usize,
 }
 
 impl Default for GlobalLevelCounter {
     fn default() -> Self {
-        Self { level_count: 1 }
+        Self {
+            level_count: 1,
+            difficulty_offset: 0,
+        }
     }
 }
 
@@ -114,7 +120,27 @@ impl GlobalLevelCounter {
         self.level_count
     }
 
+    /// The level enemies are spawned and scaled for, ahead of [GlobalLevelCounter::level] with new
+    /// game plus
+    pub fn difficulty_level(&self) -> usize {
+        self.level_count + self.difficulty_offset
+    }
+
+    pub fn set_difficulty_offset(&mut self, difficulty_offset: usize) {
+        self.difficulty_offset = difficulty_offset;
+    }
+
+    /// Levels completed this run, i.e. those before the current one
+    pub fn levels_cleared(&self) -> usize {
+        self.level_count - 1
+    }
+
     pub fn reset(&mut self) {
         self.level_count = 1;
+        self.difficulty_offset = 0;
+    }
+
+    pub fn set(&mut self, level: usize) {
+        self.level_count = level;
     }
 }
@@ -0,0 +1,266 @@
+//! A look back over the last few turns after dying, to see what went wrong. Where the player and
+//! every enemy stood is recorded at the start of each turn into [TurnHistory], and with
+//! [Settings::death_review] on, dying opens a [TurnReview] of it instead of heading straight back
+//! to the hub. The move left and right keys step through the turns, shown as markers over the
+//! map, and the confirm key carries on as usual
+
+use crate::game::components::Player;
+use crate::game::enemy::Enemy;
+use crate::game::events::GameEvent;
+use crate::game::key_bindings::KeyBindings;
+use crate::game::tilemap::TilePosExt;
+use crate::game::turn::{GamePhase, GlobalTurnCounter, TurnCounter};
+use crate::helpers::cleanup::recursive_cleanup;
+use crate::menu_core::menu_core::text::UiFont;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+use std::collections::VecDeque;
+
+pub struct TurnReviewPlugin;
+
+impl Plugin for TurnReviewPlugin {
+    fn build(&self, app: &mut App) {
+        // Only recorded in the real game, so the demo never has anything to review
+        let state = crate::CoreState::GameLevel;
+        app.init_resource::<TurnHistory>()
+            .add_system_set(
+                SystemSet::on_update(state)
+                    .with_system(record_turn_system)
+                    .with_system(turn_review_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(state)
+                    .with_system(recursive_cleanup::<TurnReviewOnly>)
+                    .with_system(turn_review_cleanup),
+            );
+    }
+}
+
+/// Turns kept in the [TurnHistory], older ones drop off
+const REVIEW_TURNS: usize = 8;
+
+/// Where everyone stood at the start of a turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnFrame {
+    pub turn: usize,
+    pub player: TilePos,
+    pub enemies: Vec<TilePos>,
+}
+
+/// The last [REVIEW_TURNS] [TurnFrame]s of the level, oldest first
+#[derive(Debug, Default)]
+pub struct TurnHistory(VecDeque<TurnFrame>);
+
+impl TurnHistory {
+    pub fn record(&mut self, frame: TurnFrame) {
+        self.0.push_back(frame);
+        if self.0.len() > REVIEW_TURNS {
+            self.0.pop_front();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Resource present while reviewing, holding which frame of the [TurnHistory] is shown. Starts on
+/// the moment of death
+#[derive(Debug, Default)]
+pub struct TurnReview {
+    index: usize,
+}
+
+impl TurnReview {
+    /// Moves [steps] frames on, or back for negative, staying within the [frame_count] frames
+    fn step(&mut self, steps: isize, frame_count: usize) {
+        let last = frame_count.saturating_sub(1) as isize;
+        self.index = (self.index as isize + steps).clamp(0, last) as usize;
+    }
+}
+
+/// On everything spawned to show the review
+#[derive(Component)]
+struct TurnReviewOnly;
+
+#[derive(Component)]
+struct TurnReviewLabel;
+
+const PLAYER_MARKER_COLOUR: Color = Color::rgba(0.3, 0.6, 1.0, 0.8);
+const ENEMY_MARKER_COLOUR: Color = Color::rgba(1.0, 0.2, 0.2, 0.8);
+const MARKER_SIZE: f32 = 40.0;
+/// Over everything standing on the map
+const MARKER_Z: f32 = 20.0;
+
+fn record_turn_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut local_turn_counter: Local<TurnCounter>,
+    player_query: Query<&TilePos, With<Player>>,
+    enemy_query: Query<&TilePos, With<Enemy>>,
+    mut turn_history: ResMut<TurnHistory>,
+) {
+    if global_turn_counter.can_take_turn(&mut local_turn_counter, GamePhase::PlayerMovement) {
+        if let Ok(player) = player_query.get_single() {
+            turn_history.record(TurnFrame {
+                turn: global_turn_counter.turn_count,
+                player: *player,
+                enemies: enemy_query.iter().copied().collect(),
+            });
+        }
+        local_turn_counter.incr();
+    }
+}
+
+fn spawn_marker(commands: &mut Commands, tile_pos: &TilePos, colour: Color) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: colour,
+                custom_size: Some(Vec2::splat(MARKER_SIZE)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(tile_pos.to_world_pos(MARKER_Z)),
+            ..Default::default()
+        })
+        .insert(TurnReviewOnly);
+}
+
+fn turn_review_system(
+    mut commands: Commands,
+    review: Option<ResMut<TurnReview>>,
+    mut input: ResMut<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut turn_history: ResMut<TurnHistory>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    player_query: Query<&TilePos, With<Player>>,
+    enemy_query: Query<&TilePos, With<Enemy>>,
+    review_only_query: Query<Entity, With<TurnReviewOnly>>,
+    mut label_query: Query<&mut Text, With<TurnReviewLabel>>,
+    ui_font: Res<UiFont>,
+    mut game_event_writer: EventWriter<GameEvent>,
+) {
+    let mut review = match review {
+        Some(review) => review,
+        None => return,
+    };
+    if review.is_added() {
+        // The moment of death goes on the end, as it came part way through the last turn
+        if let Ok(player) = player_query.get_single() {
+            turn_history.record(TurnFrame {
+                turn: global_turn_counter.turn_count,
+                player: *player,
+                enemies: enemy_query.iter().copied().collect(),
+            });
+        }
+        review.index = turn_history.0.len().saturating_sub(1);
+    }
+
+    if input.just_pressed(key_bindings.confirm) {
+        input.clear();
+        for entity in review_only_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        commands.remove_resource::<TurnReview>();
+        game_event_writer.send(GameEvent::PlayerDied);
+        return;
+    }
+    let frame_count = turn_history.0.len();
+    // Cleared once handled so the moves don't also reach the game
+    if input.just_pressed(key_bindings.move_left) {
+        input.clear();
+        review.step(-1, frame_count);
+    } else if input.just_pressed(key_bindings.move_right) {
+        input.clear();
+        review.step(1, frame_count);
+    }
+    if !review.is_changed() {
+        return;
+    }
+
+    let frame = match turn_history.0.get(review.index) {
+        Some(frame) => frame,
+        None => return,
+    };
+    for entity in review_only_query.iter() {
+        // The label is kept, only the markers are redrawn
+        if label_query.get(entity).is_err() {
+            commands.entity(entity).despawn();
+        }
+    }
+    spawn_marker(&mut commands, &frame.player, PLAYER_MARKER_COLOUR);
+    for enemy in frame.enemies.iter() {
+        spawn_marker(&mut commands, enemy, ENEMY_MARKER_COLOUR);
+    }
+    let moment = if review.index + 1 == frame_count {
+        "Death".to_string()
+    } else {
+        format!("Turn {}", frame.turn)
+    };
+    let text = format!(
+        "{} ({}/{})\n{:?}/{:?} to step through, {:?} to continue",
+        moment,
+        review.index + 1,
+        frame_count,
+        key_bindings.move_left,
+        key_bindings.move_right,
+        key_bindings.confirm
+    );
+    if review.is_added() {
+        commands
+            .spawn_bundle(
+                TextBundle::from_section(text, ui_font.text_style(30.0))
+                    .with_text_alignment(TextAlignment::CENTER)
+                    .with_style(Style {
+                        size: Size::new(Val::Percent(100.0), Val::Auto),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            top: Val::Percent(15.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+            )
+            .insert(TurnReviewLabel)
+            .insert(TurnReviewOnly);
+    } else {
+        for mut label in label_query.iter_mut() {
+            label.sections[0].value = text.clone();
+        }
+    }
+}
+
+fn turn_review_cleanup(mut commands: Commands, mut turn_history: ResMut<TurnHistory>) {
+    turn_history.0.clear();
+    commands.remove_resource::<TurnReview>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TurnFrame, TurnHistory, TurnReview, REVIEW_TURNS};
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    #[test]
+    fn test_history_keeps_latest_turns() {
+        let mut history = TurnHistory::default();
+        for turn in 0..REVIEW_TURNS + 3 {
+            history.record(TurnFrame {
+                turn,
+                player: TilePos { x: 0, y: 0 },
+                enemies: vec![],
+            });
+        }
+        assert_eq!(REVIEW_TURNS, history.0.len());
+        assert_eq!(Some(3), history.0.front().map(|frame| frame.turn));
+    }
+
+    #[test]
+    fn test_review_step_stays_in_history() {
+        let mut review = TurnReview { index: 2 };
+        review.step(1, 4);
+        assert_eq!(3, review.index);
+        review.step(1, 4);
+        assert_eq!(3, review.index);
+        review.step(-5, 4);
+        assert_eq!(0, review.index);
+    }
+}
@@ -3,17 +3,38 @@ use code_location::code_location;
 use log::info;
 use num::Integer;
 
-use crate::asset_handling::ImageAssetStore;
-use crate::game::components::{Health, Player, PowerCharges};
-use crate::game::turn::{GlobalLevelCounter, GlobalTurnCounter};
-use crate::game::ui::ui_components::{HealthCounter, PowerChargeCounter};
+use crate::asset_handling::asset::{AudioAsset, ImageAsset};
+use crate::asset_handling::{AudioAssetStore, ImageAssetStore};
+use crate::game::balance::BalanceConfig;
+use crate::game::components::{GameCamera, Health, Inventory, Player, PowerCharges};
+use crate::game::end_game::VortexSpawnStatus;
+use crate::game::enemy::{Barnacle, Enemy};
+use crate::game::events::InfoEvent;
+use crate::game::game::{cursor_world_position, RunMode, SnailsCollectedThisRun};
+use crate::game::key_bindings::KeyBindings;
+use crate::game::objective::LevelObjective;
+use crate::game::relax_mode::RelaxMode;
+use crate::game::run_timer::{format_run_time, RunTimer};
+use crate::game::tilemap::{HasTileType, TilePosExt, TileStorageQuery};
+use crate::game::timed_removal::TimedDespawn;
+use crate::game::turn::{GamePhase, GlobalLevelCounter, GlobalTurnCounter};
+use crate::game::ui::ui_components::{HealthCounter, InventoryStrip, PowerChargeCounter};
 use crate::helpers::cleanup::recursive_cleanup;
 use crate::helpers::error_handling::ResultOkLog;
+use crate::map_gen::cell_map::CellMap;
 use crate::menu_core::helpers::RectExt;
-use crate::menu_core::menu_core::text::standard_centred_text;
+use crate::menu_core::menu_core::text::{
+    standard_centred_text, standard_centred_text_custom, UiFont,
+};
 use crate::profiles::profiles::LoadedUserProfile;
+use crate::profiles::settings::{Settings, TurnCounterDisplay};
 use bevy::prelude::JustifyContent;
+use bevy::sprite::Anchor;
+use bevy_ecs_tilemap::map::TilemapSize;
+use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_kira_audio::Audio;
 use bevy_ui_nodes::HeightOrWidth;
+use std::time::Duration;
 
 #[derive(Debug, Component)]
 pub struct GameUiOnly;
@@ -32,21 +53,38 @@ impl Plugin for GameUiPlugin {
             .add_system_set(
                 SystemSet::on_update(crate::CoreState::GameLevel)
                     .with_system(ui_player_health_system)
+                    .with_system(ui_low_health_vignette_system)
+                    .with_system(player_health_bar_system)
                     .with_system(ui_player_power_system)
-                    .with_system(ui_turn_counter_system),
+                    .with_system(ui_low_power_warning_system)
+                    .with_system(ui_player_inventory_system)
+                    .with_system(ui_toast_system)
+                    .with_system(ui_turn_counter_system)
+                    .with_system(ui_turn_cue_system)
+                    .with_system(ui_run_timer_system)
+                    .with_system(ui_objective_system)
+                    .with_system(ui_shells_system)
+                    .with_system(ui_phase_indicator_system)
+                    .with_system(turn_counter_display_toggle_system)
+                    .with_system(tile_hover_label_system),
             );
     }
 }
 
 fn ui_setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    ui_font: Res<UiFont>,
     image_assets: Res<ImageAssetStore>,
     loaded_profile: Res<LoadedUserProfile>,
+    run_timer: Res<RunTimer>,
+    run_mode: Res<RunMode>,
+    global_level_counter: Res<GlobalLevelCounter>,
 ) {
-    let font: Handle<Font> = asset_server.load("fonts/bigfish/Bigfish.ttf");
     let banner_height = Val::Px((ui_components::ICON_HEIGHT * 2.0) + 4.0);
     let mut root_node = None;
+    let mut run_timer_text = None;
+    let mut objective_text = None;
+    let mut shells_text = None;
     commands
         .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
         .insert(GameUiOnly {})
@@ -65,6 +103,19 @@ fn ui_setup(
                     ..Default::default()
                 })
                 .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(PHASE_INDICATOR_WIDTH),
+                                    Val::Percent(100.0),
+                                ),
+                                ..Default::default()
+                            },
+                            color: UiColor(phase_indicator_colour(&GamePhase::PlayerMovement)),
+                            ..Default::default()
+                        })
+                        .insert(PhaseIndicator);
                     parent
                         .spawn_bundle({
                             use bevy_ui_nodes::*;
@@ -88,7 +139,18 @@ fn ui_setup(
                                 loaded_profile.user_profile.max_health(),
                             );
                         });
-                    ui_components::turn_counter(parent, font.clone(), &banner_height);
+                    ui_components::turn_counter(parent, &ui_font, &banner_height);
+                    ui_components::inventory_strip(parent);
+                    shells_text = Some(
+                        standard_centred_text_custom(
+                            parent,
+                            String::new(),
+                            &ui_font,
+                            30.0,
+                            Color::BLACK,
+                        )
+                        .text,
+                    );
                 });
 
             // Central Panel
@@ -116,10 +178,152 @@ fn ui_setup(
                     ..Default::default()
                 })
                 .with_children(|parent| {
-                    standard_centred_text(parent, "Top Bar!".to_string(), font);
+                    run_timer_text = Some(
+                        standard_centred_text(parent, format_run_time(run_timer.elapsed), &ui_font)
+                            .text,
+                    );
+                    objective_text =
+                        Some(standard_centred_text(parent, String::new(), &ui_font).text);
+                    // Fixed for the level, the UI is rebuilt for each one
+                    if *run_mode == RunMode::Endless {
+                        standard_centred_text(
+                            parent,
+                            format!("Streak: {}", global_level_counter.levels_cleared()),
+                            &ui_font,
+                        );
+                    }
                 });
         });
     commands.insert_resource(GameOverlayUiRootNode(root_node.unwrap()));
+    commands
+        .entity(run_timer_text.unwrap())
+        .insert(RunTimerText);
+    commands
+        .entity(objective_text.unwrap())
+        .insert(ObjectiveText);
+    commands.entity(shells_text.unwrap()).insert(ShellsText);
+
+    // Spawned last so it draws over the rest of the UI, starts fully transparent
+    commands
+        .spawn_bundle(ImageBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            image: UiImage(image_assets.get(&ImageAsset::UiLowHealthVignette)),
+            color: UiColor(LOW_HEALTH_VIGNETTE_COLOUR),
+            ..Default::default()
+        })
+        .insert(LowHealthVignette)
+        .insert(GameUiOnly {});
+}
+
+/// Shows the [RunTimer] in the top bar
+#[derive(Component)]
+struct RunTimerText;
+
+fn ui_run_timer_system(
+    run_timer: Res<RunTimer>,
+    mut ui_query: Query<&mut Text, With<RunTimerText>>,
+) {
+    if run_timer.is_changed() {
+        for mut text in ui_query.iter_mut() {
+            text.sections[0].value = format_run_time(run_timer.elapsed);
+        }
+    }
+}
+
+/// Shows the level's objective and progress in the top bar, empty for levels without one
+#[derive(Component)]
+struct ObjectiveText;
+
+fn ui_objective_system(
+    level_objective: Res<LevelObjective>,
+    mut ui_query: Query<(&mut Text, ChangeTrackers<ObjectiveText>)>,
+) {
+    for (mut text, change_tracker) in ui_query.iter_mut() {
+        // The text is respawned with each level, so also fill it in whenever it's new
+        if level_objective.is_changed() || change_tracker.is_added() {
+            text.sections[0].value = level_objective.progress_text().unwrap_or_default();
+        }
+    }
+}
+
+/// Shows the profile's banked shells against the run's, which are lost on dying, in the bottom
+/// bar
+#[derive(Component)]
+struct ShellsText;
+
+fn ui_shells_system(
+    loaded_profile: Res<LoadedUserProfile>,
+    snails_collected_this_run: Res<SnailsCollectedThisRun>,
+    settings: Res<Settings>,
+    relax_mode: Res<RelaxMode>,
+    mut ui_query: Query<(&mut Text, ChangeTrackers<ShellsText>)>,
+) {
+    let changed = loaded_profile.is_changed()
+        || snails_collected_this_run.is_changed()
+        || settings.is_changed()
+        || relax_mode.is_changed();
+    for (mut text, change_tracker) in ui_query.iter_mut() {
+        if changed || change_tracker.is_added() {
+            let collected = snails_collected_this_run.0;
+            let at_risk = collected - relax_mode.shells_banked(&settings, collected, true);
+            text.sections[0].value = format!(
+                "Shells: {} banked, {} at risk",
+                loaded_profile.user_profile.snail_shells, at_risk
+            );
+        }
+    }
+}
+
+/// Strip at the edge of the bottom bar showing whose turn it is, see [phase_indicator_colour]
+#[derive(Component)]
+struct PhaseIndicator;
+
+const PHASE_INDICATOR_WIDTH: f32 = 8.0;
+
+/// Green while the player's input is taken, red while everything else resolves and input is
+/// ignored
+fn phase_indicator_colour(phase: &GamePhase) -> Color {
+    match phase {
+        GamePhase::PlayerMovement => Color::rgba(0.2, 0.8, 0.2, 0.8),
+        GamePhase::PlayerPowerEffect
+        | GamePhase::PreEnemyMovement
+        | GamePhase::EnemyPowerEffect
+        | GamePhase::EnemyMovement => Color::rgba(0.8, 0.2, 0.2, 0.8),
+    }
+}
+
+fn ui_phase_indicator_system(
+    global_turn_counter: Res<GlobalTurnCounter>,
+    mut indicator_query: Query<&mut UiColor, With<PhaseIndicator>>,
+) {
+    if global_turn_counter.is_changed() {
+        let colour = phase_indicator_colour(&global_turn_counter.current_phase);
+        for mut indicator_colour in indicator_query.iter_mut() {
+            indicator_colour.0 = colour;
+        }
+    }
+}
+
+/// Red screen-edge overlay, more opaque the lower the player's health
+#[derive(Component)]
+struct LowHealthVignette;
+
+/// Alpha is driven by health, see [low_health_vignette_alpha]
+const LOW_HEALTH_VIGNETTE_COLOUR: Color = Color::rgba(0.8, 0.0, 0.0, 0.0);
+/// Below this fraction of max health the vignette starts to show
+const LOW_HEALTH_VIGNETTE_THRESHOLD: f32 = 0.6;
+const LOW_HEALTH_VIGNETTE_MAX_ALPHA: f32 = 0.8;
+
+fn low_health_vignette_alpha(hp: usize, max_hp: usize) -> f32 {
+    let health_fraction = hp as f32 / max_hp.max(1) as f32;
+    let intensity = ((LOW_HEALTH_VIGNETTE_THRESHOLD - health_fraction)
+        / LOW_HEALTH_VIGNETTE_THRESHOLD)
+        .clamp(0.0, 1.0);
+    intensity * LOW_HEALTH_VIGNETTE_MAX_ALPHA
 }
 
 fn ui_player_health_system(
@@ -142,12 +346,137 @@ fn ui_player_health_system(
     }
 }
 
+fn ui_low_health_vignette_system(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut vignette_query: Query<&mut UiColor, With<LowHealthVignette>>,
+    loaded_profile: Res<LoadedUserProfile>,
+) {
+    if let Ok(health) = player_query.get_single() {
+        let alpha = low_health_vignette_alpha(health.hp, loaded_profile.user_profile.max_health());
+        for mut colour in vignette_query.iter_mut() {
+            colour.0.set_a(alpha);
+        }
+    }
+}
+
+/// World space health bar under the player, see [Settings::player_health_bar]. A child of the
+/// player so it moves with them
+#[derive(Component)]
+struct PlayerHealthBar;
+
+const HEALTH_BAR_WIDTH: f32 = 40.0;
+const HEALTH_BAR_HEIGHT: f32 = 5.0;
+
+/// Respawns the bar whenever the player's health or the setting changes, or if it's missing, as
+/// when the player is respawned for a new level
+fn player_health_bar_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    loaded_profile: Res<LoadedUserProfile>,
+    player_query: Query<(Entity, &Health, ChangeTrackers<Health>), With<Player>>,
+    bar_query: Query<Entity, With<PlayerHealthBar>>,
+) {
+    if !settings.player_health_bar {
+        if settings.is_changed() {
+            for entity in bar_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        return;
+    }
+    let (player, health, health_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    if !(bar_query.is_empty() || health_tracker.is_changed() || settings.is_changed()) {
+        return;
+    }
+    for entity in bar_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if health.hp == 0 {
+        return;
+    }
+    let max_hp = loaded_profile.user_profile.max_health().max(health.hp);
+    let fill_width = HEALTH_BAR_WIDTH * health.hp as f32 / max_hp as f32;
+    commands.entity(player).with_children(|parent| {
+        // Relative to the player, centred just below the bottom edge of their tile
+        parent
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                0.0, -30.0, 1.0,
+            )))
+            .insert(PlayerHealthBar)
+            .with_children(|parent| {
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(0.1, 0.1, 0.1, 0.8),
+                        custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+                // Anchored at its left end so it empties towards the left
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.2, 0.8, 0.2),
+                        custom_size: Some(Vec2::new(fill_width, HEALTH_BAR_HEIGHT)),
+                        anchor: Anchor::CenterLeft,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(-HEALTH_BAR_WIDTH / 2.0, 0.0, 0.1),
+                    ..Default::default()
+                });
+            });
+    });
+}
+
+/// On the power counter while it flashes after trying to use the power with no charges
+#[derive(Component)]
+struct PowerUnavailableFlash(Timer);
+
+impl Default for PowerUnavailableFlash {
+    fn default() -> Self {
+        Self(Timer::from_seconds(POWER_UNAVAILABLE_FLASH_SECS, false))
+    }
+}
+
+const POWER_UNAVAILABLE_FLASH_COLOUR: Color = Color::rgba(1.0, 0.1, 0.1, 0.9);
+const POWER_UNAVAILABLE_FLASH_SECS: f32 = 0.3;
+
 fn ui_player_power_system(
     mut commands: Commands,
     player_query: Query<&PowerCharges, (With<Player>, Changed<PowerCharges>)>,
     mut ui_query: Query<(Entity, &PowerChargeCounter)>,
     image_assets: Res<ImageAssetStore>,
+    mut info_event_reader: EventReader<InfoEvent>,
+    time: Res<Time>,
+    mut flash_query: Query<
+        (Entity, &mut UiColor, Option<&mut PowerUnavailableFlash>),
+        With<PowerChargeCounter>,
+    >,
 ) {
+    let unavailable = info_event_reader
+        .iter()
+        .any(|event| matches!(event, InfoEvent::PowerUnavailable));
+    for (entity, mut colour, maybe_flash) in flash_query.iter_mut() {
+        if unavailable {
+            // Restarts any flash already going
+            commands
+                .entity(entity)
+                .insert(PowerUnavailableFlash::default());
+            colour.0 = POWER_UNAVAILABLE_FLASH_COLOUR;
+        } else if let Some(mut flash) = maybe_flash {
+            flash.0.tick(time.delta());
+            colour.0 = POWER_UNAVAILABLE_FLASH_COLOUR;
+            colour
+                .0
+                .set_a(POWER_UNAVAILABLE_FLASH_COLOUR.a() * flash.0.percent_left());
+            // Hands the colour back to [ui_low_power_warning_system]
+            if flash.0.finished() {
+                commands.entity(entity).remove::<PowerUnavailableFlash>();
+            }
+        }
+    }
     if let Ok(charges) = player_query.get_single() {
         info!("Setting power charge ui to: {}", charges.charges);
         if let Some((entity, counter)) = ui_query.get_single().ok_log(code_location!()) {
@@ -162,26 +491,288 @@ fn ui_player_power_system(
     }
 }
 
+/// Tint pulsed behind the power counter while out of charges
+const LOW_POWER_WARNING_COLOUR: Color = Color::rgba(1.0, 0.2, 0.2, 0.0);
+const LOW_POWER_WARNING_MAX_ALPHA: f32 = 0.6;
+/// Full pulses per second
+const LOW_POWER_WARNING_PULSE_RATE: f32 = 1.5;
+
+fn ui_low_power_warning_system(
+    time: Res<Time>,
+    player_query: Query<&PowerCharges, With<Player>>,
+    mut counter_query: Query<
+        &mut UiColor,
+        (With<PowerChargeCounter>, Without<PowerUnavailableFlash>),
+    >,
+) {
+    let out_of_power = player_query
+        .get_single()
+        .map_or(false, |charges| charges.charges == 0);
+    let alpha = if out_of_power {
+        let phase = time.seconds_since_startup() as f32 * LOW_POWER_WARNING_PULSE_RATE;
+        (0.5 - 0.5 * (phase * std::f32::consts::TAU).cos()) * LOW_POWER_WARNING_MAX_ALPHA
+    } else {
+        0.0
+    };
+    for mut colour in counter_query.iter_mut() {
+        // Only written when it differs, so the UI isn't marked changed every frame
+        if colour.0.a() != alpha {
+            colour.0 = LOW_POWER_WARNING_COLOUR;
+            colour.0.set_a(alpha);
+        }
+    }
+}
+
+fn ui_player_inventory_system(
+    mut commands: Commands,
+    player_query: Query<&Inventory, (With<Player>, Changed<Inventory>)>,
+    ui_query: Query<Entity, With<InventoryStrip>>,
+    image_assets: Res<ImageAssetStore>,
+) {
+    if let Ok(inventory) = player_query.get_single() {
+        info!("Setting inventory ui to: {:?}", inventory);
+        if let Some(entity) = ui_query.get_single().ok_log(code_location!()) {
+            ui_components::inventory_strip_set(
+                &mut commands,
+                entity,
+                &image_assets,
+                inventory.items(),
+            );
+        }
+    }
+}
+
+/// How long the "Second Chance!" message stays up after a starfish is used
+const SECOND_CHANCE_TOAST_DURATION: Duration = Duration::from_millis(1500);
+const LAST_STAND_TOAST_DURATION: Duration = Duration::from_millis(1500);
+/// How long the message stays up once the level's objective is met
+const OBJECTIVE_COMPLETE_TOAST_DURATION: Duration = Duration::from_millis(1500);
+/// How long the warning stays up when the level had to fall back to the safe map
+const FALLBACK_MAP_TOAST_DURATION: Duration = Duration::from_millis(4000);
+
+fn ui_toast_system(
+    mut commands: Commands,
+    mut info_event_reader: EventReader<InfoEvent>,
+    ui_font: Res<UiFont>,
+) {
+    for event in info_event_reader.iter() {
+        let (text, font_size, duration) = match event {
+            InfoEvent::SecondChanceUsed => ("Second Chance!", 60.0, SECOND_CHANCE_TOAST_DURATION),
+            InfoEvent::LastStandUsed => ("Last Stand!", 60.0, LAST_STAND_TOAST_DURATION),
+            InfoEvent::ObjectiveCompleted => (
+                "Objective Complete!",
+                60.0,
+                OBJECTIVE_COMPLETE_TOAST_DURATION,
+            ),
+            InfoEvent::FallbackMapUsed => (
+                "Couldn't generate a level, using a small safe map instead",
+                30.0,
+                FALLBACK_MAP_TOAST_DURATION,
+            ),
+            _ => continue,
+        };
+        // A lone text node rather than a child of the central panel, so the timed despawn
+        // doesn't leave anything dangling
+        commands
+            .spawn_bundle(
+                TextBundle::from_section(text, ui_font.text_style(font_size))
+                    .with_text_alignment(TextAlignment::CENTER)
+                    .with_style(Style {
+                        size: Size::new(Val::Percent(100.0), Val::Auto),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            top: Val::Percent(25.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+            )
+            .insert(TimedDespawn::new(duration))
+            .insert(GameUiOnly {});
+    }
+}
+
+/// Follows the cursor naming the tile under it, see [Settings::tile_hover_label]
+#[derive(Component)]
+struct TileHoverLabel;
+
+/// Pixels right of the cursor the [TileHoverLabel] sits, to keep it out from under the pointer
+const TILE_HOVER_LABEL_OFFSET: f32 = 16.0;
+
+/// e.g. "(12, 7)", with the tile's cell map cost and type on debug builds
+fn tile_hover_text(
+    tile_pos: &TilePos,
+    cost: Option<i32>,
+    tile_type: Option<&HasTileType>,
+) -> String {
+    let coords = format!("({}, {})", tile_pos.x, tile_pos.y);
+    if !cfg!(debug_assertions) {
+        return coords;
+    }
+    let cost = cost.map_or_else(|| "-".to_string(), |cost| cost.to_string());
+    match tile_type {
+        Some(HasTileType(tile_type)) => format!("{} cost: {} {:?}", coords, cost, tile_type),
+        None => format!("{} cost: {}", coords, cost),
+    }
+}
+
+fn tile_hover_label_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    ui_font: Res<UiFont>,
+    windows: Res<Windows>,
+    camera_query: Query<&Transform, With<GameCamera>>,
+    tile_storage_query: TileStorageQuery,
+    tilemap_size_query: Query<&TilemapSize>,
+    tile_type_query: Query<&HasTileType>,
+    cell_map: Option<Res<CellMap<i32>>>,
+    mut label_query: Query<(Entity, &mut Text, &mut Style), With<TileHoverLabel>>,
+) {
+    let (mut text, mut style) = match (settings.tile_hover_label, label_query.get_single_mut()) {
+        (true, Ok((_, text, style))) => (text, style),
+        (true, Err(_)) => {
+            commands
+                .spawn_bundle(
+                    TextBundle::from_section("", ui_font.text_style(20.0)).with_style(Style {
+                        position_type: PositionType::Absolute,
+                        ..Default::default()
+                    }),
+                )
+                .insert(TileHoverLabel)
+                .insert(GameUiOnly {});
+            return;
+        }
+        (false, Ok((entity, _, _))) => {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        (false, Err(_)) => return,
+    };
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let hovered = window.cursor_position().zip(
+        camera_query
+            .get_single()
+            .ok()
+            .and_then(|camera_transform| cursor_world_position(window, camera_transform)),
+    );
+    // Anything off the map, including left of or below it, shows nothing
+    let on_map = hovered.and_then(|(cursor, world_position)| {
+        if world_position.x < 0.0 || world_position.y < 0.0 {
+            return None;
+        }
+        let tile_pos = TilePos::from_world_pos(world_position.x, world_position.y);
+        let tilemap_size = tilemap_size_query.get_single().ok()?;
+        (tile_pos.x < tilemap_size.x && tile_pos.y < tilemap_size.y).then(|| (cursor, tile_pos))
+    });
+    let (cursor, tile_pos) = match on_map {
+        Some(hovered) => hovered,
+        None => {
+            text.sections[0].value.clear();
+            return;
+        }
+    };
+
+    let tile_type = tile_storage_query
+        .get_single()
+        .ok()
+        .and_then(|tile_storage| tile_storage.get(&tile_pos))
+        .and_then(|tile_entity| tile_type_query.get(tile_entity).ok());
+    let cost = cell_map.and_then(|cell_map| cell_map.0.get(&tile_pos.as_i32s()).copied());
+    text.sections[0].value = tile_hover_text(&tile_pos, cost, tile_type);
+    style.position = UiRect {
+        left: Val::Px(cursor.x + TILE_HOVER_LABEL_OFFSET),
+        bottom: Val::Px(cursor.y),
+        ..Default::default()
+    };
+}
+
+/// Rough number of turns it takes to hunt down an enemy, used to suggest clearing enemies over
+/// waiting out the turns when that looks quicker
+const ESTIMATED_TURNS_PER_KILL: usize = 3;
+
+fn vortex_countdown_text(status: VortexSpawnStatus) -> String {
+    match status {
+        VortexSpawnStatus::Ready => "Vortex open".to_string(),
+        VortexSpawnStatus::Pending {
+            turns_remaining,
+            enemies_to_clear,
+        } => {
+            if enemies_to_clear * ESTIMATED_TURNS_PER_KILL < turns_remaining {
+                format!("Clear {} more enemies", enemies_to_clear)
+            } else {
+                format!("Vortex in ~{} turns", turns_remaining)
+            }
+        }
+    }
+}
+
+/// Saved straight away, as nothing else saves [Settings] outside the settings menu
+fn turn_counter_display_toggle_system(
+    input: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut settings: ResMut<Settings>,
+) {
+    if input.just_pressed(key_bindings.toggle_turn_counter) {
+        settings.turn_counter_display = settings.turn_counter_display.next();
+        settings.save();
+    }
+}
+
+/// Sets the turn counter text on each new turn, and whenever anything the vortex countdown is
+/// worked out from changes: the enemy count, as enemies die mid turn, [RelaxMode] or the level's
+/// objective, which holds the vortex back until it's met
 fn ui_turn_counter_system(
     global_turn_counter: Res<GlobalTurnCounter>,
     global_level_counter: Res<GlobalLevelCounter>,
+    settings: Res<Settings>,
+    // Matches the count the vortex spawns from, see [super::game]
+    enemy_query: Query<Entity, (With<Enemy>, Without<Barnacle>)>,
+    balance: Res<BalanceConfig>,
+    relax_mode: Res<RelaxMode>,
+    level_objective: Res<LevelObjective>,
     mut last_set_turn: Local<usize>,
+    mut last_enemy_count: Local<usize>,
     mut ui_query: Query<&mut Text, With<ui_components::TurnCounter>>,
     mut double_set: Local<usize>,
 ) {
-    if (*last_set_turn != global_turn_counter.turn_count) || *double_set > 0 {
+    let enemy_count = enemy_query.iter().count();
+    let turn_counter_display = settings.turn_counter_display;
+    let countdown_changed = turn_counter_display == TurnCounterDisplay::VortexCountdown
+        && (*last_enemy_count != enemy_count
+            || relax_mode.is_changed()
+            || level_objective.is_changed());
+    if (*last_set_turn != global_turn_counter.turn_count)
+        || *double_set > 0
+        || settings.is_changed()
+        || countdown_changed
+    {
         info!(
             "Setting turn counter ui to: {}",
             global_turn_counter.turn_count
         );
+        let counter = match turn_counter_display {
+            TurnCounterDisplay::Turns => format!("Turn: {}", global_turn_counter.turn_count),
+            TurnCounterDisplay::VortexCountdown => match level_objective.objective {
+                Some(objective) if !level_objective.completed => {
+                    format!("Vortex after: {}", objective.description())
+                }
+                Some(_) => "Vortex open".to_string(),
+                None => vortex_countdown_text(super::end_game::vortex_spawn_status(
+                    global_turn_counter.turn_count,
+                    enemy_count,
+                    &relax_mode.vortex_balance(&balance.vortex),
+                )),
+            },
+        };
         for mut text in ui_query.iter_mut() {
-            text.sections[0].value = format!(
-                "{} - Turn: {}",
-                global_level_counter.level(),
-                global_turn_counter.turn_count
-            );
+            text.sections[0].value = format!("{} - {}", global_level_counter.level(), counter);
         }
         *last_set_turn = global_turn_counter.turn_count;
+        *last_enemy_count = enemy_count;
         *double_set = match *double_set {
             0_usize => 1,
             _ => 0,
@@ -189,12 +780,66 @@ fn ui_turn_counter_system(
     }
 }
 
+/// Seconds the turn counter takes to swell and settle back as a new turn begins
+const TURN_PULSE_DURATION: f32 = 0.3;
+/// How much bigger the turn counter is at the height of the pulse
+const TURN_PULSE_GROWTH: f32 = 0.2;
+
+/// Scale of the turn counter [elapsed] seconds into its pulse, back to 1.0 once it's over
+fn turn_pulse_scale(elapsed: f32) -> f32 {
+    let progress = (elapsed / TURN_PULSE_DURATION).clamp(0.0, 1.0);
+    1.0 + (progress * std::f32::consts::PI).sin() * TURN_PULSE_GROWTH
+}
+
+/// Marks each new turn with a tick, if [Settings::turn_tick_sound] is on, and a pulse of the turn
+/// counter, unless [Settings::reduce_motion] is on. The start of the level isn't marked
+fn ui_turn_cue_system(
+    time: Res<Time>,
+    global_turn_counter: Res<GlobalTurnCounter>,
+    settings: Res<Settings>,
+    audio: Res<Audio>,
+    audio_asset_store: Res<AudioAssetStore>,
+    mut last_turn: Local<usize>,
+    mut pulse_elapsed: Local<Option<f32>>,
+    mut ui_query: Query<&mut Transform, With<ui_components::TurnCounter>>,
+) {
+    if *last_turn != global_turn_counter.turn_count {
+        *last_turn = global_turn_counter.turn_count;
+        if global_turn_counter.turn_count > 1 {
+            if settings.turn_tick_sound {
+                audio.play(audio_asset_store.get(&AudioAsset::TurnTick));
+            }
+            if !settings.reduce_motion {
+                *pulse_elapsed = Some(0.0);
+            }
+        }
+    }
+
+    if let Some(elapsed) = pulse_elapsed.as_mut() {
+        *elapsed += time.delta_seconds();
+        // Reducing motion part way through snaps the counter straight back
+        let scale = if !settings.reduce_motion {
+            turn_pulse_scale(*elapsed)
+        } else {
+            1.0
+        };
+        for mut transform in ui_query.iter_mut() {
+            transform.scale = Vec3::splat(scale);
+        }
+        if *elapsed >= TURN_PULSE_DURATION || settings.reduce_motion {
+            *pulse_elapsed = None;
+        }
+    }
+}
+
 mod ui_components {
     use bevy::prelude::*;
 
     use crate::asset_handling::asset::ImageAsset;
     use crate::asset_handling::ImageAssetStore;
+    use crate::game::components::InventoryItem;
     use crate::menu_core::helpers::RectExt;
+    use crate::menu_core::menu_core::text::UiFont;
     use bevy::prelude::{FlexDirection, JustifyContent};
     use bevy::ui::UiImage;
 
@@ -209,6 +854,9 @@ mod ui_components {
     #[derive(Debug, Component)]
     pub struct TurnCounter;
 
+    #[derive(Debug, Component)]
+    pub struct InventoryStrip;
+
     pub fn health_counter(parent: &mut ChildBuilder, max: usize) {
         use bevy_ui_nodes::*;
         println!("HEALTH COUNTER");
@@ -290,6 +938,35 @@ mod ui_components {
         });
     }
 
+    pub fn inventory_strip(parent: &mut ChildBuilder) {
+        use bevy_ui_nodes::*;
+        // Node, left of the turn counter, with item icons stacking right to left
+        parent
+            .spawn_bundle(new(vec![
+                Property::Justify(JustifyContent::FlexStart),
+                Property::Height(Val::Percent(100.0)),
+                Property::Width(Val::Auto),
+                Property::FlexGrow(1.0),
+                Property::PaddingAll(Val::Px(10.0)),
+                Property::Direction(FlexDirection::RowReverse),
+            ]))
+            .insert(InventoryStrip);
+    }
+
+    pub fn inventory_strip_set(
+        commands: &mut Commands,
+        root: Entity,
+        image_assets: &ImageAssetStore,
+        items: &[InventoryItem],
+    ) {
+        commands.entity(root).despawn_descendants();
+        commands.entity(root).with_children(|parent| {
+            for item in items.iter() {
+                parent.spawn_bundle(image_node(image_assets, &item.to_image_asset()));
+            }
+        });
+    }
+
     fn image_node(image_assets: &ImageAssetStore, asset: &ImageAsset) -> NodeBundle {
         use bevy_ui_nodes::*;
         let properties = vec![
@@ -304,7 +981,7 @@ mod ui_components {
 
     pub fn counter_text<I: Component>(
         parent: &mut ChildBuilder,
-        font: Handle<Font>,
+        ui_font: &UiFont,
         banner_height: &Val,
         identifier: I,
     ) {
@@ -312,7 +989,7 @@ mod ui_components {
         parent
             .spawn_bundle(NodeBundle {
                 style: Style {
-                    size: Size::new(Val::Px(200.0), *banner_height),
+                    size: Size::new(Val::Px(ui_font.scaled(200.0)), *banner_height),
                     margin: UiRect::new_2(Val::Px(0.0), Val::Px(10.0)),
                     justify_content: JustifyContent::FlexStart,
                     flex_direction: FlexDirection::Column,
@@ -326,7 +1003,7 @@ mod ui_components {
                 parent
                     .spawn_bundle(NodeBundle {
                         style: Style {
-                            size: Size::new(Val::Px(150.0), *banner_height),
+                            size: Size::new(Val::Px(ui_font.scaled(150.0)), *banner_height),
                             margin: UiRect::new_2(Val::Px(0.0), Val::Px(10.0)),
                             justify_content: JustifyContent::Center,
                             flex_direction: FlexDirection::ColumnReverse,
@@ -343,9 +1020,8 @@ mod ui_components {
                                 text: Text::from_section(
                                     "A",
                                     TextStyle {
-                                        font,
-                                        font_size: 35.0,
                                         color: Color::rgb(0.0, 0.0, 0.0),
+                                        ..ui_font.text_style(35.0)
                                     },
                                 )
                                 .with_alignment(TextAlignment {
@@ -364,12 +1040,12 @@ mod ui_components {
             });
     }
 
-    pub fn turn_counter(parent: &mut ChildBuilder, font: Handle<Font>, banner_height: &Val) {
+    pub fn turn_counter(parent: &mut ChildBuilder, ui_font: &UiFont, banner_height: &Val) {
         println!("TURN COUNTER");
         parent
             .spawn_bundle(NodeBundle {
                 style: Style {
-                    size: Size::new(Val::Px(200.0), *banner_height),
+                    size: Size::new(Val::Px(ui_font.scaled(200.0)), *banner_height),
                     margin: UiRect::new_2(Val::Px(0.0), Val::Px(10.0)),
                     justify_content: JustifyContent::FlexStart,
                     flex_direction: FlexDirection::Column,
@@ -383,7 +1059,7 @@ mod ui_components {
                 parent
                     .spawn_bundle(NodeBundle {
                         style: Style {
-                            size: Size::new(Val::Px(150.0), *banner_height),
+                            size: Size::new(Val::Px(ui_font.scaled(150.0)), *banner_height),
                             margin: UiRect::new_2(Val::Px(0.0), Val::Px(10.0)),
                             justify_content: JustifyContent::Center,
                             flex_direction: FlexDirection::ColumnReverse,
@@ -400,9 +1076,8 @@ mod ui_components {
                                 text: Text::from_section(
                                     "AAA",
                                     TextStyle {
-                                        font,
-                                        font_size: 35.0,
                                         color: Color::rgb(0.0, 0.0, 0.0),
+                                        ..ui_font.text_style(35.0)
                                     },
                                 )
                                 .with_alignment(TextAlignment {
@@ -1,9 +1,14 @@
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
+use crate::game::key_bindings::KeyBindings;
 use crate::game::ui::GameOverlayUiRootNode;
 use crate::helpers::builders::WithSelf;
-use crate::menu_core::menu_core::text::standard_centred_text;
+use crate::menu_core::menu_core::text::{
+    standard_centred_text, standard_centred_text_custom, UiFont,
+};
 use crate::menu_core::menu_core::ButtonComponent;
+use crate::profiles::profiles::LoadedUserProfile;
+use bevy::app::AppExit;
 use bevy::prelude::*;
 
 pub struct GameOverlayPlugin;
@@ -33,6 +38,8 @@ enum UiOverlayButton {
     Resume,
     Help,
     Back,
+    Quit,
+    ConfirmQuit,
 }
 impl ButtonComponent for UiOverlayButton {
     fn to_text(&self) -> &'static str {
@@ -41,6 +48,8 @@ impl ButtonComponent for UiOverlayButton {
             Self::Resume => "Resume",
             Self::Help => "Help",
             Self::Back => "Back",
+            Self::Quit => "Quit to Desktop",
+            Self::ConfirmQuit => "Quit",
         }
     }
 }
@@ -55,7 +64,10 @@ fn button_click_system(
     view_parent_node: Res<ViewParentNode>,
     current_view_query: Query<Entity, With<OverlayView>>,
     image_store: Res<ImageAssetStore>,
-    asset_server: Res<AssetServer>,
+    ui_font: Res<UiFont>,
+    key_bindings: Res<KeyBindings>,
+    loaded_profile: Res<LoadedUserProfile>,
+    mut app_exit_events: EventWriter<AppExit>,
 ) {
     for (interaction, button) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
@@ -76,7 +88,8 @@ fn button_click_system(
                         &mut commands,
                         &current_view_query,
                         &image_store,
-                        &asset_server,
+                        &ui_font,
+                        &key_bindings,
                     );
                 }
                 UiOverlayButton::Back => {
@@ -87,9 +100,25 @@ fn button_click_system(
                         &mut commands,
                         &current_view_query,
                         &image_store,
-                        &asset_server,
+                        &ui_font,
+                        &key_bindings,
                     );
                 }
+                UiOverlayButton::Quit => {
+                    info!("Quit pressed");
+                    change_view(
+                        &view_parent_node,
+                        View::ConfirmQuit,
+                        &mut commands,
+                        &current_view_query,
+                        &image_store,
+                        &ui_font,
+                        &key_bindings,
+                    );
+                }
+                UiOverlayButton::ConfirmQuit => {
+                    quit_to_desktop(&loaded_profile, &mut app_exit_events);
+                }
             }
         }
     }
@@ -98,6 +127,7 @@ fn button_click_system(
 enum View {
     Base,
     Help,
+    ConfirmQuit,
 }
 
 fn change_view(
@@ -106,33 +136,66 @@ fn change_view(
     commands: &mut Commands,
     current_view_query: &Query<Entity, With<OverlayView>>,
     image_store: &ImageAssetStore,
-    asset_server: &AssetServer,
+    ui_font: &UiFont,
+    key_bindings: &KeyBindings,
 ) {
     // Clear
     for entity in current_view_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
-
     // Add
     commands.entity(parent.0).with_children(|parent| {
         match view {
             View::Help => {
-                help_view(parent, font, image_store);
+                help_view(parent, ui_font, image_store, key_bindings);
             }
             View::Base => {
-                base_view(parent, font);
+                base_view(parent, ui_font);
+            }
+            View::ConfirmQuit => {
+                confirm_quit_view(parent, ui_font, key_bindings);
             }
         };
     });
 }
 
+/// Saves the profile first so nothing banked is lost, shells still at risk in the run are gone
+/// either way
+fn quit_to_desktop(loaded_profile: &LoadedUserProfile, app_exit_events: &mut EventWriter<AppExit>) {
+    info!("Quitting to desktop");
+    loaded_profile.save();
+    app_exit_events.send(AppExit);
+}
+
 fn input_watch_system(
     mut input: ResMut<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
     mut app_state: ResMut<State<crate::CoreState>>,
+    confirm_quit_query: Query<Entity, With<ConfirmQuitView>>,
+    loaded_profile: Res<LoadedUserProfile>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut commands: Commands,
+    view_parent_node: Res<ViewParentNode>,
+    current_view_query: Query<Entity, With<OverlayView>>,
+    image_store: Res<ImageAssetStore>,
+    ui_font: Res<UiFont>,
 ) {
-    if input.just_pressed(KeyCode::Escape) {
+    let confirming_quit = !confirm_quit_query.is_empty();
+    if confirming_quit && input.just_pressed(key_bindings.confirm) {
+        quit_to_desktop(&loaded_profile, &mut app_exit_events);
+    } else if !confirming_quit && input.just_pressed(key_bindings.quit) {
+        input.clear();
+        change_view(
+            &view_parent_node,
+            View::ConfirmQuit,
+            &mut commands,
+            &current_view_query,
+            &image_store,
+            &ui_font,
+            &key_bindings,
+        );
+    } else if input.just_pressed(key_bindings.pause) {
         println!("UI Overlay popping state");
         input.clear();
         app_state.pop().unwrap();
@@ -141,17 +204,16 @@ fn input_watch_system(
 
 fn menu_setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    ui_font: Res<UiFont>,
     ui_root: Res<GameOverlayUiRootNode>,
     mut input: ResMut<Input<KeyCode>>,
 ) {
     println!("UI Overlay");
 
-    // Need to clear input as we want to use ESC to toggle back and forth but without these it
-    // just gets stuck in a loop as "just_pressed(esc)" is always true
+    // Need to clear input as we want to use the pause key to toggle back and forth but without
+    // these it just gets stuck in a loop as "just_pressed(pause)" is always true
     input.clear();
 
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
     let mut parent_view = None;
     commands.entity(ui_root.0).with_children(|parent| {
         parent
@@ -169,7 +231,7 @@ fn menu_setup(
             .insert(GameOverlayOnly)
             .with_self(|node| parent_view = Some(node.id()))
             .with_children(|parent| {
-                base_view(parent, font.clone());
+                base_view(parent, &ui_font);
             });
     });
     commands.insert_resource(ViewParentNode(parent_view.unwrap()));
@@ -181,7 +243,7 @@ fn menu_setup(
 #[derive(Component)]
 struct OverlayView;
 
-fn base_view(parent: &mut ChildBuilder, font: Handle<Font>) {
+fn base_view(parent: &mut ChildBuilder, ui_font: &UiFont) {
     parent
         .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
         .with_children(|parent| {
@@ -192,26 +254,76 @@ fn base_view(parent: &mut ChildBuilder, font: Handle<Font>) {
                         UiOverlayButton::Abandon,
                         Size::new(Val::Px(200.0), Val::Px(65.0)),
                         parent,
-                        font.clone(),
+                        ui_font,
                     );
                     crate::menu_core::menu_core::make_button_custom_size(
                         UiOverlayButton::Resume,
                         Size::new(Val::Px(200.0), Val::Px(65.0)),
                         parent,
-                        font.clone(),
+                        ui_font,
                     );
                     crate::menu_core::menu_core::make_button(
                         UiOverlayButton::Help,
                         parent,
-                        font.clone(),
+                        ui_font,
+                    );
+                    crate::menu_core::menu_core::make_button_custom_size(
+                        UiOverlayButton::Quit,
+                        Size::new(Val::Px(300.0), Val::Px(65.0)),
+                        parent,
+                        ui_font,
                     );
                 });
-            standard_centred_text(parent, "Hello".to_string(), font);
+            standard_centred_text(parent, "Hello".to_string(), ui_font);
         })
         .insert(OverlayView);
 }
 
-fn help_view(parent: &mut ChildBuilder, font: Handle<Font>, image_store: &ImageAssetStore) {
+/// On the quit confirmation view, where Enter also confirms
+#[derive(Component)]
+struct ConfirmQuitView;
+
+fn confirm_quit_view(parent: &mut ChildBuilder, ui_font: &UiFont, key_bindings: &KeyBindings) {
+    parent
+        .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(bevy_ui_nodes::default_node::full_horizontal())
+                .with_children(|parent| {
+                    crate::menu_core::menu_core::make_button(
+                        UiOverlayButton::ConfirmQuit,
+                        parent,
+                        ui_font,
+                    );
+                    crate::menu_core::menu_core::make_button(
+                        UiOverlayButton::Back,
+                        parent,
+                        ui_font,
+                    );
+                });
+            standard_centred_text(
+                parent,
+                format!(
+                    "Quit to desktop? Shells not yet banked this run will be lost\n\
+                     Press {:?} to quit",
+                    key_bindings.confirm
+                ),
+                ui_font,
+            );
+        })
+        .insert(OverlayView)
+        .insert(ConfirmQuitView);
+}
+
+/// Font size for each line of the controls list in the help view
+const CONTROLS_FONT_SIZE: f32 = 24.0;
+
+fn help_view(
+    parent: &mut ChildBuilder,
+    ui_font: &UiFont,
+    image_store: &ImageAssetStore,
+    key_bindings: &KeyBindings,
+) {
     parent
         .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
         .with_children(|parent| {
@@ -229,13 +341,14 @@ fn help_view(parent: &mut ChildBuilder, font: Handle<Font>, image_store: &ImageA
                     crate::menu_core::menu_core::make_button(
                         UiOverlayButton::Back,
                         parent,
-                        font.clone(),
+                        ui_font,
                     );
                 });
 
             parent
-                .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
+                .spawn_bundle(bevy_ui_nodes::default_node::full_horizontal())
                 .with_children(|parent| {
+                    controls_list(parent, ui_font, key_bindings);
                     parent.spawn_bundle(bevy_ui_nodes::new(bevy_ui_nodes::defaults::full(
                         FlexDirection::Column,
                         Some(vec![
@@ -252,6 +365,23 @@ fn help_view(parent: &mut ChildBuilder, font: Handle<Font>, image_store: &ImageA
         .insert(OverlayView);
 }
 
+/// The current key bindings as a column of text, so the help always matches any remapping
+fn controls_list(parent: &mut ChildBuilder, ui_font: &UiFont, key_bindings: &KeyBindings) {
+    parent
+        .spawn_bundle(bevy_ui_nodes::default_node::full_vertical())
+        .with_children(|parent| {
+            for (action, key) in key_bindings.controls() {
+                standard_centred_text_custom(
+                    parent,
+                    format!("{}: {}", action, key),
+                    ui_font,
+                    CONTROLS_FONT_SIZE,
+                    Color::WHITE,
+                );
+            }
+        });
+}
+
 fn menu_cleanup(q: Query<Entity, With<GameOverlayOnly>>, mut commands: Commands) {
     commands.remove_resource::<ViewParentNode>();
     for entity in q.iter() {
@@ -0,0 +1,210 @@
+//! A cursor for picking tiles without a mouse. The right stick moves it, as does dragging a finger
+//! across a touch screen, and it snaps to the tile beneath it. Confirming sends the same
+//! [MouseClickEvent] clicking that tile would: the south button or a tap for a left click, the
+//! east button or a long press for a right click. Only shown while a gamepad or touch is the
+//! [ActiveInput], so mouse players never see it
+
+use crate::game::components::{GameCamera, GameOnly, MouseClickEvent};
+use crate::game::game::screen_to_world_position;
+use crate::game::tilemap::TilePosExt;
+use bevy::input::mouse::MouseMotion;
+use bevy::input::touch::Touches;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::map::TilemapSize;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+pub struct VirtualCursorPlugin;
+
+impl Plugin for VirtualCursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveInput>().add_system_set(
+            SystemSet::on_update(crate::CoreState::GameLevel)
+                .with_system(active_input_system.before("input"))
+                // Labelled as input so its clicks are acted on the same frame as a mouse's
+                .with_system(virtual_cursor_system.label("input")),
+        );
+    }
+}
+
+/// Whichever input was last used
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ActiveInput {
+    #[default]
+    KeyboardMouse,
+    Gamepad,
+    Touch,
+}
+
+impl ActiveInput {
+    fn shows_virtual_cursor(&self) -> bool {
+        *self != Self::KeyboardMouse
+    }
+}
+
+/// The cursor's sprite, drawn on the tile under [VirtualCursor::world_position]
+#[derive(Component, Debug, Default)]
+pub struct VirtualCursor {
+    world_position: Vec2,
+}
+
+/// Stick deflection ignored, so a resting stick doesn't creep the cursor about
+const STICK_DEADZONE: f32 = 0.2;
+/// Pixels per second the cursor moves with the stick fully over
+const CURSOR_SPEED: f32 = 600.0;
+/// A touch moving further than this, in pixels, is a drag rather than a tap
+const TAP_DISTANCE: f32 = 16.0;
+/// A tap held at least this long is a long press
+const LONG_PRESS_SECS: f64 = 0.5;
+const CURSOR_COLOUR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+const CURSOR_SIZE: f32 = 60.0;
+/// Over everything standing on the map
+const CURSOR_Z: f32 = 20.0;
+
+fn right_stick(gamepad: Gamepad, axes: &Axis<GamepadAxis>) -> Vec2 {
+    let axis = |axis_type| axes.get(GamepadAxis { gamepad, axis_type }).unwrap_or(0.0);
+    let stick = Vec2::new(
+        axis(GamepadAxisType::RightStickX),
+        axis(GamepadAxisType::RightStickY),
+    );
+    if stick.length() < STICK_DEADZONE {
+        Vec2::ZERO
+    } else {
+        stick
+    }
+}
+
+fn active_input_system(
+    mut active_input: ResMut<ActiveInput>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_reader: EventReader<MouseMotion>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    touches: Res<Touches>,
+) {
+    let mouse_moved = mouse_motion_reader.iter().count() > 0;
+    let used = if touches.iter_just_pressed().next().is_some() {
+        Some(ActiveInput::Touch)
+    } else if gamepad_buttons.get_just_pressed().next().is_some()
+        || gamepads
+            .iter()
+            .any(|gamepad| right_stick(*gamepad, &axes) != Vec2::ZERO)
+    {
+        Some(ActiveInput::Gamepad)
+    } else if keyboard.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || mouse_moved
+    {
+        Some(ActiveInput::KeyboardMouse)
+    } else {
+        None
+    };
+    if let Some(used) = used {
+        if *active_input != used {
+            debug!("Active input now {:?}", used);
+            *active_input = used;
+        }
+    }
+}
+
+fn virtual_cursor_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    active_input: Res<ActiveInput>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    touches: Res<Touches>,
+    mut touch_started: Local<Option<f64>>,
+    windows: Res<Windows>,
+    camera_query: Query<&Transform, (With<GameCamera>, Without<VirtualCursor>)>,
+    tilemap_size_query: Query<&TilemapSize>,
+    mut cursor_query: Query<(&mut VirtualCursor, &mut Transform, &mut Visibility)>,
+    mut mouse_event_writer: EventWriter<MouseClickEvent>,
+) {
+    let (mut cursor, mut transform, mut visibility) = match cursor_query.get_single_mut() {
+        Ok(cursor) => cursor,
+        Err(_) => {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: CURSOR_COLOUR,
+                        custom_size: Some(Vec2::splat(CURSOR_SIZE)),
+                        ..Default::default()
+                    },
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(VirtualCursor::default())
+                .insert(GameOnly);
+            return;
+        }
+    };
+    visibility.is_visible = active_input.shows_virtual_cursor();
+    if !visibility.is_visible {
+        return;
+    }
+
+    let mut confirmed = None;
+    for gamepad in gamepads.iter().cloned() {
+        cursor.world_position += right_stick(gamepad, &axes) * CURSOR_SPEED * time.delta_seconds();
+        let pressed = |button_type| {
+            gamepad_buttons.just_pressed(GamepadButton {
+                gamepad,
+                button_type,
+            })
+        };
+        if pressed(GamepadButtonType::South) {
+            confirmed = Some(MouseButton::Left);
+        } else if pressed(GamepadButtonType::East) {
+            confirmed = Some(MouseButton::Right);
+        }
+    }
+
+    let window = windows.get_primary();
+    let camera_transform = camera_query.get_single().ok();
+    if let (Some(window), Some(camera_transform)) = (window, camera_transform) {
+        // Only the first finger down moves the cursor
+        if let Some(touch) = touches.iter().next() {
+            cursor.world_position =
+                screen_to_world_position(window, camera_transform, touch.position()).truncate();
+            if touches.just_pressed(touch.id()) {
+                *touch_started = Some(time.seconds_since_startup());
+            }
+        }
+        for touch in touches.iter_just_released() {
+            let held_secs = touch_started
+                .take()
+                .map_or(0.0, |started| time.seconds_since_startup() - started);
+            if touch.distance().length() <= TAP_DISTANCE {
+                confirmed = Some(if held_secs >= LONG_PRESS_SECS {
+                    MouseButton::Right
+                } else {
+                    MouseButton::Left
+                });
+            }
+        }
+    }
+
+    // Kept on the map, so the cursor can't be lost off the edge
+    if let Ok(tilemap_size) = tilemap_size_query.get_single() {
+        let map_extent = TilePos {
+            x: tilemap_size.x.saturating_sub(1),
+            y: tilemap_size.y.saturating_sub(1),
+        }
+        .to_world_pos(0.0)
+        .truncate();
+        cursor.world_position = cursor.world_position.clamp(Vec2::ZERO, map_extent);
+    }
+    let tile_pos = TilePos::from_world_pos(cursor.world_position.x, cursor.world_position.y);
+    transform.translation = tile_pos.to_world_pos(CURSOR_Z);
+
+    if let Some(button) = confirmed {
+        debug!("Virtual cursor click on {:?}", tile_pos);
+        mouse_event_writer.send(MouseClickEvent {
+            button,
+            world_position: transform.translation,
+        });
+    }
+}
@@ -13,12 +13,18 @@ pub struct NewGameMenuOnly;
 #[derive(Component)]
 pub enum HubButton {
     Run,
+    EndlessRun,
+    RetrySeed,
     Store,
+    NewGamePlus,
+    AdaptiveDifficulty,
     Quit,
 }
 #[derive(Component)]
 pub enum StoreButton {
     LevelUp,
+    BuyStarfish,
+    BuyOverkill,
     Back,
 }
 #[derive(Component)]
@@ -36,7 +42,11 @@ impl ButtonComponent for HubButton {
     fn to_text(&self) -> &'static str {
         match self {
             Self::Run => "Start Run",
+            Self::EndlessRun => "Endless Run",
+            Self::RetrySeed => "Retry Seed",
             Self::Store => "Store",
+            Self::NewGamePlus => "New Game+",
+            Self::AdaptiveDifficulty => "Adaptive",
             Self::Quit => "Quit",
         }
     }
@@ -46,6 +56,8 @@ impl ButtonComponent for StoreButton {
         match self {
             Self::Back => "Back",
             Self::LevelUp => "Level Up",
+            Self::BuyStarfish => "Buy Starfish",
+            Self::BuyOverkill => "Buy Overkill",
         }
     }
 }
@@ -2,10 +2,15 @@ use bevy::prelude::*;
 
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
+use crate::game::run_timer::format_run_time;
+use crate::game::{MapSeed, RunMode};
 use crate::game_menus::components::{HubButton, HubMenuOnly};
+use crate::menu_core::animated_background::{animated_background, animated_background_system};
 use crate::menu_core::menu_core;
 use crate::menu_core::menu_core::rect_consts::CENTRED;
-use crate::menu_core::menu_core::text::{standard_centred_text, standard_centred_text_custom};
+use crate::menu_core::menu_core::text::{
+    standard_centred_text, standard_centred_text_custom, UiFont,
+};
 use crate::profiles::profiles::{LoadedUserProfile, UserProfile};
 use bevy_ui_nodes::{HeightOrWidth, Property};
 
@@ -18,6 +23,7 @@ impl Plugin for MenuPlugin {
             .add_system_set(
                 SystemSet::on_update(state)
                     .with_system(menu_core::button_system)
+                    .with_system(animated_background_system)
                     .with_system(button_click_system),
             )
             .add_system_set(SystemSet::on_exit(state).with_system(menu_cleanup));
@@ -27,11 +33,29 @@ impl Plugin for MenuPlugin {
 fn button_click_system(
     interaction_query: Query<(&Interaction, &HubButton), (With<Button>, Changed<Interaction>)>,
     mut app_state: ResMut<State<crate::CoreState>>,
+    mut run_mode: ResMut<RunMode>,
+    mut map_seed: ResMut<MapSeed>,
+    mut loaded_profile: ResMut<LoadedUserProfile>,
+    mut new_game_plus_text_query: Query<&mut Text, With<NewGamePlusText>>,
+    mut adaptive_text_query: Query<
+        &mut Text,
+        (With<AdaptiveDifficultyText>, Without<NewGamePlusText>),
+    >,
 ) {
     for (interaction, button) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
             match button {
                 HubButton::Run => {
+                    *run_mode = RunMode::Normal;
+                    app_state.set(crate::CoreState::GameLevel).unwrap();
+                }
+                HubButton::EndlessRun => {
+                    *run_mode = RunMode::Endless;
+                    app_state.set(crate::CoreState::GameLevel).unwrap();
+                }
+                HubButton::RetrySeed => {
+                    // Keeps the last run's mode along with its seed
+                    map_seed.retry = map_seed.seed;
                     app_state.set(crate::CoreState::GameLevel).unwrap();
                 }
                 HubButton::Quit => {
@@ -40,49 +64,104 @@ fn button_click_system(
                 HubButton::Store => {
                     app_state.set(crate::CoreState::GameStore).unwrap();
                 }
+                HubButton::NewGamePlus => {
+                    let user_profile = &mut loaded_profile.user_profile;
+                    user_profile.new_game_plus = !user_profile.new_game_plus;
+                    loaded_profile.save();
+                    for mut text in new_game_plus_text_query.iter_mut() {
+                        text.sections[0].value = new_game_plus_text(&loaded_profile.user_profile);
+                    }
+                }
+                HubButton::AdaptiveDifficulty => {
+                    let user_profile = &mut loaded_profile.user_profile;
+                    user_profile.adaptive_difficulty = !user_profile.adaptive_difficulty;
+                    loaded_profile.save();
+                    for mut text in adaptive_text_query.iter_mut() {
+                        text.sections[0].value =
+                            adaptive_difficulty_text(&loaded_profile.user_profile);
+                    }
+                }
             }
         }
     }
 }
 
+#[derive(Component)]
+struct NewGamePlusText;
+
+fn new_game_plus_text(user_profile: &UserProfile) -> String {
+    let setting = if user_profile.new_game_plus {
+        "On"
+    } else {
+        "Off"
+    };
+    format!("New Game+: {}", setting)
+}
+
+#[derive(Component)]
+struct AdaptiveDifficultyText;
+
+fn adaptive_difficulty_text(user_profile: &UserProfile) -> String {
+    let setting = if user_profile.adaptive_difficulty {
+        "On"
+    } else {
+        "Off"
+    };
+    format!("Adaptive Difficulty: {}", setting)
+}
+
 fn menu_setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    ui_font: Res<UiFont>,
     image_assets: Res<ImageAssetStore>,
     loaded_profile: Res<LoadedUserProfile>,
+    map_seed: Res<MapSeed>,
 ) {
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
-
     // Always save on loading in
     loaded_profile.save();
 
+    let mut stats_text_entities = None;
     commands
         .spawn_bundle(bevy_ui_nodes::new(bevy_ui_nodes::defaults::full(
             FlexDirection::Row,
-            Some(vec![
-                Property::Colour(Color::rgba(1., 1., 1., 1.0)),
-                Property::Image(image_assets.get(&ImageAsset::Background)),
-            ]),
+            Some(vec![Property::Colour(Color::rgba(1., 1., 1., 1.0))]),
         )))
         //.spawn_bundle(bevy_ui_nodes::defaults::full_horizontal())
         .insert(HubMenuOnly {})
         .with_children(|parent| {
-            left_bar_stats_bundle(
+            animated_background(parent, image_assets.get(&ImageAsset::Background));
+            stats_text_entities = Some(left_bar_stats_bundle(
                 parent,
-                font.clone(),
+                &ui_font,
                 &image_assets,
                 &loaded_profile.user_profile,
+            ));
+            right_bar_button_bundle(
+                parent,
+                &ui_font,
+                map_seed.seed.is_some(),
+                loaded_profile.user_profile.new_game_plus_unlocked(),
             );
-            right_bar_button_bundle(parent, font.clone());
         });
+    let (adaptive_text_entity, new_game_plus_text_entity) = stats_text_entities.unwrap();
+    commands
+        .entity(adaptive_text_entity)
+        .insert(AdaptiveDifficultyText);
+    if let Some(new_game_plus_text_entity) = new_game_plus_text_entity {
+        commands
+            .entity(new_game_plus_text_entity)
+            .insert(NewGamePlusText);
+    }
 }
 
 fn left_bar_stats_bundle(
     parent: &mut ChildBuilder,
-    font: Handle<Font>,
+    ui_font: &UiFont,
     image_assets: &Res<ImageAssetStore>,
     user_profile: &UserProfile,
-) {
+) -> (Entity, Option<Entity>) {
+    let mut adaptive_text_entity = None;
+    let mut new_game_plus_text_entity = None;
     let image = image_assets.get(&user_profile.haddock_variant.to_image_asset());
     parent
         .spawn_bundle(bevy_ui_nodes::default_node::half(
@@ -96,7 +175,7 @@ fn left_bar_stats_bundle(
             standard_centred_text_custom(
                 parent,
                 user_profile.name.clone(),
-                font.clone(),
+                ui_font,
                 60.0,
                 Color::BLACK,
             );
@@ -112,17 +191,62 @@ fn left_bar_stats_bundle(
             standard_centred_text(
                 parent,
                 format!("Shells: {}", user_profile.snail_shells),
-                font.clone(),
+                ui_font,
             );
+            standard_centred_text(parent, format!("Level: {}", user_profile.level), ui_font);
             standard_centred_text(
                 parent,
-                format!("Level: {}", user_profile.level),
-                font.clone(),
+                format!("Longest Run: {} turns", user_profile.longest_run_turns),
+                ui_font,
             );
+            if let Some(best_run_time) = user_profile.best_run_time {
+                standard_centred_text(
+                    parent,
+                    format!("Best Time: {}", format_run_time(best_run_time)),
+                    ui_font,
+                );
+            }
+            if user_profile.best_level_reached > 0 {
+                standard_centred_text(
+                    parent,
+                    format!("Best Level: {}", user_profile.best_level_reached),
+                    ui_font,
+                );
+            }
+            if user_profile.best_endless_streak > 0 {
+                standard_centred_text(
+                    parent,
+                    format!("Best Endless Streak: {}", user_profile.best_endless_streak),
+                    ui_font,
+                );
+            }
+            if user_profile.pacifist_runs > 0 {
+                standard_centred_text(
+                    parent,
+                    format!("Pacifist Runs: {}", user_profile.pacifist_runs),
+                    ui_font,
+                );
+            }
+            adaptive_text_entity = Some(
+                standard_centred_text(parent, adaptive_difficulty_text(user_profile), ui_font).text,
+            );
+            if user_profile.new_game_plus_unlocked() {
+                new_game_plus_text_entity = Some(
+                    standard_centred_text(parent, new_game_plus_text(user_profile), ui_font).text,
+                );
+            }
         });
+    (adaptive_text_entity.unwrap(), new_game_plus_text_entity)
 }
 
-fn right_bar_button_bundle(parent: &mut ChildBuilder, font: Handle<Font>) {
+/// [can_retry] once there's been a run this session to retry the seed of, [can_new_game_plus]
+/// once a run has reached [UserProfile::NEW_GAME_PLUS_MIN_LEVEL]
+fn right_bar_button_bundle(
+    parent: &mut ChildBuilder,
+    ui_font: &UiFont,
+    can_retry: bool,
+    can_new_game_plus: bool,
+) {
     parent
         .spawn_bundle(bevy_ui_nodes::default_node::half(
             HeightOrWidth::Width,
@@ -132,13 +256,21 @@ fn right_bar_button_bundle(parent: &mut ChildBuilder, font: Handle<Font>) {
             )]),
         ))
         .with_children(|parent| {
-            menu_core::make_button(HubButton::Quit, parent, font.clone());
-            menu_core::make_button(HubButton::Store, parent, font.clone());
+            menu_core::make_button(HubButton::Quit, parent, ui_font);
+            menu_core::make_button(HubButton::Store, parent, ui_font);
+            menu_core::make_button(HubButton::AdaptiveDifficulty, parent, ui_font);
+            if can_retry {
+                menu_core::make_button(HubButton::RetrySeed, parent, ui_font);
+            }
+            if can_new_game_plus {
+                menu_core::make_button(HubButton::NewGamePlus, parent, ui_font);
+            }
+            menu_core::make_button(HubButton::EndlessRun, parent, ui_font);
             menu_core::make_button_custom_size(
                 HubButton::Run,
                 Size::new(Val::Px(300.0), Val::Px(65.0)),
                 parent,
-                font.clone(),
+                ui_font,
             );
         });
 }
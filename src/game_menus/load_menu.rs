@@ -4,12 +4,15 @@ use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
 use crate::game_menus::components::{LoadButton, LoadMenuOnly};
 
+use crate::menu_core::animated_background::{animated_background, animated_background_system};
 use crate::menu_core::helpers::RectExt;
 use crate::menu_core::menu_core;
 
-use crate::menu_core::menu_core::text::{standard_centred_text, TextNodes};
+use crate::menu_core::menu_core::text::{standard_centred_text, TextNodes, UiFont};
 use crate::menu_core::menu_core::{make_button, ButtonComponent};
-use crate::profiles::profiles::{load_profiles_blocking, LoadingProfileSlotNum, ProfileSlot};
+use crate::profiles::profiles::{
+    load_profiles_blocking, set_aside_corrupt_save, LoadingProfileSlotNum, ProfileSlot,
+};
 use bevy::prelude::{FlexDirection, JustifyContent};
 use bevy_ui_nodes::{HeightOrWidth, Property};
 
@@ -22,8 +25,10 @@ impl Plugin for MenuPlugin {
             .add_system_set(
                 SystemSet::on_update(state)
                     .with_system(menu_core::button_system)
+                    .with_system(animated_background_system)
                     .with_system(button_click_system)
-                    .with_system(profile_picker_click_system),
+                    .with_system(profile_picker_click_system)
+                    .with_system(keyboard_input_system),
             )
             .add_system_set(SystemSet::on_exit(state).with_system(menu_cleanup));
     }
@@ -31,34 +36,117 @@ impl Plugin for MenuPlugin {
 
 fn button_click_system(
     interaction_query: Query<(&Interaction, &LoadButton), (With<Button>, Changed<Interaction>)>,
-    profile_picker_query: Query<&ProfilePicker>,
+    mut profile_picker_query: Query<&mut ProfilePicker>,
     mut app_state: ResMut<State<crate::CoreState>>,
     mut commands: Commands,
+    mut text_query: Query<&mut Text>,
+    mut image_query: Query<&mut UiImage>,
+    image_asset_store: Res<ImageAssetStore>,
 ) {
     for (interaction, button) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
             match button {
                 LoadButton::Back => {
-                    app_state.set(crate::CoreState::MainMenu).unwrap();
+                    set_state_handle_error(&mut app_state, crate::CoreState::MainMenu);
                 }
-                LoadButton::LoadOrNew => {
-                    let picker = profile_picker_query.single();
-                    let slot: &ProfileSlot = picker.get_current_slot();
-                    match slot {
-                        ProfileSlot::Loaded(user_profile) => {
-                            commands.insert_resource(user_profile.clone());
-                            app_state.set(crate::CoreState::GameHub).unwrap();
-                        }
-                        ProfileSlot::Free(slot_num) => {
-                            commands.insert_resource(LoadingProfileSlotNum(*slot_num));
-                            app_state.set(crate::CoreState::NewGameMenu).unwrap();
-                        }
-                    }
+                LoadButton::LoadOrNew => load_or_new(
+                    &mut profile_picker_query.single_mut(),
+                    &mut app_state,
+                    &mut commands,
+                    &mut text_query,
+                    &mut image_query,
+                    &image_asset_store,
+                ),
+            }
+        }
+    }
+}
+
+/// Acts on the currently picked slot, as the [LoadButton::LoadOrNew] button does
+fn load_or_new(
+    picker: &mut ProfilePicker,
+    app_state: &mut State<crate::CoreState>,
+    commands: &mut Commands,
+    text_query: &mut Query<&mut Text>,
+    image_query: &mut Query<&mut UiImage>,
+    image_asset_store: &ImageAssetStore,
+) {
+    match picker.get_current_slot() {
+        ProfileSlot::Loaded(user_profile) => {
+            user_profile.mark_last_played();
+            commands.insert_resource(user_profile.clone());
+            set_state_handle_error(app_state, crate::CoreState::GameHub);
+        }
+        ProfileSlot::Free(slot_num) => {
+            commands.insert_resource(LoadingProfileSlotNum(*slot_num));
+            set_state_handle_error(app_state, crate::CoreState::NewGameMenu);
+        }
+        ProfileSlot::Corrupt(slot_num) => {
+            let slot_num = *slot_num;
+            info!("Setting aside corrupt save in slot {}", slot_num);
+            match set_aside_corrupt_save(slot_num) {
+                Ok(()) => picker.free_current_slot(text_query, image_query, image_asset_store),
+                Err(e) => {
+                    error!(
+                        "Failed to set aside corrupt save in slot {}: {}",
+                        slot_num, e
+                    );
+                    picker.show_set_aside_failed(slot_num, text_query);
                 }
             }
         }
     }
 }
+
+/// Left/Right cycle through profiles and Enter loads (or creates) the current one, so the menu
+/// can be used without a mouse
+fn keyboard_input_system(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut profile_picker_query: Query<&mut ProfilePicker>,
+    mut app_state: ResMut<State<crate::CoreState>>,
+    mut commands: Commands,
+    mut text_query: Query<&mut Text>,
+    mut image_query: Query<&mut UiImage>,
+    image_asset_store: Res<ImageAssetStore>,
+) {
+    let mut profile_picker = profile_picker_query.single_mut();
+    if !profile_picker.initialised {
+        return;
+    }
+    let change = if keyboard_input.just_pressed(KeyCode::Left) {
+        Some(-1)
+    } else if keyboard_input.just_pressed(KeyCode::Right) {
+        Some(1)
+    } else {
+        None
+    };
+    if let Some(change) = change {
+        profile_picker.change(
+            change,
+            &mut text_query,
+            &mut image_query,
+            &image_asset_store,
+        );
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        // Clear so the press isn't seen again by the menu we may be moving to
+        keyboard_input.clear();
+        load_or_new(
+            &mut profile_picker,
+            &mut app_state,
+            &mut commands,
+            &mut text_query,
+            &mut image_query,
+            &image_asset_store,
+        );
+    }
+}
+
+fn set_state_handle_error(state: &mut State<crate::CoreState>, new_state: crate::CoreState) {
+    if let Err(e) = state.set(new_state) {
+        warn!("Error setting state from load menu: {:?}", e);
+    }
+}
 fn profile_picker_click_system(
     interaction_query: Query<
         (&Interaction, &ProfilePickerButton),
@@ -89,14 +177,9 @@ fn profile_picker_click_system(
     }
 }
 
-fn menu_setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    image_assets: Res<ImageAssetStore>,
-) {
+fn menu_setup(mut commands: Commands, ui_font: Res<UiFont>, image_assets: Res<ImageAssetStore>) {
     println!("LoadMenu Setup Start");
     let loaded_profiles = load_profiles_blocking();
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
 
     println!("LoadMenu Setup Middle");
     let mut profile_picker = None;
@@ -104,12 +187,11 @@ fn menu_setup(
     commands
         .spawn_bundle(bevy_ui_nodes::new(bevy_ui_nodes::defaults::full(
             FlexDirection::Column,
-            Some(vec![bevy_ui_nodes::Property::Image(
-                image_assets.get(&ImageAsset::Background),
-            )]),
+            None,
         )))
         .insert(LoadMenuOnly {})
         .with_children(|parent| {
+            animated_background(parent, image_assets.get(&ImageAsset::Background));
             parent
                 .spawn_bundle({
                     use bevy_ui_nodes::*;
@@ -122,8 +204,8 @@ fn menu_setup(
                     ])
                 })
                 .with_children(|parent| {
-                    make_button(LoadButton::Back, parent, font.clone());
-                    let (_button, text) = make_button(LoadButton::LoadOrNew, parent, font.clone());
+                    make_button(LoadButton::Back, parent, &ui_font);
+                    let (_button, text) = make_button(LoadButton::LoadOrNew, parent, &ui_font);
                     load_button_text_entity = Some(text);
                 });
             parent
@@ -135,7 +217,7 @@ fn menu_setup(
                 .with_children(|parent| {
                     profile_picker = Some(ProfilePicker::create(
                         parent,
-                        font.clone(),
+                        &ui_font,
                         loaded_profiles,
                         load_button_text_entity.unwrap(),
                         &image_assets,
@@ -194,12 +276,16 @@ impl ProfilePicker {
                 (picker_text, button_text)
             }
             ProfileSlot::Free(slot_num) => (format!("Empty: {}", slot_num), String::from("New")),
+            ProfileSlot::Corrupt(slot_num) => (
+                format!("Corrupt Save: {}", slot_num),
+                String::from("Set Aside"),
+            ),
         }
     }
 
     fn image_from_slot(slot: &ProfileSlot, image_asset_store: &ImageAssetStore) -> Handle<Image> {
         match slot {
-            ProfileSlot::Free(_) => Default::default(),
+            ProfileSlot::Free(_) | ProfileSlot::Corrupt(_) => Default::default(),
             ProfileSlot::Loaded(loaded_profile) => {
                 image_asset_store.get(&loaded_profile.user_profile.haddock_variant.to_image_asset())
             }
@@ -208,7 +294,7 @@ impl ProfilePicker {
 
     fn create(
         builder: &mut ChildBuilder,
-        font: Handle<Font>,
+        ui_font: &UiFont,
         loaded_profiles: Vec<ProfileSlot>,
         load_button_entity: Entity,
         image_asset_store: &ImageAssetStore,
@@ -229,7 +315,7 @@ impl ProfilePicker {
                 parent
                     .spawn_bundle(arrow_node.clone())
                     .with_children(|parent| {
-                        make_button(ProfilePickerButton::Left, parent, font.clone());
+                        make_button(ProfilePickerButton::Left, parent, ui_font);
                     });
                 parent
                     .spawn_bundle(bevy_ui_nodes::default_node::half(
@@ -266,12 +352,11 @@ impl ProfilePicker {
                                         .id(),
                                 );
                                 let (text, _) = Self::profile_specific_texts(&loaded_profiles[0]);
-                                text_nodes =
-                                    Some(standard_centred_text(parent, text, font.clone()));
+                                text_nodes = Some(standard_centred_text(parent, text, ui_font));
                             });
                     });
                 parent.spawn_bundle(arrow_node).with_children(|parent| {
-                    make_button(ProfilePickerButton::Right, parent, font.clone());
+                    make_button(ProfilePickerButton::Right, parent, ui_font);
                 });
             });
         ProfilePicker {
@@ -288,6 +373,29 @@ impl ProfilePicker {
         &self.loaded_profiles[self.current_index as usize]
     }
 
+    /// Replace the current slot with a free one, i.e. after its corrupt save was set aside
+    fn free_current_slot(
+        &mut self,
+        text_query: &mut Query<&mut Text>,
+        image_query: &mut Query<&mut UiImage>,
+        image_asset_store: &ImageAssetStore,
+    ) {
+        let index = self.current_index as usize;
+        let slot_num = match self.loaded_profiles[index] {
+            ProfileSlot::Loaded(ref loaded_profile) => loaded_profile.file_index(),
+            ProfileSlot::Free(slot_num) | ProfileSlot::Corrupt(slot_num) => slot_num,
+        };
+        self.loaded_profiles[index] = ProfileSlot::Free(slot_num);
+        self.change(0, text_query, image_query, image_asset_store);
+    }
+
+    /// Notes on the current, still corrupt, slot that it couldn't be set aside
+    fn show_set_aside_failed(&self, slot_num: usize, text_query: &mut Query<&mut Text>) {
+        let mut text = text_query.get_mut(self.text_nodes.text).unwrap();
+        text.sections[0].value =
+            format!("Corrupt Save: {}\nCouldn't Set Aside, Try Again", slot_num);
+    }
+
     fn change(
         &mut self,
         change: i32,
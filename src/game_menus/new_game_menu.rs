@@ -1,20 +1,19 @@
 use bevy::prelude::*;
 
-use log::info;
-
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
 use crate::game_menus::components::{NewGameButton, NewGameMenuOnly};
+use crate::menu_core::animated_background::{animated_background, animated_background_system};
 use crate::menu_core::menu_core;
 
-use crate::menu_core::menu_core::text::{standard_centred_text, TextNodes};
+use crate::menu_core::menu_core::text::{standard_centred_text, UiFont};
 use crate::menu_core::menu_core::{make_button, make_button_custom_size};
+use crate::menu_core::text_input::TextInput;
 use crate::profiles::profiles::{
-    HaddockVariant, LoadedUserProfile, LoadingProfileSlotNum, UserProfile,
+    HaddockVariant, LoadedUserProfile, LoadingProfileSlotNum, PerformanceHistory, UserProfile,
 };
+use bevy_ui_nodes::HeightOrWidth;
 use bevy_ui_nodes::HeightOrWidth::Height;
-use bevy_ui_nodes::{HeightOrWidth, Property};
-use std::time::Duration;
 
 pub struct MenuPlugin;
 
@@ -25,6 +24,7 @@ impl Plugin for MenuPlugin {
             .add_system_set(
                 SystemSet::on_update(state)
                     .with_system(menu_core::button_system)
+                    .with_system(animated_background_system)
                     .with_system(TextInput::system)
                     .with_system(button_click_system),
             )
@@ -55,6 +55,16 @@ fn button_click_system(
                                     level: 0,
                                     name: input.to_string(),
                                     haddock_variant: HaddockVariant::Normal,
+                                    longest_run_turns: 0,
+                                    starfish: 0,
+                                    overkill_passthrough: false,
+                                    best_run_time: None,
+                                    best_endless_streak: 0,
+                                    best_level_reached: 0,
+                                    new_game_plus: false,
+                                    adaptive_difficulty: false,
+                                    performance: PerformanceHistory::default(),
+                                    pacifist_runs: 0,
                                 },
                                 loaded_slot_num.0,
                             );
@@ -70,24 +80,18 @@ fn button_click_system(
     }
 }
 
-fn menu_setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    image_assets: Res<ImageAssetStore>,
-) {
+fn menu_setup(mut commands: Commands, ui_font: Res<UiFont>, image_assets: Res<ImageAssetStore>) {
     println!("NewGameMenu Setup Start");
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
 
     let mut text_input = None;
     commands
         .spawn_bundle(bevy_ui_nodes::new(bevy_ui_nodes::defaults::full(
             FlexDirection::Column,
-            Some(vec![Property::Image(
-                image_assets.get(&ImageAsset::Background),
-            )]),
+            None,
         )))
         .insert(NewGameMenuOnly {})
         .with_children(|parent| {
+            animated_background(parent, image_assets.get(&ImageAsset::Background));
             parent
                 .spawn_bundle(bevy_ui_nodes::default_node::half(
                     HeightOrWidth::Height,
@@ -95,12 +99,12 @@ fn menu_setup(
                     None,
                 ))
                 .with_children(|parent| {
-                    make_button(NewGameButton::Back, parent, font.clone());
+                    make_button(NewGameButton::Back, parent, &ui_font);
                     make_button_custom_size(
                         NewGameButton::NewGame,
                         Size::new(Val::Px(250.0), Val::Px(65.0)),
                         parent,
-                        font.clone(),
+                        &ui_font,
                     );
                 });
             parent
@@ -112,8 +116,8 @@ fn menu_setup(
                     )]),
                 ))
                 .with_children(|parent| {
-                    standard_centred_text(parent, "Profile Name", font.clone());
-                    text_input = Some(TextInput::create(parent, font.clone()));
+                    standard_centred_text(parent, "Profile Name", &ui_font);
+                    text_input = Some(TextInput::create(parent, &ui_font, is_valid_name_char));
                 });
         });
 
@@ -129,104 +133,7 @@ fn menu_cleanup(q: Query<Entity, With<NewGameMenuOnly>>, mut commands: Commands)
     }
 }
 
-//TODO Move this out of this file
-#[derive(Debug, Component)]
-struct TextInput {
-    text_nodes: TextNodes,
-    current_text: String,
-    cursor_on: bool,
-    timer: Timer,
-}
-
-impl TextInput {
-    const MAX_LEN: usize = 32;
-    fn create(parent: &mut ChildBuilder, font: Handle<Font>) -> Self {
-        let mut text_nodes = None;
-        parent
-            .spawn_bundle(bevy_ui_nodes::default_node::empty(
-                HeightOrWidth::Width,
-                FlexDirection::Row,
-                Some(vec![bevy_ui_nodes::Property::Colour(Color::GRAY)]),
-            ))
-            .with_children(|parent| {
-                text_nodes = Some(standard_centred_text(parent, "|", font));
-            });
-
-        let timer = Timer::new(Duration::from_millis(500), true);
-        Self {
-            text_nodes: text_nodes.unwrap(),
-            current_text: String::new(),
-            cursor_on: true,
-            timer,
-        }
-    }
-
-    fn update(&self, text_query: &mut Query<&mut Text>) {
-        if let Ok(mut text) = text_query.get_mut(self.text_nodes.text) {
-            let cursor = if self.cursor_on { "|" } else { "" };
-            text.sections[0].value = format!("{}{}", self.current_text.clone(), cursor);
-        }
-    }
-
-    fn add_char(&mut self, char: char, text_query: &mut Query<&mut Text>) {
-        if self.current_text.len() < Self::MAX_LEN {
-            self.current_text.push(char);
-            info!("Appending: {:?}. Result: {}", char, self.current_text);
-            self.update(text_query);
-        }
-    }
-
-    fn backspace(&mut self, text_query: &mut Query<&mut Text>) {
-        if !self.current_text.is_empty() {
-            self.current_text.pop();
-            info!("Backspace. Result: {}", self.current_text);
-            self.update(text_query);
-        }
-    }
-
-    fn system(
-        mut received_character_events: EventReader<ReceivedCharacter>,
-        input_keys: Res<Input<KeyCode>>,
-        mut text_query: Query<&mut Text>,
-        mut self_query: Query<&mut Self>,
-        time: Res<Time>,
-    ) {
-        let mut text_input = self_query.single_mut();
-        for event in received_character_events.iter() {
-            //TODO: Sanitise chars here, but also validate before submitting just in case too
-            if Self::is_valid_input_char(&event.char) {
-                text_input.add_char(event.char, &mut text_query);
-            }
-        }
-
-        if input_keys.just_pressed(KeyCode::Back) {
-            text_input.backspace(&mut text_query);
-        }
-
-        text_input.timer.tick(time.delta());
-
-        if text_input.timer.just_finished() {
-            text_input.cursor_on = !text_input.cursor_on;
-            text_input.update(&mut text_query);
-        }
-    }
-
-    fn current_string_if_valid(&self) -> Option<&str> {
-        let len_ok = 2 <= self.current_text.len() && self.current_text.len() <= 32;
-        let chars_ok = {
-            self.current_text
-                .chars()
-                .map(|c| Self::is_valid_input_char(&c))
-                .any(|x| x)
-        };
-        if len_ok && chars_ok {
-            Some(&self.current_text)
-        } else {
-            None
-        }
-    }
-
-    fn is_valid_input_char(c: &char) -> bool {
-        c.is_ascii_alphabetic() || c == &' '
-    }
+/// Profile names are letters and spaces
+fn is_valid_name_char(c: &char) -> bool {
+    c.is_ascii_alphabetic() || c == &' '
 }
@@ -3,12 +3,15 @@ use bevy::prelude::*;
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
 use crate::game_menus::components::{StoreButton, StoreMenuOnly};
+use crate::menu_core::animated_background::{animated_background, animated_background_system};
 use crate::menu_core::menu_core;
-use crate::menu_core::menu_core::text::{standard_centred_text, standard_centred_text_custom};
+use crate::menu_core::menu_core::text::{
+    standard_centred_text, standard_centred_text_custom, UiFont,
+};
+use crate::menu_core::scroll_view::{scroll_view, scroll_view_system};
 use crate::menu_core::structure::SplitWay;
 use crate::profiles::profiles::{LoadedUserProfile, UserProfile};
-use bevy::prelude::{FlexDirection, JustifyContent};
-use bevy_ui_nodes::Property;
+use bevy::prelude::FlexDirection;
 
 pub struct MenuPlugin;
 
@@ -28,6 +31,8 @@ impl Plugin for MenuPlugin {
             .add_system_set(
                 SystemSet::on_update(state)
                     .with_system(menu_core::button_system)
+                    .with_system(animated_background_system)
+                    .with_system(scroll_view_system)
                     .with_system(text_update_system)
                     .with_system(button_click_system),
             )
@@ -55,6 +60,75 @@ fn maybe_level_up(profile: &mut UserProfile) -> bool {
     }
 }
 
+/// Shell cost of one Starfish of Second Chances
+const STARFISH_COST: usize = 15;
+
+fn maybe_buy_starfish(profile: &mut UserProfile) -> bool {
+    if STARFISH_COST <= profile.snail_shells {
+        info!("Bought a starfish!");
+        profile.starfish += 1;
+        profile.snail_shells -= STARFISH_COST;
+        true
+    } else {
+        debug!("Can't afford a starfish!");
+        false
+    }
+}
+
+/// Shell cost of the overkill passthrough upgrade, see [UserProfile::overkill_passthrough]
+const OVERKILL_COST: usize = 40;
+
+/// Only ever bought once
+fn maybe_buy_overkill(profile: &mut UserProfile) -> bool {
+    if profile.overkill_passthrough {
+        debug!("Already have overkill!");
+        false
+    } else if OVERKILL_COST <= profile.snail_shells {
+        info!("Bought overkill!");
+        profile.overkill_passthrough = true;
+        profile.snail_shells -= OVERKILL_COST;
+        true
+    } else {
+        debug!("Can't afford overkill!");
+        false
+    }
+}
+
+/// e.g. "Health: 4 -> 5", or just "Health: 4" if levelling up doesn't change it
+fn stat_preview(name: &str, now: usize, next: usize) -> String {
+    if now == next {
+        format!("{}: {}", name, now)
+    } else {
+        format!("{}: {} -> {}", name, now, next)
+    }
+}
+
+/// The profile's stats, each alongside what it would be after the next level up
+fn stats_text(profile: &UserProfile) -> String {
+    let levelled_up = UserProfile {
+        level: profile.level + 1,
+        ..profile.clone()
+    };
+    let overkill = if profile.overkill_passthrough {
+        "Yes".to_string()
+    } else {
+        format!("No (Cost: {})", OVERKILL_COST)
+    };
+    format!(
+        "{}\n\n{}\n{}\n\nStarfish: {} (Cost: {})\nOverkill: {}",
+        stat_preview("Level", profile.level, levelled_up.level),
+        stat_preview("Health", profile.max_health(), levelled_up.max_health()),
+        stat_preview(
+            "Power Charges",
+            profile.max_power_charges(),
+            levelled_up.max_power_charges()
+        ),
+        profile.starfish,
+        STARFISH_COST,
+        overkill,
+    )
+}
+
 fn text_update_system(
     mut text_query: Query<&mut Text>,
     text_entity_query: Query<&StoreMenuDisplayText, Changed<StoreMenuDisplayText>>,
@@ -67,14 +141,7 @@ fn text_update_system(
                 StoreMenuDisplayTextType::Shells => {
                     format!("Shells: {}", user_profile.user_profile.snail_shells)
                 }
-                StoreMenuDisplayTextType::Stats => {
-                    format!(
-                        "Level: {}\n\nHealth: {}\nPower Charges: {}",
-                        user_profile.user_profile.level,
-                        user_profile.user_profile.max_health(),
-                        user_profile.user_profile.max_power_charges()
-                    )
-                }
+                StoreMenuDisplayTextType::Stats => stats_text(&user_profile.user_profile),
                 StoreMenuDisplayTextType::Cost => {
                     format!(
                         "Cost: {}",
@@ -112,6 +179,18 @@ fn button_click_system(
                     }
                     trigger_change_on_text_entities(&mut text_entity_query);
                 }
+                StoreButton::BuyStarfish => {
+                    if maybe_buy_starfish(&mut loaded_profile.user_profile) {
+                        loaded_profile.save();
+                    }
+                    trigger_change_on_text_entities(&mut text_entity_query);
+                }
+                StoreButton::BuyOverkill => {
+                    if maybe_buy_overkill(&mut loaded_profile.user_profile) {
+                        loaded_profile.save();
+                    }
+                    trigger_change_on_text_entities(&mut text_entity_query);
+                }
             }
         }
     }
@@ -119,23 +198,20 @@ fn button_click_system(
 
 fn menu_setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    ui_font: Res<UiFont>,
     image_asset_store: Res<ImageAssetStore>,
 ) {
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
-
     let mut display_text_cost = None;
     let mut display_text_shells = None;
     let mut display_text_stats = None;
     commands
         .spawn_bundle(bevy_ui_nodes::default_node::full(
             FlexDirection::Column,
-            Some(vec![Property::Image(
-                image_asset_store.get(&ImageAsset::Background),
-            )]),
+            None,
         ))
         .insert(StoreMenuOnly {})
         .with_children(|parent| {
+            animated_background(parent, image_asset_store.get(&ImageAsset::Background));
             crate::menu_core::structure::split_unequal(
                 parent,
                 SplitWay::Vertical,
@@ -147,7 +223,7 @@ fn menu_setup(
                             let text_nodes = standard_centred_text(
                                 parent,
                                 "Cost To Level".to_string(),
-                                font.clone(),
+                                &ui_font,
                             );
                             display_text_cost = Some(StoreMenuDisplayText(
                                 StoreMenuDisplayTextType::Cost,
@@ -159,7 +235,19 @@ fn menu_setup(
                                 StoreButton::LevelUp,
                                 button_size,
                                 parent,
-                                font.clone(),
+                                &ui_font,
+                            );
+                            menu_core::make_button_custom_size(
+                                StoreButton::BuyStarfish,
+                                button_size,
+                                parent,
+                                &ui_font,
+                            );
+                            menu_core::make_button_custom_size(
+                                StoreButton::BuyOverkill,
+                                button_size,
+                                parent,
+                                &ui_font,
                             );
                         },
                         |parent| {
@@ -170,7 +258,7 @@ fn menu_setup(
                                     let text_nodes = standard_centred_text_custom(
                                         parent,
                                         "Shells".to_string(),
-                                        font.clone(),
+                                        &ui_font,
                                         40.0,
                                         Color::WHITE,
                                     );
@@ -181,28 +269,20 @@ fn menu_setup(
                                     ));
                                 },
                                 |parent| {
-                                    parent
-                                        .spawn_bundle({
-                                            use bevy_ui_nodes::*;
-                                            new(defaults::full(
-                                                FlexDirection::Column,
-                                                Some(vec![Property::Justify(
-                                                    JustifyContent::Center,
-                                                )]),
+                                    // Scrolls as the stats grow past what fits
+                                    scroll_view(parent, |parent| {
+                                        let text = parent
+                                            .spawn_bundle(TextBundle::from_section(
+                                                "Stats",
+                                                ui_font.text_style(40.0),
                                             ))
-                                        })
-                                        .with_children(|parent| {
-                                            let text_nodes = standard_centred_text(
-                                                parent,
-                                                "Stats".to_string(),
-                                                font.clone(),
-                                            );
-                                            display_text_stats = Some(StoreMenuDisplayText(
-                                                StoreMenuDisplayTextType::Stats,
-                                                text_nodes.text,
-                                                0,
-                                            ));
-                                        });
+                                            .id();
+                                        display_text_stats = Some(StoreMenuDisplayText(
+                                            StoreMenuDisplayTextType::Stats,
+                                            text,
+                                            0,
+                                        ));
+                                    });
                                 },
                                 20f32,
                             );
@@ -210,7 +290,7 @@ fn menu_setup(
                     );
                 },
                 |parent| {
-                    menu_core::make_button(StoreButton::Back, parent, font.clone());
+                    menu_core::make_button(StoreButton::Back, parent, &ui_font);
                 },
                 70.0,
             )
@@ -229,3 +309,22 @@ fn menu_cleanup(q: Query<Entity, With<StoreMenuOnly>>, mut commands: Commands) {
         commands.entity(entity).despawn_recursive();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::stats_text;
+    use crate::profiles::profiles::UserProfile;
+
+    #[test]
+    fn test_stats_text_previews_next_level() {
+        let profile = UserProfile {
+            level: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            "Level: 2 -> 3\n\nHealth: 3 -> 4\nPower Charges: 3\n\nStarfish: 0 (Cost: 15)\n\
+             Overkill: No (Cost: 40)",
+            stats_text(&profile)
+        );
+    }
+}
@@ -2,18 +2,22 @@ use bevy::prelude::*;
 
 use bevy::utils::Duration;
 
-use crate::game::components::GameCamera;
+use crate::game::components::{CameraShake, GameCamera};
+use crate::menu_core::menu_core::text::UiFont;
+use crate::profiles::settings::Settings;
+use bevy::asset::AssetServerSettings;
 use bevy::render::texture::ImageSettings;
 use bevy_ecs_tilemap::TilemapPlugin;
 use bevy_kira_audio::AudioPlugin;
 use log::info;
 
 mod asset_handling;
+mod audio;
 mod game;
 mod game_menus;
 mod helpers;
 mod main_menu;
-mod map_gen;
+pub mod map_gen;
 mod menu_core;
 mod profiles;
 
@@ -28,19 +32,38 @@ enum CoreState {
     GameStore,
     LoadMenu,
     NewGameMenu,
+    Demo,
 }
 
 pub fn main() {
     let initial_state = CoreState::Loading;
     App::new()
         .insert_resource(ImageSettings::default_nearest())
+        // Picks up edited assets without a restart while developing, there's no file watching on
+        // the web
+        .insert_resource(AssetServerSettings {
+            watch_for_changes: cfg!(all(debug_assertions, not(target_arch = "wasm32"))),
+            ..Default::default()
+        })
+        // The canvas is fixed at the minimum size on wasm anyway, see [print_window_info]
+        .insert_resource(WindowDescriptor {
+            resize_constraints: bevy::window::WindowResizeConstraints {
+                min_width: MIN_WINDOW_WIDTH,
+                min_height: MIN_WINDOW_HEIGHT,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
         .add_plugins(DefaultPlugins)
         .add_plugin(TilemapPlugin)
         .add_plugin(AudioPlugin)
+        .insert_resource(Settings::load())
+        .init_resource::<UiFont>()
         .add_plugin(crate::game::Plugin)
         .add_plugin(crate::game::GameOverlayPlugin)
         .add_plugin(crate::main_menu::Plugin)
         .add_plugin(crate::asset_handling::Plugin)
+        .add_plugin(crate::audio::Plugin)
         .add_plugin(crate::game_menus::HubMenuPlugin)
         .add_plugin(crate::game_menus::StoreMenuPlugin)
         .add_plugin(crate::game_menus::LoadMenuPlugin)
@@ -62,7 +85,8 @@ impl Default for ActiveSystem {
 fn general_game_setup(mut commands: Commands) {
     commands
         .spawn_bundle(Camera2dBundle::default())
-        .insert(GameCamera);
+        .insert(GameCamera)
+        .insert(CameraShake::default());
 }
 
 fn setup_window_title(
@@ -81,6 +105,11 @@ fn setup_window_title(
     }
 }
 
+/// Smallest window the menus lay out in without overlapping or clipping, the same as the size
+/// forced on wasm
+const MIN_WINDOW_WIDTH: f32 = 800.0;
+const MIN_WINDOW_HEIGHT: f32 = 550.0;
+
 #[cfg(target_arch = "wasm32")]
 fn print_window_info(mut windows: ResMut<Windows>) {
     for window in windows.iter_mut() {
@@ -1,18 +1,128 @@
 use crate::menu_core::menu_core::ButtonComponent;
+use crate::profiles::settings::{CameraFollowMode, HitStopMode, StartPlacement};
 use bevy::prelude::Component;
 
 #[derive(Component)]
 pub struct MenuOnly;
 
+/// Which of the main menu's screens is showing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MenuScreen {
+    #[default]
+    Main,
+    Settings,
+}
+
+/// Buttons for a setting hold its current value, to show it
 #[derive(Component)]
 pub enum MenuButton {
+    Continue,
     Play,
+    Settings,
+    Back,
+    TextSize,
+    ConfirmAttacks(bool),
+    SwapMouseButtons(bool),
+    AutoAim(bool),
+    Vibration(bool),
+    EnemyTiming(f32),
+    Lighting(f32),
+    SpectateEnemies(bool),
+    ScreenShake(bool),
+    PlayerHealthBar(bool),
+    Forgiveness(f32),
+    LastStand(bool),
+    CameraFollow(CameraFollowMode),
+    TileHoverLabel(bool),
+    ReduceMotion(bool),
+    DeathReview(bool),
+    StartPosition(StartPlacement),
+    TurnTick(bool),
+    RelaxMode(bool),
+    PowerAiming(bool),
+    MapGenReplay(bool),
+    IntentionPreview(bool),
+    Volume(f32),
+    MusicVolume(f32),
+    DepositChests(bool),
+    IdleDemo(bool),
+    HitStop(HitStopMode),
+    Autosave(bool),
     Quit,
 }
 impl ButtonComponent for MenuButton {
     fn to_text(&self) -> &'static str {
         match self {
+            Self::Continue => "Continue",
             Self::Play => "Play",
+            Self::Settings => "Settings",
+            Self::Back => "Back",
+            Self::TextSize => "Text Size",
+            Self::ConfirmAttacks(true) => "Confirm Attacks: On",
+            Self::ConfirmAttacks(false) => "Confirm Attacks: Off",
+            Self::SwapMouseButtons(true) => "Swap Mouse Buttons: On",
+            Self::SwapMouseButtons(false) => "Swap Mouse Buttons: Off",
+            Self::AutoAim(true) => "Auto-Aim: On",
+            Self::AutoAim(false) => "Auto-Aim: Off",
+            Self::Vibration(true) => "Vibration: On",
+            Self::Vibration(false) => "Vibration: Off",
+            Self::EnemyTiming(timing) if *timing >= 2.0 => "Enemy Timing: Slowest",
+            Self::EnemyTiming(timing) if *timing > 1.0 => "Enemy Timing: Slow",
+            Self::EnemyTiming(_) => "Enemy Timing: Normal",
+            Self::Lighting(intensity) if *intensity > 0.5 => "Lighting: High",
+            Self::Lighting(intensity) if *intensity > 0.0 => "Lighting: Low",
+            Self::Lighting(_) => "Lighting: Off",
+            Self::SpectateEnemies(true) => "Spectate Enemies: On",
+            Self::SpectateEnemies(false) => "Spectate Enemies: Off",
+            Self::ScreenShake(true) => "Screen Shake: On",
+            Self::ScreenShake(false) => "Screen Shake: Off",
+            Self::PlayerHealthBar(true) => "Health Bar: On",
+            Self::PlayerHealthBar(false) => "Health Bar: Off",
+            Self::Forgiveness(fraction) if *fraction >= 0.5 => "Forgiveness: Keep Half",
+            Self::Forgiveness(fraction) if *fraction > 0.0 => "Forgiveness: Keep Quarter",
+            Self::Forgiveness(_) => "Forgiveness: Off",
+            Self::LastStand(true) => "Last Stand: On",
+            Self::LastStand(false) => "Last Stand: Off",
+            Self::CameraFollow(CameraFollowMode::Box) => "Camera: Box",
+            Self::CameraFollow(CameraFollowMode::Centred) => "Camera: Centred",
+            Self::CameraFollow(CameraFollowMode::SmoothBox) => "Camera: Smooth",
+            Self::TileHoverLabel(true) => "Tile Coords: On",
+            Self::TileHoverLabel(false) => "Tile Coords: Off",
+            Self::ReduceMotion(true) => "Reduce Motion: On",
+            Self::ReduceMotion(false) => "Reduce Motion: Off",
+            Self::DeathReview(true) => "Death Review: On",
+            Self::DeathReview(false) => "Death Review: Off",
+            Self::StartPosition(StartPlacement::Edge) => "Start: Edge",
+            Self::StartPosition(StartPlacement::Centre) => "Start: Centre",
+            Self::TurnTick(true) => "Turn Tick: On",
+            Self::TurnTick(false) => "Turn Tick: Off",
+            Self::RelaxMode(true) => "Relax Mode: On",
+            Self::RelaxMode(false) => "Relax Mode: Off",
+            Self::PowerAiming(true) => "Power Aiming: On",
+            Self::PowerAiming(false) => "Power Aiming: Off",
+            Self::MapGenReplay(true) => "Map Forming: On",
+            Self::MapGenReplay(false) => "Map Forming: Off",
+            Self::IntentionPreview(true) => "Enemy Intentions: On",
+            Self::IntentionPreview(false) => "Enemy Intentions: Off",
+            Self::Volume(volume) if *volume >= 1.0 => "Volume: 100%",
+            Self::Volume(volume) if *volume >= 0.75 => "Volume: 75%",
+            Self::Volume(volume) if *volume >= 0.5 => "Volume: 50%",
+            Self::Volume(volume) if *volume > 0.0 => "Volume: 25%",
+            Self::Volume(_) => "Volume: Off",
+            Self::MusicVolume(volume) if *volume >= 1.0 => "Music: 100%",
+            Self::MusicVolume(volume) if *volume >= 0.75 => "Music: 75%",
+            Self::MusicVolume(volume) if *volume >= 0.5 => "Music: 50%",
+            Self::MusicVolume(volume) if *volume > 0.0 => "Music: 25%",
+            Self::MusicVolume(_) => "Music: Off",
+            Self::DepositChests(true) => "Deposit Chests: On",
+            Self::DepositChests(false) => "Deposit Chests: Off",
+            Self::IdleDemo(true) => "Idle Demo: On",
+            Self::IdleDemo(false) => "Idle Demo: Off",
+            Self::HitStop(HitStopMode::Off) => "Hit Stop: Off",
+            Self::HitStop(HitStopMode::PowerKills) => "Hit Stop: Power Kills",
+            Self::HitStop(HitStopMode::AllKills) => "Hit Stop: All Kills",
+            Self::Autosave(true) => "Autosave: On",
+            Self::Autosave(false) => "Autosave: Off",
             Self::Quit => "Quit",
         }
     }
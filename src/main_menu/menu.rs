@@ -3,18 +3,30 @@ use bevy::prelude::*;
 
 use crate::asset_handling::asset::ImageAsset;
 use crate::asset_handling::ImageAssetStore;
-use crate::main_menu::components::{MenuButton, MenuOnly};
+use crate::main_menu::components::{MenuButton, MenuOnly, MenuScreen};
+use crate::menu_core::animated_background::{animated_background, animated_background_system};
 use crate::menu_core::menu_core;
+use crate::menu_core::menu_core::text::UiFont;
+use crate::menu_core::scroll_view::{scroll_view, scroll_view_system};
+use crate::menu_core::structure::SplitWay;
+use crate::profiles::profiles::load_last_played;
+use crate::profiles::settings::{
+    next_death_keep_fraction, next_enemy_timing, next_font_scale, next_lighting_intensity,
+    next_volume, Settings,
+};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         let state = crate::CoreState::MainMenu;
-        app.add_system_set(SystemSet::on_enter(state).with_system(menu_setup))
+        app.init_resource::<MenuScreen>()
+            .add_system_set(SystemSet::on_enter(state).with_system(menu_setup))
             .add_system_set(
                 SystemSet::on_update(state)
                     .with_system(menu_core::button_system)
+                    .with_system(animated_background_system)
+                    .with_system(scroll_view_system)
                     .with_system(button_click_system),
             )
             .add_system_set(SystemSet::on_exit(state).with_system(menu_cleanup));
@@ -25,27 +37,151 @@ fn button_click_system(
     interaction_query: Query<(&Interaction, &MenuButton), (With<Button>, Changed<Interaction>)>,
     mut app_state: ResMut<State<crate::CoreState>>,
     mut app_exit_events: EventWriter<AppExit>,
-    _commands: Commands,
+    mut ui_font: ResMut<UiFont>,
+    mut settings: ResMut<Settings>,
+    mut menu_screen: ResMut<MenuScreen>,
+    image_assets: Res<ImageAssetStore>,
+    menu_query: Query<Entity, With<MenuOnly>>,
+    mut commands: Commands,
 ) {
     for (interaction, button) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
             match button {
+                MenuButton::Continue => match load_last_played() {
+                    Some(profile) => {
+                        info!("Continuing profile in slot {}", profile.file_index());
+                        commands.insert_resource(profile);
+                        app_state.set(crate::CoreState::GameHub).unwrap();
+                    }
+                    // Only shown when there's a profile to continue, but it may have gone since
+                    None => warn!("No profile to continue"),
+                },
                 MenuButton::Play => {
                     app_state.set(crate::CoreState::LoadMenu).unwrap();
                 }
+                MenuButton::Settings | MenuButton::Back => {
+                    *menu_screen = match button {
+                        MenuButton::Settings => MenuScreen::Settings,
+                        _ => MenuScreen::Main,
+                    };
+                    respawn_menu(
+                        &mut commands,
+                        &menu_query,
+                        &image_assets,
+                        &ui_font,
+                        &settings,
+                        *menu_screen,
+                    );
+                }
                 MenuButton::Quit => app_exit_events.send(AppExit),
+                setting => {
+                    change_setting(setting, &mut settings);
+                    settings.save();
+                    if ui_font.scale != settings.font_scale {
+                        ui_font.scale = settings.font_scale;
+                    }
+                    // Respawn the menu so the button shows the new state, and any new text size
+                    respawn_menu(
+                        &mut commands,
+                        &menu_query,
+                        &image_assets,
+                        &ui_font,
+                        &settings,
+                        *menu_screen,
+                    );
+                }
             }
         }
     }
 }
 
+/// Moves the setting [button] shows on to its next value
+fn change_setting(button: &MenuButton, settings: &mut Settings) {
+    match button {
+        MenuButton::TextSize => settings.font_scale = next_font_scale(settings.font_scale),
+        MenuButton::ConfirmAttacks(_) => settings.confirm_attacks = !settings.confirm_attacks,
+        MenuButton::SwapMouseButtons(_) => {
+            settings.swap_mouse_buttons = !settings.swap_mouse_buttons
+        }
+        MenuButton::AutoAim(_) => settings.auto_aim = !settings.auto_aim,
+        MenuButton::Vibration(_) => settings.vibration = !settings.vibration,
+        MenuButton::EnemyTiming(_) => {
+            settings.enemy_timing = next_enemy_timing(settings.enemy_timing)
+        }
+        MenuButton::Lighting(_) => {
+            settings.lighting_intensity = next_lighting_intensity(settings.lighting_intensity)
+        }
+        MenuButton::SpectateEnemies(_) => settings.spectate_enemies = !settings.spectate_enemies,
+        MenuButton::ScreenShake(_) => settings.screen_shake = !settings.screen_shake,
+        MenuButton::PlayerHealthBar(_) => settings.player_health_bar = !settings.player_health_bar,
+        MenuButton::Forgiveness(_) => {
+            settings.death_keep_fraction = next_death_keep_fraction(settings.death_keep_fraction)
+        }
+        MenuButton::LastStand(_) => settings.last_stand = !settings.last_stand,
+        MenuButton::CameraFollow(_) => {
+            settings.camera_follow_mode = settings.camera_follow_mode.next()
+        }
+        MenuButton::TileHoverLabel(_) => settings.tile_hover_label = !settings.tile_hover_label,
+        MenuButton::ReduceMotion(_) => settings.reduce_motion = !settings.reduce_motion,
+        MenuButton::DeathReview(_) => settings.death_review = !settings.death_review,
+        MenuButton::StartPosition(_) => settings.start_placement = settings.start_placement.next(),
+        MenuButton::TurnTick(_) => settings.turn_tick_sound = !settings.turn_tick_sound,
+        MenuButton::RelaxMode(_) => settings.relax_mode = !settings.relax_mode,
+        MenuButton::PowerAiming(_) => settings.power_aiming = !settings.power_aiming,
+        MenuButton::MapGenReplay(_) => settings.map_gen_replay = !settings.map_gen_replay,
+        MenuButton::IntentionPreview(_) => settings.intention_preview = !settings.intention_preview,
+        MenuButton::Volume(_) => settings.volume = next_volume(settings.volume),
+        MenuButton::MusicVolume(_) => settings.music_volume = next_volume(settings.music_volume),
+        MenuButton::DepositChests(_) => settings.deposit_chests = !settings.deposit_chests,
+        MenuButton::IdleDemo(_) => settings.idle_demo = !settings.idle_demo,
+        MenuButton::HitStop(_) => settings.hit_stop = settings.hit_stop.next(),
+        MenuButton::Autosave(_) => settings.autosave = !settings.autosave,
+        MenuButton::Continue
+        | MenuButton::Play
+        | MenuButton::Settings
+        | MenuButton::Back
+        | MenuButton::Quit => {}
+    }
+}
+
 fn menu_setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     image_assets: Res<ImageAssetStore>,
+    ui_font: Res<UiFont>,
+    settings: Res<Settings>,
+    mut menu_screen: ResMut<MenuScreen>,
+) {
+    *menu_screen = MenuScreen::Main;
+    spawn_menu(
+        &mut commands,
+        &image_assets,
+        &ui_font,
+        &settings,
+        *menu_screen,
+    );
+}
+
+fn respawn_menu(
+    commands: &mut Commands,
+    menu_query: &Query<Entity, With<MenuOnly>>,
+    image_assets: &ImageAssetStore,
+    ui_font: &UiFont,
+    settings: &Settings,
+    menu_screen: MenuScreen,
 ) {
-    let font = asset_server.load("fonts/bigfish/Bigfish.ttf");
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_menu(commands, image_assets, ui_font, settings, menu_screen);
+}
 
+fn spawn_menu(
+    commands: &mut Commands,
+    image_assets: &ImageAssetStore,
+    ui_font: &UiFont,
+    settings: &Settings,
+    menu_screen: MenuScreen,
+) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -55,16 +191,90 @@ fn menu_setup(
                 flex_direction: FlexDirection::Column,
                 ..Default::default()
             },
-            image: UiImage(image_assets.get(&ImageAsset::Background)),
             ..Default::default()
         })
         .insert(MenuOnly {})
         .with_children(|parent| {
-            menu_core::make_button(MenuButton::Quit, parent, font.clone());
-            menu_core::make_button(MenuButton::Play, parent, font.clone());
+            animated_background(parent, image_assets.get(&ImageAsset::Background));
+            match menu_screen {
+                MenuScreen::Main => spawn_main_buttons(parent, ui_font),
+                MenuScreen::Settings => spawn_settings_buttons(parent, ui_font, settings),
+            }
         });
 }
 
+fn spawn_main_buttons(parent: &mut ChildBuilder, ui_font: &UiFont) {
+    menu_core::make_button(MenuButton::Quit, parent, ui_font);
+    menu_core::make_button_custom_size(
+        MenuButton::Settings,
+        Size::new(Val::Px(200.0), Val::Px(65.0)),
+        parent,
+        ui_font,
+    );
+    menu_core::make_button(MenuButton::Play, parent, ui_font);
+    if load_last_played().is_some() {
+        menu_core::make_button_custom_size(
+            MenuButton::Continue,
+            Size::new(Val::Px(200.0), Val::Px(65.0)),
+            parent,
+            ui_font,
+        );
+    }
+}
+
+/// Every setting in a scrolling list, as there are too many to fit the window at once
+fn spawn_settings_buttons(parent: &mut ChildBuilder, ui_font: &UiFont, settings: &Settings) {
+    crate::menu_core::structure::split_unequal(
+        parent,
+        SplitWay::Vertical,
+        |parent| {
+            scroll_view(parent, |parent| {
+                for button in [
+                    MenuButton::TextSize,
+                    MenuButton::Volume(settings.volume),
+                    MenuButton::MusicVolume(settings.music_volume),
+                    MenuButton::ConfirmAttacks(settings.confirm_attacks),
+                    MenuButton::SwapMouseButtons(settings.swap_mouse_buttons),
+                    MenuButton::AutoAim(settings.auto_aim),
+                    MenuButton::Vibration(settings.vibration),
+                    MenuButton::EnemyTiming(settings.enemy_timing),
+                    MenuButton::Lighting(settings.lighting_intensity),
+                    MenuButton::SpectateEnemies(settings.spectate_enemies),
+                    MenuButton::ScreenShake(settings.screen_shake),
+                    MenuButton::PlayerHealthBar(settings.player_health_bar),
+                    MenuButton::Forgiveness(settings.death_keep_fraction),
+                    MenuButton::LastStand(settings.last_stand),
+                    MenuButton::CameraFollow(settings.camera_follow_mode),
+                    MenuButton::TileHoverLabel(settings.tile_hover_label),
+                    MenuButton::ReduceMotion(settings.reduce_motion),
+                    MenuButton::DeathReview(settings.death_review),
+                    MenuButton::StartPosition(settings.start_placement),
+                    MenuButton::TurnTick(settings.turn_tick_sound),
+                    MenuButton::RelaxMode(settings.relax_mode),
+                    MenuButton::PowerAiming(settings.power_aiming),
+                    MenuButton::MapGenReplay(settings.map_gen_replay),
+                    MenuButton::IntentionPreview(settings.intention_preview),
+                    MenuButton::HitStop(settings.hit_stop),
+                    MenuButton::DepositChests(settings.deposit_chests),
+                    MenuButton::IdleDemo(settings.idle_demo),
+                    MenuButton::Autosave(settings.autosave),
+                ] {
+                    menu_core::make_button_custom_size(
+                        button,
+                        Size::new(Val::Px(400.0), Val::Px(65.0)),
+                        parent,
+                        ui_font,
+                    );
+                }
+            });
+        },
+        |parent| {
+            menu_core::make_button(MenuButton::Back, parent, ui_font);
+        },
+        80.0,
+    );
+}
+
 fn menu_cleanup(q: Query<Entity, With<MenuOnly>>, mut commands: Commands) {
     for entity in q.iter() {
         commands.entity(entity).despawn_recursive();
@@ -1,8 +1,9 @@
+use log::warn;
 use rand::prelude::SliceRandom;
 use std::collections::{HashMap, VecDeque};
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CellMap<V>(pub HashMap<(i32, i32), V>);
 
 impl<V> CellMap<V> {
@@ -49,6 +50,77 @@ impl<V> CellMap<V> {
     pub fn contains(&self, cell: &(i32, i32)) -> bool {
         self.0.contains_key(cell)
     }
+
+    /// All cells in the map within [radius] (manhattan distance) of [center], including itself
+    pub fn cells_within(&self, center: (i32, i32), radius: i32) -> Vec<(i32, i32)> {
+        self.0
+            .keys()
+            .filter(|(x, y)| (x - center.0).abs() + (y - center.1).abs() <= radius)
+            .cloned()
+            .collect()
+    }
+
+    /// The cell in the map closest (manhattan distance) to [pos], ignoring any in [exclude]. Ties
+    /// go to the lowest position so the choice is stable
+    pub fn nearest_cell(&self, pos: (i32, i32), exclude: &[(i32, i32)]) -> Option<(i32, i32)> {
+        self.0
+            .keys()
+            .filter(|cell| !exclude.contains(cell))
+            .min_by_key(|(x, y)| ((x - pos.0).abs() + (y - pos.1).abs(), (*x, *y)))
+            .cloned()
+    }
+
+    /// The cell nearest the centroid of the whole map. The centroid itself can land in a wall on
+    /// an oddly shaped map, so it's the nearest actual cell. Ties go to the lowest position
+    pub fn central_cell(&self) -> Option<(i32, i32)> {
+        let count = self.0.len() as f32;
+        let (sum_x, sum_y) = self.0.keys().fold((0.0, 0.0), |(sum_x, sum_y), (x, y)| {
+            (sum_x + *x as f32, sum_y + *y as f32)
+        });
+        let centroid = (sum_x / count, sum_y / count);
+        let distance = |(x, y): &(i32, i32)| {
+            (*x as f32 - centroid.0).powi(2) + (*y as f32 - centroid.1).powi(2)
+        };
+        self.0
+            .keys()
+            .min_by(|a, b| {
+                distance(a)
+                    .partial_cmp(&distance(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.cmp(b))
+            })
+            .cloned()
+    }
+
+    /// Cells with exactly one orthogonal neighbour in the map, i.e. the ends of corridors
+    pub fn dead_ends(&self) -> Vec<(i32, i32)> {
+        self.0
+            .keys()
+            .filter(|(x, y)| {
+                super::map_gen::ORTHOG_NEIGHBOURS
+                    .into_iter()
+                    .filter(|(i, j)| self.0.contains_key(&(x + i, y + j)))
+                    .count()
+                    == 1
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Cells in a one wide straight corridor, with neighbours on opposite sides and nothing
+    /// either side of that, so anything standing in one blocks the way through
+    pub fn corridor_cells(&self) -> Vec<(i32, i32)> {
+        self.0
+            .keys()
+            .filter(|(x, y)| {
+                let open = |(i, j): (i32, i32)| self.0.contains_key(&(x + i, y + j));
+                let (up, down) = (open((0, 1)), open((0, -1)));
+                let (left, right) = (open((-1, 0)), open((1, 0)));
+                (up && down && !left && !right) || (left && right && !up && !down)
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 impl<V> CellMap<V>
@@ -107,6 +179,10 @@ impl CellMap<i32> {
         Self(new_self)
     }
 
+    /// Picks up to [n] cells, weighted toward the middle cost so points land away from both the
+    /// start and the far end. If there aren't enough cells strictly between the min and max cost,
+    /// it tops up from the max cost cells then the min cost ones. Returns fewer than [n] only when
+    /// the map has no more cells to give
     pub fn distribute_points_by_cost(
         &self,
         n: usize,
@@ -115,20 +191,23 @@ impl CellMap<i32> {
         // Find min, max, and mid cost
         // Fetch all cells where min < cost < max (i.e drop min/max)
         // pick with weigh: 1/ distance from mid
-        // TODO: Error handle here.
-        let min_cost = self.0.values().min().cloned().unwrap();
-        let max_cost = self.0.values().max().cloned().unwrap();
+        let (min_cost, max_cost) = match (self.0.values().min(), self.0.values().max()) {
+            (Some(min_cost), Some(max_cost)) => (*min_cost, *max_cost),
+            _ => {
+                warn!("Cannot distribute points over an empty cell map");
+                return Vec::new();
+            }
+        };
         let mid_cost = min_cost + (max_cost - min_cost) / 2;
+        let not_excluded = |pos: &(i32, i32)| match exclude_points {
+            Some(excluded) => !excluded.contains(pos),
+            None => true,
+        };
         let positions: Vec<(i32, i32)> = self
             .0
             .iter()
             .filter_map(|(k, v)| {
-                let not_excluded = if let Some(excluded) = exclude_points {
-                    !excluded.contains(k)
-                } else {
-                    true
-                };
-                if *v > min_cost && *v < max_cost && not_excluded {
+                if *v > min_cost && *v < max_cost && not_excluded(k) {
                     Some(*k)
                 } else {
                     None
@@ -136,17 +215,170 @@ impl CellMap<i32> {
             })
             .collect();
 
-        println!("{:?}", positions);
-
+        // Every candidate weighs at least 1, so none are starved when asked for them all
         let weights = |pos: &(i32, i32)| match self.0.get(pos) {
-            Some(val) => mid_cost - (mid_cost - val).abs(),
-            None => 0,
+            Some(val) => (mid_cost - (mid_cost - val).abs()).max(1),
+            None => 1,
         };
         let mut rng = rand::thread_rng();
-        positions
-            .choose_multiple_weighted(&mut rng, n, weights)
-            .unwrap()
-            .cloned()
-            .collect()
+        let mut chosen: Vec<(i32, i32)> =
+            match positions.choose_multiple_weighted(&mut rng, n.min(positions.len()), weights) {
+                Ok(chosen) => chosen.cloned().collect(),
+                Err(e) => {
+                    warn!("Unable to weight {} positions: {}", positions.len(), e);
+                    Vec::new()
+                }
+            };
+
+        if chosen.len() < n {
+            // Highest cost first keeps the top up as far from the start as possible, and sorting
+            // means the top up doesn't depend on hash order
+            let mut top_up: Vec<((i32, i32), i32)> = self
+                .0
+                .iter()
+                .filter(|(k, v)| {
+                    (**v == min_cost || **v == max_cost) && not_excluded(k) && !chosen.contains(k)
+                })
+                .map(|(k, v)| (*k, *v))
+                .collect();
+            top_up.sort_by_key(|(pos, cost)| (-cost, *pos));
+            let needed = n - chosen.len();
+            chosen.extend(top_up.into_iter().take(needed).map(|(pos, _)| pos));
+        }
+        if chosen.len() < n {
+            warn!(
+                "Only able to distribute {} of {} requested points",
+                chosen.len(),
+                n
+            );
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CellMap;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_cells_within() {
+        let mut m = HashMap::new();
+        for x in 0..5 {
+            for y in 0..5 {
+                m.insert((x, y), 0);
+            }
+        }
+        let cell_map = CellMap::new(m);
+        let mut cells = cell_map.cells_within((0, 0), 1);
+        cells.sort();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0)], cells);
+        assert_eq!(13, cell_map.cells_within((2, 2), 2).len());
+        assert_eq!(25, cell_map.cells_within((2, 2), 10).len());
+    }
+
+    #[test]
+    fn test_corridor_cells() {
+        // Two 3x3 rooms joined by a corridor along y = 1 from x = 3 to 5
+        let mut m = HashMap::new();
+        for x in (0..3).chain(6..9) {
+            for y in 0..3 {
+                m.insert((x, y), 0);
+            }
+        }
+        for x in 3..6 {
+            m.insert((x, 1), 0);
+        }
+        let cell_map = CellMap::new(m);
+        let mut cells = cell_map.corridor_cells();
+        cells.sort();
+        assert_eq!(vec![(3, 1), (4, 1), (5, 1)], cells);
+    }
+
+    #[test]
+    fn test_nearest_cell() {
+        let mut m = HashMap::new();
+        for x in 0..3 {
+            m.insert((x, 0), 0);
+        }
+        let cell_map = CellMap::new(m);
+        assert_eq!(Some((1, 0)), cell_map.nearest_cell((1, 0), &[]));
+        assert_eq!(Some((2, 0)), cell_map.nearest_cell((5, 1), &[]));
+        // Equally near (0, 0) and (2, 0), so the lower wins
+        assert_eq!(Some((0, 0)), cell_map.nearest_cell((1, 0), &[(1, 0)]));
+        assert_eq!(
+            None,
+            cell_map.nearest_cell((1, 0), &[(0, 0), (1, 0), (2, 0)])
+        );
+    }
+
+    #[test]
+    fn test_central_cell() {
+        let mut m = HashMap::new();
+        for x in 0..5 {
+            for y in 0..3 {
+                m.insert((x, y), 0);
+            }
+        }
+        assert_eq!(Some((2, 1)), CellMap::new(m).central_cell());
+        // The edge of a 5x5 square, whose centroid at (2, 2) is off the map. The middle of each
+        // side is as near as the others, so the lowest wins
+        let mut m = HashMap::new();
+        for i in 0..5 {
+            for cell in [(i, 0), (i, 4), (0, i), (4, i)] {
+                m.insert(cell, 0);
+            }
+        }
+        assert_eq!(Some((0, 2)), CellMap::new(m).central_cell());
+        assert_eq!(None, CellMap::<i32>::new(HashMap::new()).central_cell());
+    }
+
+    #[test]
+    fn test_distribute_points_beyond_capacity() {
+        // A corridor costing 0 to 3, so only (1, 0) and (2, 0) sit strictly between min and max
+        let mut m = HashMap::new();
+        for x in 0..4 {
+            m.insert((x, 0), x);
+        }
+        let cell_map = CellMap::new(m);
+
+        let mut points = cell_map.distribute_points_by_cost(3, None);
+        let top_up = points.pop();
+        points.sort();
+        assert_eq!(vec![(1, 0), (2, 0)], points);
+        // Topped up from the max cost end first
+        assert_eq!(Some((3, 0)), top_up);
+
+        let mut points = cell_map.distribute_points_by_cost(10, None);
+        points.sort();
+        assert_eq!(vec![(0, 0), (1, 0), (2, 0), (3, 0)], points);
+
+        let excluded = vec![(0, 0), (2, 0)];
+        let mut points = cell_map.distribute_points_by_cost(10, Some(&excluded));
+        points.sort();
+        assert_eq!(vec![(1, 0), (3, 0)], points);
+    }
+
+    #[test]
+    fn test_dead_ends() {
+        // A 3x3 room with a two cell corridor off its top right corner, ending at (4, 2)
+        let mut m = HashMap::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                m.insert((x, y), 0);
+            }
+        }
+        m.insert((3, 2), 0);
+        m.insert((4, 2), 0);
+        let cell_map = CellMap::new(m);
+        assert_eq!(vec![(4, 2)], cell_map.dead_ends());
+
+        // A room alone has no dead ends
+        let mut m = HashMap::new();
+        m.insert((0, 0), 0);
+        m.insert((0, 1), 0);
+        m.insert((1, 0), 0);
+        m.insert((1, 1), 0);
+        assert!(CellMap::new(m).dead_ends().is_empty());
     }
 }
@@ -1,54 +1,51 @@
 use super::cell_map::CellMap;
 use crate::game::components::TileType;
-use array2d::Array2D;
-use log::info;
+use bevy::utils::Instant;
+use log::{debug, info};
 
 use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+pub const DEFAULT_GRID_SIZE: (i32, i32) = (20, 20);
+/// Side length of the open room used by [fallback_cell_map]
+const FALLBACK_MAP_SIZE: i32 = 6;
 
 #[derive(Debug)]
-enum AbSelector {
-    A,
-    B,
+pub enum MapGenError {
+    /// No attempt produced a connected area of at least `min_size` cells
+    TooSmall { min_size: usize, max_tries: i32 },
 }
-impl AbSelector {
-    fn not(&self) -> Self {
+
+impl fmt::Display for MapGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            AbSelector::A => AbSelector::B,
-            AbSelector::B => AbSelector::A,
+            Self::TooSmall {
+                min_size,
+                max_tries,
+            } => write!(
+                f,
+                "Unable to generate a map of at least {} cells within {} attempts",
+                min_size, max_tries
+            ),
         }
     }
 }
-#[derive(Clone, Debug)]
-pub struct AB<T: Clone> {
-    a: T,
-    b: T,
-}
-
-impl<T: Clone> AB<T> {
-    fn new(v: T) -> Self {
-        Self { a: v.clone(), b: v }
-    }
 
-    fn get(&self, selector: &AbSelector) -> &T {
-        match selector {
-            AbSelector::A => &self.a,
-            AbSelector::B => &self.b,
-        }
-    }
-    fn set(&mut self, selector: &AbSelector, v: T) {
-        match selector {
-            AbSelector::A => self.a = v,
-            AbSelector::B => self.b = v,
-        }
-    }
-}
+impl std::error::Error for MapGenError {}
 
-#[derive(Debug)]
+/// Grid used for cellular automata generation. Cells are stored flat (row major) with a second
+/// buffer of the same size that each [update] pass writes into before the two are swapped, so no
+/// allocation happens between passes
+#[derive(Debug, Clone)]
 pub struct Grid {
     pub grid_size: (i32, i32),
-    pub grid: Array2D<AB<TileType>>,
-    current: AbSelector,
+    cells: Vec<TileType>,
+    back_buffer: Vec<TileType>,
+    // Flat indices of each cell's in-bounds neighbours, along with how many of the array are valid
+    neighbours: Vec<([usize; 9], usize)>,
 }
 
 fn pos_is_valid(pos: (i32, i32), grid_size: (i32, i32)) -> bool {
@@ -70,59 +67,88 @@ const NEIGHBOURS: [(i32, i32); 9] = [
     (-1, -1),
 ];
 
-fn pos_as_usize(p: (i32, i32)) -> (usize, usize) {
-    (p.0 as usize, p.1 as usize)
+fn flat_index(pos: (i32, i32), grid_size: (i32, i32)) -> usize {
+    (pos.1 * grid_size.0 + pos.0) as usize
+}
+
+fn neighbour_cache(grid_size: (i32, i32)) -> Vec<([usize; 9], usize)> {
+    let mut cache = Vec::with_capacity((grid_size.0 * grid_size.1) as usize);
+    for y in 0i32..grid_size.1 {
+        for x in 0i32..grid_size.0 {
+            let mut indices = [0usize; 9];
+            let mut valid = 0;
+            for (i, j) in NEIGHBOURS.iter() {
+                let pos = (x + i, y + j);
+                if pos_is_valid(pos, grid_size) {
+                    indices[valid] = flat_index(pos, grid_size);
+                    valid += 1;
+                }
+            }
+            cache.push((indices, valid));
+        }
+    }
+    cache
 }
 
 impl Grid {
     pub fn new(grid_size: (i32, i32)) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::with_rng(grid_size, &mut rand::thread_rng())
+    }
+
+    /// As [Grid::new], with the initial fill drawn from [rng]
+    pub fn with_rng(grid_size: (i32, i32), rng: &mut impl Rng) -> Self {
+        let cell_count = (grid_size.0 * grid_size.1) as usize;
+        let mut grid = Self {
+            grid_size,
+            cells: vec![TileType::WALL; cell_count],
+            back_buffer: vec![TileType::WALL; cell_count],
+            neighbours: neighbour_cache(grid_size),
+        };
+        grid.randomise(rng);
+        grid
+    }
+
+    /// Refill the grid with random tiles in place, ready for another generation attempt
+    pub fn randomise(&mut self, rng: &mut impl Rng) {
         let types = [TileType::WALL, TileType::WATER];
         let weights = |tile_type: &TileType| match tile_type {
             &TileType::WATER => 45,
             &TileType::WALL => 55,
         };
-        let random_tile_type = || {
-            let tt = types.choose_weighted(&mut rng, weights).unwrap().clone();
-            AB::new(tt)
-        };
-        let grid = Array2D::filled_by_row_major(
-            random_tile_type,
-            grid_size.1 as usize,
-            grid_size.0 as usize,
-        );
-        Self {
-            grid_size,
-            grid,
-            current: AbSelector::A,
+        for cell in self.cells.iter_mut() {
+            *cell = types.choose_weighted(rng, weights).unwrap().clone();
         }
     }
 
+    pub fn tile_type(&self, pos: (i32, i32)) -> TileType {
+        self.get(pos).clone()
+    }
+
+    fn get(&self, pos: (i32, i32)) -> &TileType {
+        &self.cells[flat_index(pos, self.grid_size)]
+    }
+
+    fn set(&mut self, pos: (i32, i32), tile_type: TileType) {
+        let index = flat_index(pos, self.grid_size);
+        self.cells[index] = tile_type;
+    }
+
     pub fn update(&mut self) {
-        for y in 0i32..self.grid_size.1 {
-            for x in 0i32..self.grid_size.0 {
-                let mut neighbour_count = 0;
-                for (i, j) in NEIGHBOURS.iter() {
-                    let pos = (x + i, y + j);
-                    if pos_is_valid(pos, self.grid_size) {
-                        match self.grid[pos_as_usize(pos)].get(&self.current) {
-                            TileType::WATER => (),
-                            TileType::WALL => neighbour_count += 1,
-                        }
-                    } else {
-                        // Maybe remove this?
-                        neighbour_count += 1;
-                    }
+        for (index, (neighbours, valid)) in self.neighbours.iter().enumerate() {
+            // Out of bounds neighbours count as walls
+            let mut neighbour_count = NEIGHBOURS.len() - valid;
+            for neighbour in neighbours[..*valid].iter() {
+                if self.cells[*neighbour] == TileType::WALL {
+                    neighbour_count += 1;
                 }
-                let new_type = if neighbour_count > 4 || neighbour_count == 0 {
-                    TileType::WALL
-                } else {
-                    TileType::WATER
-                };
-                self.grid[(x as usize, y as usize)].set(&self.current.not(), new_type);
             }
+            self.back_buffer[index] = if neighbour_count > 4 || neighbour_count == 0 {
+                TileType::WALL
+            } else {
+                TileType::WATER
+            };
         }
-        self.current = self.current.not();
+        std::mem::swap(&mut self.cells, &mut self.back_buffer);
     }
 
     fn draw_internal(&self, cost_map: &HashMap<(i32, i32), i32>) {
@@ -140,12 +166,7 @@ impl Grid {
                             String::from("!")
                         }
                     })
-                    .unwrap_or_else(|| {
-                        self.grid[(x as usize, y as usize)]
-                            .get(&self.current)
-                            .to_str()
-                            .to_string()
-                    });
+                    .unwrap_or_else(|| self.get((x, y)).to_str().to_string());
 
                 print!("{}", print_str)
             }
@@ -166,7 +187,7 @@ impl Grid {
             for j in 0..self.grid_size.1 {
                 let x = (x0 as isize + to_half_signed(i as isize)) as usize;
                 let y = y0 as usize - (j as usize);
-                if self.grid[(x, y)].get(&self.current) == &TileType::WATER {
+                if self.get((x as i32, y as i32)) == &TileType::WATER {
                     return Some((x as i32, y as i32));
                 }
             }
@@ -177,7 +198,7 @@ impl Grid {
 
     fn pos_is_valid_and(&self, pos: (i32, i32), and: TileType) -> bool {
         if pos_is_valid(pos, self.grid_size) {
-            self.grid[(pos.0 as usize, pos.1 as usize)].get(&self.current) == &and
+            self.get(pos) == &and
         } else {
             false
         }
@@ -212,22 +233,24 @@ impl Grid {
             }
         }
 
-        println!("Walked: {:?}", distance_map);
-        self.draw_internal(&distance_map);
+        debug!("Walked: {:?}", distance_map);
+        if log::log_enabled!(log::Level::Debug) {
+            self.draw_internal(&distance_map);
+        }
 
         // Cull any Water cells not in Distance Map
         let reachable_cells: HashSet<(i32, i32)> = distance_map.keys().cloned().collect();
         for i in 0..self.grid_size.0 {
             for j in 0..self.grid_size.1 {
-                if self.grid[pos_as_usize((i, j))].get(&self.current) == &TileType::WATER
-                    && !reachable_cells.contains(&(i, j))
-                {
-                    self.grid[pos_as_usize((i, j))].set(&self.current, TileType::WALL);
+                if self.get((i, j)) == &TileType::WATER && !reachable_cells.contains(&(i, j)) {
+                    self.set((i, j), TileType::WALL);
                 }
             }
         }
 
-        self.draw_internal(&distance_map);
+        if log::log_enabled!(log::Level::Debug) {
+            self.draw_internal(&distance_map);
+        }
 
         distance_map
     }
@@ -238,34 +261,183 @@ fn to_half_signed(i: isize) -> isize {
     (i / 2) * sign
 }
 
-pub fn get_cell_map(min_size: usize, max_tries: i32) -> CellMap<i32> {
-    for _i in 0..max_tries {
-        let map = run_single(min_size);
+pub fn get_cell_map(min_size: usize, max_tries: i32) -> Result<CellMap<i32>, MapGenError> {
+    get_cell_map_sized(DEFAULT_GRID_SIZE, min_size, max_tries)
+}
+
+pub fn get_cell_map_sized(
+    grid_size: (i32, i32),
+    min_size: usize,
+    max_tries: i32,
+) -> Result<CellMap<i32>, MapGenError> {
+    generate(
+        grid_size,
+        min_size,
+        max_tries,
+        &mut rand::thread_rng(),
+        None,
+    )
+}
+
+/// As [get_cell_map], but the same [seed] always gives the same map
+pub fn get_cell_map_seeded(
+    min_size: usize,
+    max_tries: i32,
+    seed: u64,
+) -> Result<CellMap<i32>, MapGenError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    generate(DEFAULT_GRID_SIZE, min_size, max_tries, &mut rng, None)
+}
+
+/// As [get_cell_map], but also returns the successful attempt's grid at each stage: the initial
+/// random fill, after each automata pass, and after culling unreachable cells
+pub fn get_cell_map_with_history(
+    min_size: usize,
+    max_tries: i32,
+) -> Result<(CellMap<i32>, Vec<Grid>), MapGenError> {
+    let mut history = Vec::new();
+    let cell_map = generate(
+        DEFAULT_GRID_SIZE,
+        min_size,
+        max_tries,
+        &mut rand::thread_rng(),
+        Some(&mut history),
+    )?;
+    Ok((cell_map, history))
+}
+
+/// A small hand-built open room, always valid, for when generation fails outright. Costs count
+/// out from the bottom left corner so it has a start point and room to place things
+pub fn fallback_cell_map() -> CellMap<i32> {
+    let cells = (0..FALLBACK_MAP_SIZE)
+        .flat_map(|x| (0..FALLBACK_MAP_SIZE).map(move |y| ((x, y), 0)))
+        .collect();
+    CellMap::new(cells).recalculate((0, 0))
+}
+
+fn generate(
+    grid_size: (i32, i32),
+    min_size: usize,
+    max_tries: i32,
+    rng: &mut impl Rng,
+    mut history: Option<&mut Vec<Grid>>,
+) -> Result<CellMap<i32>, MapGenError> {
+    // The grid (and its buffers) is reused across attempts, only being re-randomised each time
+    let mut grid = Grid::with_rng(grid_size, rng);
+    for i in 0..max_tries {
+        let attempt_start = Instant::now();
+        if let Some(history) = history.as_mut() {
+            history.clear();
+        }
+        let map = run_single(&mut grid, min_size, history.as_deref_mut());
+        debug!(
+            "Map generation attempt {} took {:?}",
+            i,
+            attempt_start.elapsed()
+        );
         if let Some(valid_map) = map {
-            return valid_map;
+            info!("Generated map after {} attempt(s)", i + 1);
+            return Ok(valid_map);
         }
+        grid.randomise(rng);
     }
-    panic!(
-        "Unable to generate big enough cell map within [max_tries]({})",
-        max_tries
-    );
+    Err(MapGenError::TooSmall {
+        min_size,
+        max_tries,
+    })
 }
 
-fn run_single(min_size: usize) -> Option<CellMap<i32>> {
-    let mut grid = Grid::new((20, 20));
-    //grid.draw();
+fn run_single(
+    grid: &mut Grid,
+    min_size: usize,
+    mut history: Option<&mut Vec<Grid>>,
+) -> Option<CellMap<i32>> {
+    if let Some(history) = history.as_mut() {
+        history.push(grid.clone());
+    }
     for _i in 0..6 {
         grid.update();
+        if let Some(history) = history.as_mut() {
+            history.push(grid.clone());
+        }
     }
-    //grid.draw();
     let start = grid.find_start()?;
-    //println!("Start: {:?}", start);
     let cell_map = CellMap::new(grid.map_and_cull(start));
+    if let Some(history) = history {
+        history.push(grid.clone());
+    }
     if cell_map.cell_count() < min_size {
         return None;
     }
-    //println!("cell_map: {:?}", cell_map);
     let normalised_cell_map = cell_map.normalise();
-    //println!("normalise_cell_map: {:?}", normalised_cell_map);
     Some(normalised_cell_map)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        fallback_cell_map, get_cell_map_seeded, get_cell_map_sized, get_cell_map_with_history,
+        Grid, MapGenError,
+    };
+    use crate::game::components::TileType;
+
+    #[test]
+    fn test_update_keeps_buffers_sized() {
+        let mut grid = Grid::new((13, 7));
+        for _ in 0..6 {
+            grid.update();
+        }
+        assert_eq!(13 * 7, grid.cells.len());
+        assert_eq!(13 * 7, grid.back_buffer.len());
+        // Corners always see 5 out of bounds neighbours so must end up as walls
+        assert_eq!(&TileType::WALL, grid.get((0, 0)));
+        assert_eq!(&TileType::WALL, grid.get((12, 6)));
+    }
+
+    #[test]
+    fn test_generation_meets_min_size() {
+        for _ in 0..10 {
+            let cell_map = get_cell_map_sized((30, 30), 50, 50).unwrap();
+            assert!(cell_map.cell_count() >= 50);
+        }
+    }
+
+    #[test]
+    fn test_impossible_min_size_is_an_error() {
+        let result = get_cell_map_sized((5, 5), 100, 3);
+        assert!(matches!(
+            result,
+            Err(MapGenError::TooSmall {
+                min_size: 100,
+                max_tries: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_same_seed_same_map() {
+        let first = get_cell_map_seeded(50, 50, 1234).unwrap();
+        let second = get_cell_map_seeded(50, 50, 1234).unwrap();
+        assert_eq!(first.0, second.0);
+    }
+
+    #[test]
+    fn test_fallback_map_can_place_points() {
+        let cell_map = fallback_cell_map();
+        assert_eq!(Some((0, 0)), cell_map.start_point());
+        assert_eq!(3, cell_map.distribute_points_by_cost(3, None).len());
+    }
+
+    #[test]
+    fn test_history_ends_with_culled_grid() {
+        let (cell_map, history) = get_cell_map_with_history(50, 50).unwrap();
+        // Initial fill, six automata passes, then the cull
+        assert_eq!(8, history.len());
+        let last = history.last().unwrap();
+        let water_count = (0..last.grid_size.0)
+            .flat_map(|x| (0..last.grid_size.1).map(move |y| (x, y)))
+            .filter(|pos| last.tile_type(*pos) == TileType::WATER)
+            .count();
+        assert_eq!(cell_map.cell_count(), water_count);
+    }
+}
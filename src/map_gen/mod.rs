@@ -1,4 +1,9 @@
 pub mod cell_map;
 mod map_gen;
+mod render;
 
-pub use map_gen::get_cell_map;
+pub use map_gen::{
+    fallback_cell_map, get_cell_map, get_cell_map_seeded, get_cell_map_sized,
+    get_cell_map_with_history, Grid, MapGenError,
+};
+pub use render::{export_map_image, render_cell_map_to_image};
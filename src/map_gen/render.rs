@@ -0,0 +1,137 @@
+//! Renders a [CellMap] to a small image, for sharing what a level looks like
+
+use super::cell_map::CellMap;
+use image::{Rgba, RgbaImage};
+
+const WALL_COLOUR: Rgba<u8> = Rgba([40, 32, 24, 255]);
+const WATER_COLOUR: Rgba<u8> = Rgba([48, 112, 200, 255]);
+const START_COLOUR: Rgba<u8> = Rgba([80, 220, 80, 255]);
+const EXIT_COLOUR: Rgba<u8> = Rgba([230, 60, 60, 255]);
+
+/// Draws each cell of [cell_map] as a [pixels_per_cell] square, water over a wall background,
+/// with the start (lowest cost) and exit (highest cost) cells marked. The image covers the map's
+/// bounding box, with up on the map up in the image
+pub fn render_cell_map_to_image(cell_map: &CellMap<i32>, pixels_per_cell: u32) -> RgbaImage {
+    let cells = cell_map.get_all_cells();
+    let x_min = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let x_max = cells.iter().map(|(x, _)| *x).max().unwrap_or(-1);
+    let y_min = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let y_max = cells.iter().map(|(_, y)| *y).max().unwrap_or(-1);
+    let width = (x_max - x_min + 1) as u32;
+    let height = (y_max - y_min + 1) as u32;
+
+    let mut image = RgbaImage::from_pixel(
+        width * pixels_per_cell,
+        height * pixels_per_cell,
+        WALL_COLOUR,
+    );
+    let start = cell_map.start_point();
+    let exit = cell_map.end_point();
+    for cell in cells.iter() {
+        let colour = if Some(*cell) == start {
+            START_COLOUR
+        } else if Some(*cell) == exit {
+            EXIT_COLOUR
+        } else {
+            WATER_COLOUR
+        };
+        // Image rows count down from the top, map rows count up from the bottom
+        let left = (cell.0 - x_min) as u32 * pixels_per_cell;
+        let top = (y_max - cell.1) as u32 * pixels_per_cell;
+        for x in left..left + pixels_per_cell {
+            for y in top..top + pixels_per_cell {
+                image.put_pixel(x, y, colour);
+            }
+        }
+    }
+    image
+}
+
+/// Exports [image] for sharing, returning where it went: saved as `saves/[name].png` natively,
+/// or as a data URL on the web where there's nowhere to save it
+pub fn export_map_image(image: &RgbaImage, name: &str) -> Result<String, String> {
+    platform_export::export(image, name)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform_export {
+    use image::RgbaImage;
+
+    pub fn export(image: &RgbaImage, name: &str) -> Result<String, String> {
+        let filename = format!("saves/{}.png", name);
+        image.save(&filename).map_err(|e| e.to_string())?;
+        Ok(filename)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform_export {
+    use super::base64;
+    use image::{DynamicImage, ImageOutputFormat, RgbaImage};
+    use std::io::Cursor;
+
+    pub fn export(image: &RgbaImage, _name: &str) -> Result<String, String> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(image.clone())
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(format!("data:image/png;base64,{}", base64(&png)))
+    }
+}
+
+#[cfg(any(target_arch = "wasm32", test))]
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64, to save pulling in a crate just for the web export
+#[cfg(any(target_arch = "wasm32", test))]
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base64, render_cell_map_to_image};
+    use crate::map_gen::cell_map::CellMap;
+
+    #[test]
+    fn test_image_matches_map_size() {
+        let cell_map = CellMap::new(
+            (3..13)
+                .flat_map(|x| (5..7).map(move |y| ((x, y), x)))
+                .collect(),
+        );
+        let image = render_cell_map_to_image(&cell_map, 4);
+        assert_eq!((40, 8), image.dimensions());
+    }
+
+    #[test]
+    fn test_base64_known_vectors() {
+        // From RFC 4648, covering each amount of padding
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYg==", base64(b"foob"));
+        assert_eq!("Zm9vYmE=", base64(b"fooba"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+        // The top of the alphabet
+        assert_eq!("+/7/", base64(&[0xfb, 0xfe, 0xff]));
+    }
+}
@@ -0,0 +1,87 @@
+//! A menu background that drifts slowly about, on a node a little bigger than the menu so no edge
+//! ever shows. Add [animated_background_system] to the state using it. Held still when
+//! [Settings::reduce_motion] is on
+//!
+//! The background is its own node rather than the menu root's image so moving it leaves the
+//! buttons alone
+
+use crate::profiles::settings::Settings;
+use bevy::prelude::*;
+
+/// Percent of the menu the background overhangs it on each side, and so the furthest it drifts
+const OVERSCAN: f32 = 4.0;
+/// Radians per second of the drift across and up and down. Different so the path doesn't repeat
+/// too obviously
+const DRIFT_SPEED: Vec2 = Vec2::new(0.11, 0.07);
+
+/// On the background node spawned by [animated_background]
+#[derive(Component, Debug)]
+pub struct AnimatedBackground;
+
+/// Spawns [image] filling [parent] and overhanging it by [OVERSCAN]. Spawn it as the first child
+/// so it's drawn behind the rest of the menu
+pub fn animated_background(parent: &mut ChildBuilder, image: Handle<Image>) -> Entity {
+    parent
+        .spawn_bundle(ImageBundle {
+            style: Style {
+                size: Size::new(
+                    Val::Percent(100.0 + OVERSCAN * 2.0),
+                    Val::Percent(100.0 + OVERSCAN * 2.0),
+                ),
+                position_type: PositionType::Absolute,
+                position: background_position(Vec2::ZERO),
+                ..Default::default()
+            },
+            image: UiImage(image),
+            ..Default::default()
+        })
+        .insert(AnimatedBackground)
+        .id()
+}
+
+/// Offset in percent of the menu at [seconds] in, each axis swinging between +/- [OVERSCAN]
+pub fn drift_offset(seconds: f32) -> Vec2 {
+    Vec2::new(
+        (seconds * DRIFT_SPEED.x).sin(),
+        (seconds * DRIFT_SPEED.y).cos(),
+    ) * OVERSCAN
+}
+
+fn background_position(offset: Vec2) -> UiRect<Val> {
+    UiRect {
+        left: Val::Percent(offset.x - OVERSCAN),
+        top: Val::Percent(offset.y - OVERSCAN),
+        ..Default::default()
+    }
+}
+
+pub fn animated_background_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut background_query: Query<&mut Style, With<AnimatedBackground>>,
+) {
+    let offset = if !settings.reduce_motion {
+        drift_offset(time.seconds_since_startup() as f32)
+    } else if settings.is_changed() {
+        Vec2::ZERO
+    } else {
+        // Already settled, and newly spawned backgrounds start centred
+        return;
+    };
+    for mut style in background_query.iter_mut() {
+        style.position = background_position(offset);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{drift_offset, OVERSCAN};
+
+    #[test]
+    fn test_drift_stays_within_overscan() {
+        for second in 0..1000 {
+            let offset = drift_offset(second as f32);
+            assert!(offset.x.abs() <= OVERSCAN && offset.y.abs() <= OVERSCAN);
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::helpers::builders::WithSelf;
+use crate::menu_core::menu_core::text::UiFont;
 
-use bevy::asset::Handle;
 use bevy::ecs::entity::Entity;
 use bevy::ecs::prelude::{Changed, Query, With};
 use bevy::prelude::{
-    AlignItems, BuildChildren, Button, ButtonBundle, ChildBuilder, Color, Component, Font,
-    Interaction, JustifyContent, Style, Text, TextBundle, TextStyle, UiColor, Val,
+    AlignItems, BuildChildren, Button, ButtonBundle, ChildBuilder, Color, Component, Interaction,
+    JustifyContent, Style, Text, TextBundle, TextStyle, UiColor, Val,
 };
 use bevy::ui::{Size, UiRect};
 
@@ -52,23 +52,28 @@ pub trait ButtonComponent: Component {
 pub fn make_button<C>(
     button_component: C,
     parent: &mut ChildBuilder,
-    font: Handle<Font>,
+    ui_font: &UiFont,
 ) -> (Entity, Entity)
 where
     C: ButtonComponent,
 {
     let button_size = Size::new(Val::Px(150.0), Val::Px(65.0));
-    make_button_custom_size(button_component, button_size, parent, font)
+    make_button_custom_size(button_component, button_size, parent, ui_font)
 }
+/// `button_size` is given at a font scale of 1.0 and grows with the font so the text still fits
 pub fn make_button_custom_size<C>(
     button_component: C,
     button_size: Size<Val>,
     parent: &mut ChildBuilder,
-    font: Handle<Font>,
+    ui_font: &UiFont,
 ) -> (Entity, Entity)
 where
     C: ButtonComponent,
 {
+    let button_size = Size::new(
+        ui_font.scaled_val(button_size.width),
+        ui_font.scaled_val(button_size.height),
+    );
     let mut button_entity = None;
     let mut text_entity = None;
     let text = button_component.to_text();
@@ -101,9 +106,8 @@ where
                     text: Text::from_section(
                         text,
                         TextStyle {
-                            font,
-                            font_size: 40.0,
                             color: Color::rgb(0.9, 0.9, 0.9),
+                            ..ui_font.text_style(40.0)
                         },
                     ),
                     ..Default::default()
@@ -116,8 +120,50 @@ where
 pub mod text {
     use crate::helpers::builders::WithSelf;
     use crate::menu_core::helpers::RectExt;
+    use crate::profiles::settings::Settings;
     use bevy::prelude::*;
 
+    /// The font used by all UI text, along with the user's font size multiplier which every text
+    /// size is scaled by
+    pub struct UiFont {
+        pub font: Handle<Font>,
+        pub scale: f32,
+    }
+
+    impl FromWorld for UiFont {
+        fn from_world(world: &mut World) -> Self {
+            let font = world
+                .resource::<AssetServer>()
+                .load("fonts/bigfish/Bigfish.ttf");
+            Self {
+                font,
+                scale: world.resource::<Settings>().font_scale,
+            }
+        }
+    }
+
+    impl UiFont {
+        pub fn scaled(&self, font_size: f32) -> f32 {
+            font_size * self.scale
+        }
+
+        /// Scales pixel values only, percentages and auto are left to the layout
+        pub fn scaled_val(&self, val: Val) -> Val {
+            match val {
+                Val::Px(px) => Val::Px(self.scaled(px)),
+                other => other,
+            }
+        }
+
+        pub fn text_style(&self, font_size: f32) -> TextStyle {
+            TextStyle {
+                font: self.font.clone(),
+                font_size: self.scaled(font_size),
+                color: Color::WHITE,
+            }
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TextNodes {
         pub node: Entity,
@@ -127,7 +173,7 @@ pub mod text {
     pub fn standard_centred_text_custom(
         builder: &mut ChildBuilder,
         text: impl Into<String>,
-        font: Handle<Font>,
+        ui_font: &UiFont,
         font_size: f32,
         color: Color,
     ) -> TextNodes {
@@ -137,7 +183,7 @@ pub mod text {
             .spawn_bundle(NodeBundle {
                 style: Style {
                     // center button
-                    size: Size::new(Val::Auto, Val::Px(font_size + 2.0)),
+                    size: Size::new(Val::Auto, Val::Px(ui_font.scaled(font_size) + 2.0)),
                     margin: super::rect_consts::CENTRED,
                     padding: UiRect::new_2(Val::Px(100.0), Val::Percent(0.0)),
                     // horizontally center child text
@@ -156,9 +202,8 @@ pub mod text {
                         text: Text::from_section(
                             text,
                             TextStyle {
-                                font,
-                                font_size: 40.0,
                                 color,
+                                ..ui_font.text_style(font_size)
                             },
                         )
                         .with_alignment(TextAlignment {
@@ -177,9 +222,9 @@ pub mod text {
     pub fn standard_centred_text(
         builder: &mut ChildBuilder,
         text: impl Into<String>,
-        font: Handle<Font>,
+        ui_font: &UiFont,
     ) -> TextNodes {
         let font_size = 40.0;
-        standard_centred_text_custom(builder, text, font, font_size, Color::rgb(0.0, 0.0, 0.0))
+        standard_centred_text_custom(builder, text, ui_font, font_size, Color::rgb(0.0, 0.0, 0.0))
     }
 }
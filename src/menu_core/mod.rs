@@ -1,4 +1,7 @@
+pub mod animated_background;
 pub mod helpers;
 pub mod menu_core;
 //pub mod nodes;
+pub mod scroll_view;
 pub mod structure;
+pub mod text_input;
@@ -0,0 +1,92 @@
+//! A clipped area whose content scrolls with the mouse wheel or the up and down arrow keys, for
+//! content that can outgrow its space. Add [scroll_view_system] to the state using it
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+/// Pixels scrolled per line of mouse wheel movement
+const LINE_HEIGHT: f32 = 20.0;
+/// Pixels per second scrolled while an arrow key is held
+const KEY_SCROLL_SPEED: f32 = 400.0;
+
+/// On the moving content node of a scroll view, see [scroll_view]
+#[derive(Component, Debug, Default)]
+pub struct ScrollView {
+    /// How far the content has moved, from 0 at the top to minus the overflowing height
+    position: f32,
+}
+
+/// Spawns a node filling [parent] that clips whatever [build_content] adds, stacked top down,
+/// with a [ScrollView] to move it. Returns the content node
+pub fn scroll_view(
+    parent: &mut ChildBuilder,
+    build_content: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    let mut content = None;
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            color: UiColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            content = Some(
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::ColumnReverse,
+                            flex_grow: 1.0,
+                            max_size: Size::new(Val::Undefined, Val::Undefined),
+                            ..Default::default()
+                        },
+                        color: UiColor(Color::NONE),
+                        ..Default::default()
+                    })
+                    .insert(ScrollView::default())
+                    .with_children(build_content)
+                    .id(),
+            );
+        });
+    content.unwrap()
+}
+
+pub fn scroll_view_system(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut scroll_query: Query<(&mut ScrollView, &mut Style, &Children, &Node)>,
+    node_query: Query<&Node>,
+) {
+    // Positive scrolls the content down, towards its top
+    let mut scroll: f32 = mouse_wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if input.pressed(KeyCode::Up) {
+        scroll += KEY_SCROLL_SPEED * time.delta_seconds();
+    }
+    if input.pressed(KeyCode::Down) {
+        scroll -= KEY_SCROLL_SPEED * time.delta_seconds();
+    }
+    if scroll == 0.0 {
+        return;
+    }
+    for (mut scroll_view, mut style, children, node) in scroll_query.iter_mut() {
+        let content_height: f32 = children
+            .iter()
+            .filter_map(|child| node_query.get(*child).ok())
+            .map(|child_node| child_node.size.y)
+            .sum();
+        let max_scroll = (content_height - node.size.y).max(0.0);
+        scroll_view.position = (scroll_view.position + scroll).clamp(-max_scroll, 0.0);
+        style.position.top = Val::Px(scroll_view.position);
+    }
+}
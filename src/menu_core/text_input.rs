@@ -0,0 +1,125 @@
+//! A single line text box typed into from the keyboard, with a blinking cursor. Spawn one with
+//! [TextInput::create] and add [TextInput::system] to the state using it
+
+use crate::menu_core::menu_core::text::{standard_centred_text, TextNodes, UiFont};
+use bevy::prelude::*;
+use bevy_ui_nodes::HeightOrWidth;
+use log::info;
+use std::time::Duration;
+
+#[derive(Debug, Component)]
+pub struct TextInput {
+    text_nodes: TextNodes,
+    current_text: String,
+    cursor_on: bool,
+    timer: Timer,
+    /// Characters typed that fail this are ignored
+    valid_char: fn(&char) -> bool,
+}
+
+impl TextInput {
+    const MAX_LEN: usize = 32;
+    pub fn create(
+        parent: &mut ChildBuilder,
+        ui_font: &UiFont,
+        valid_char: fn(&char) -> bool,
+    ) -> Self {
+        let mut text_nodes = None;
+        parent
+            .spawn_bundle(bevy_ui_nodes::default_node::empty(
+                HeightOrWidth::Width,
+                FlexDirection::Row,
+                Some(vec![bevy_ui_nodes::Property::Colour(Color::GRAY)]),
+            ))
+            .with_children(|parent| {
+                text_nodes = Some(standard_centred_text(parent, "|", ui_font));
+            });
+
+        let timer = Timer::new(Duration::from_millis(500), true);
+        Self {
+            text_nodes: text_nodes.unwrap(),
+            current_text: String::new(),
+            cursor_on: true,
+            timer,
+            valid_char,
+        }
+    }
+
+    fn update(&self, text_query: &mut Query<&mut Text>) {
+        if let Ok(mut text) = text_query.get_mut(self.text_nodes.text) {
+            let cursor = if self.cursor_on { "|" } else { "" };
+            text.sections[0].value = format!("{}{}", self.current_text.clone(), cursor);
+        }
+    }
+
+    fn add_char(&mut self, char: char, text_query: &mut Query<&mut Text>) {
+        if self.current_text.len() < Self::MAX_LEN {
+            self.current_text.push(char);
+            info!("Appending: {:?}. Result: {}", char, self.current_text);
+            self.update(text_query);
+        }
+    }
+
+    fn backspace(&mut self, text_query: &mut Query<&mut Text>) {
+        if !self.current_text.is_empty() {
+            self.current_text.pop();
+            info!("Backspace. Result: {}", self.current_text);
+            self.update(text_query);
+        }
+    }
+
+    /// Empties the text, returning what it held
+    pub fn take(&mut self, text_query: &mut Query<&mut Text>) -> String {
+        let text = std::mem::take(&mut self.current_text);
+        self.update(text_query);
+        text
+    }
+
+    pub fn system(
+        mut received_character_events: EventReader<ReceivedCharacter>,
+        input_keys: Res<Input<KeyCode>>,
+        mut text_query: Query<&mut Text>,
+        mut self_query: Query<&mut Self>,
+        time: Res<Time>,
+    ) {
+        let mut text_input = match self_query.get_single_mut() {
+            Ok(text_input) => text_input,
+            Err(_) => {
+                // So nothing typed beforehand turns up once there is one
+                received_character_events.clear();
+                return;
+            }
+        };
+        for event in received_character_events.iter() {
+            if (text_input.valid_char)(&event.char) {
+                text_input.add_char(event.char, &mut text_query);
+            }
+        }
+
+        if input_keys.just_pressed(KeyCode::Back) {
+            text_input.backspace(&mut text_query);
+        }
+
+        text_input.timer.tick(time.delta());
+
+        if text_input.timer.just_finished() {
+            text_input.cursor_on = !text_input.cursor_on;
+            text_input.update(&mut text_query);
+        }
+    }
+
+    pub fn current_string_if_valid(&self) -> Option<&str> {
+        let len_ok = 2 <= self.current_text.len() && self.current_text.len() <= Self::MAX_LEN;
+        let chars_ok = {
+            self.current_text
+                .chars()
+                .map(|c| (self.valid_char)(&c))
+                .any(|x| x)
+        };
+        if len_ok && chars_ok {
+            Some(&self.current_text)
+        } else {
+            None
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod profiles;
+pub mod settings;
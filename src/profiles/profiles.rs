@@ -1,5 +1,8 @@
 use crate::asset_handling::asset::{ImageAsset, TextureAtlasAsset};
+use log::warn;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
 
 
 
@@ -31,6 +34,85 @@ pub struct UserProfile {
     pub level: usize,
     pub name: String,
     pub haddock_variant: HaddockVariant,
+    /// Most turns taken in a single run, across all its levels
+    #[serde(default)]
+    pub longest_run_turns: usize,
+    /// Starfish of Second Chances bought in the store, each carried into every level until used
+    #[serde(default)]
+    pub starfish: usize,
+    /// Bought in the store, the power carries on through targets it kills with damage to spare
+    #[serde(default)]
+    pub overkill_passthrough: bool,
+    /// Quickest run that ended hooked out rather than dead, in real time
+    #[serde(default)]
+    pub best_run_time: Option<Duration>,
+    /// Most levels cleared in a single endless run
+    #[serde(default)]
+    pub best_endless_streak: usize,
+    /// Furthest level any run has reached
+    #[serde(default)]
+    pub best_level_reached: usize,
+    /// Start runs with enemies as tough as at [UserProfile::best_level_reached], once unlocked
+    #[serde(default)]
+    pub new_game_plus: bool,
+    /// Adjust each level's enemies to how the last few went, see [PerformanceHistory]
+    #[serde(default)]
+    pub adaptive_difficulty: bool,
+    /// Kept whether or not [UserProfile::adaptive_difficulty] is on, so it has something to go
+    /// on as soon as it's turned on
+    #[serde(default)]
+    pub performance: PerformanceHistory,
+    /// Runs hooked out without the player killing a single enemy
+    #[serde(default)]
+    pub pacifist_runs: usize,
+    /// The route taken through the last run, for its ghost if the seed is retried
+    #[serde(default)]
+    pub seed_paths: Option<SeedPaths>,
+}
+
+/// How a single level went, ended by clearing it or dying on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LevelPerformance {
+    pub damage_taken: usize,
+    pub died: bool,
+    pub turns: usize,
+}
+
+impl LevelPerformance {
+    /// Cleared without a scratch in good time
+    fn is_clean(&self) -> bool {
+        !self.died && self.damage_taken == 0 && self.turns <= PerformanceHistory::QUICK_LEVEL_TURNS
+    }
+}
+
+/// The last few levels played, oldest first, for [UserProfile::adaptive_difficulty]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PerformanceHistory {
+    recent: Vec<LevelPerformance>,
+}
+
+impl PerformanceHistory {
+    /// Levels remembered, older ones drop off
+    pub const WINDOW: usize = 5;
+    /// Most turns a level can take and still count as clean
+    pub const QUICK_LEVEL_TURNS: usize = 40;
+    /// Furthest the difficulty is moved either way
+    pub const MAX_ADJUSTMENT: i32 = 2;
+
+    pub fn record(&mut self, level_performance: LevelPerformance) {
+        self.recent.push(level_performance);
+        if self.recent.len() > Self::WINDOW {
+            self.recent.remove(0);
+        }
+    }
+
+    /// Levels added to the difficulty, negative to ease off. Every two clean levels add one, each
+    /// death takes one off
+    pub fn difficulty_adjustment(&self) -> i32 {
+        let clean = self.recent.iter().filter(|level| level.is_clean()).count() as i32;
+        let deaths = self.recent.iter().filter(|level| level.died).count() as i32;
+        ((clean - deaths * 2) / 2).clamp(-Self::MAX_ADJUSTMENT, Self::MAX_ADJUSTMENT)
+    }
 }
 
 impl Default for UserProfile {
@@ -40,11 +122,83 @@ impl Default for UserProfile {
             level: 0,
             name: "Default".to_string(),
             haddock_variant: HaddockVariant::Normal,
+            longest_run_turns: 0,
+            starfish: 0,
+            overkill_passthrough: false,
+            best_run_time: None,
+            best_endless_streak: 0,
+            best_level_reached: 0,
+            new_game_plus: false,
+            adaptive_difficulty: false,
+            performance: PerformanceHistory::default(),
+            pacifist_runs: 0,
+            seed_paths: None,
+        }
+    }
+}
+
+/// The tiles the player crossed on each level of a run, see [crate::game::ghost_path]. Levels
+/// are generated from the run's seed, so these line up with the maps a retry of it plays
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SeedPaths {
+    pub seed: u64,
+    /// Per level, the first level's first
+    levels: Vec<Vec<(u32, u32)>>,
+}
+
+impl SeedPaths {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Replaces whatever was recorded for [level], counting from 1
+    pub fn record(&mut self, level: usize, path: Vec<(u32, u32)>) {
+        let index = level.saturating_sub(1);
+        if self.levels.len() <= index {
+            self.levels.resize(index + 1, Vec::new());
         }
+        self.levels[index] = path;
+    }
+
+    /// The path recorded for [level], counting from 1, empty if that level wasn't reached
+    pub fn level(&self, level: usize) -> &[(u32, u32)] {
+        level
+            .checked_sub(1)
+            .and_then(|index| self.levels.get(index))
+            .map_or(&[], Vec::as_slice)
     }
 }
 
 impl UserProfile {
+    /// Best level a run must have reached before new game plus can be turned on
+    pub const NEW_GAME_PLUS_MIN_LEVEL: usize = 8;
+
+    pub fn new_game_plus_unlocked(&self) -> bool {
+        self.best_level_reached >= Self::NEW_GAME_PLUS_MIN_LEVEL
+    }
+
+    /// Levels added to the difficulty of every level in a run, so with new game plus on the first
+    /// level plays like [UserProfile::best_level_reached]
+    pub fn difficulty_offset(&self) -> usize {
+        if self.new_game_plus && self.new_game_plus_unlocked() {
+            self.best_level_reached - 1
+        } else {
+            0
+        }
+    }
+
+    /// [PerformanceHistory::difficulty_adjustment] with adaptive difficulty on, otherwise 0
+    pub fn adaptive_adjustment(&self) -> i32 {
+        if self.adaptive_difficulty {
+            self.performance.difficulty_adjustment()
+        } else {
+            0
+        }
+    }
+
     pub fn max_power_charges(&self) -> usize {
         match self.level {
             0..=4 => 3,
@@ -54,6 +208,14 @@ impl UserProfile {
             _ => 7,
         }
     }
+    /// How many times the power's projectile ricochets off walls before stopping
+    pub fn projectile_bounces(&self) -> usize {
+        match self.level {
+            0..=9 => 0,
+            10..=19 => 1,
+            _ => 2,
+        }
+    }
     pub fn max_health(&self) -> usize {
         match self.level {
             0..=2 => 3,
@@ -77,6 +239,12 @@ pub struct LoadedUserProfile {
 impl LoadedUserProfile {
     pub fn save(&self) {
         platform_fs::save(self.file_index, &self.user_profile);
+        self.mark_last_played();
+    }
+
+    /// Records this as the profile the main menu's Continue picks up
+    pub fn mark_last_played(&self) {
+        platform_fs::save_last_played(self.file_index);
     }
 
     pub fn new(user_profile: UserProfile, file_index: usize) -> Self {
@@ -85,14 +253,37 @@ impl LoadedUserProfile {
             file_index,
         }
     }
+
+    pub fn file_index(&self) -> usize {
+        self.file_index
+    }
 }
 
 #[derive(Debug)]
 pub enum ProfileSlot {
     Loaded(LoadedUserProfile),
     Free(usize),
+    /// A save exists in this slot but could not be read
+    Corrupt(usize),
+}
+
+#[derive(Debug)]
+pub enum ProfileLoadError {
+    Read(String),
+    Parse(String),
 }
 
+impl fmt::Display for ProfileLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "Failed to read save: {}", e),
+            Self::Parse(e) => write!(f, "Failed to parse save: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProfileLoadError {}
+
 fn save_id_of_index(index: usize) -> String {
     format!("save_{:02}.ron", index)
 }
@@ -101,33 +292,83 @@ fn filename_of_index(index: usize) -> String {
     format!("saves/{}", save_id_of_index(index))
 }
 
+/// Where a corrupt save is moved to, so the slot can be reused without losing the data outright.
+/// Numbered by [attempt] past the first so earlier corrupt saves from the slot aren't overwritten
+fn corrupt_save_id_of_index(index: usize, attempt: usize) -> String {
+    match attempt {
+        0 => format!("{}.corrupt", save_id_of_index(index)),
+        attempt => format!("{}.corrupt.{}", save_id_of_index(index), attempt),
+    }
+}
+
 pub fn load_profiles_blocking() -> Vec<ProfileSlot> {
     let mut loaded_saves = Vec::new();
     for file_index in 0..MAX_SAVES {
-        if let Some(profile) = platform_fs::maybe_load(file_index) {
-            loaded_saves.push(ProfileSlot::Loaded(profile));
-        } else {
-            loaded_saves.push(ProfileSlot::Free(file_index));
+        match platform_fs::maybe_load(file_index) {
+            Ok(Some(profile)) => loaded_saves.push(ProfileSlot::Loaded(profile)),
+            Ok(None) => loaded_saves.push(ProfileSlot::Free(file_index)),
+            Err(e) => {
+                warn!("Save in slot {} is corrupt: {}", file_index, e);
+                loaded_saves.push(ProfileSlot::Corrupt(file_index));
+            }
         }
     }
     loaded_saves
 }
 
+/// The profile last saved or loaded, if it's still there and readable
+pub fn load_last_played() -> Option<LoadedUserProfile> {
+    let index = platform_fs::maybe_load_last_played()?;
+    match platform_fs::maybe_load(index) {
+        Ok(profile) => profile,
+        Err(e) => {
+            warn!("Last played save in slot {} can't be loaded: {}", index, e);
+            None
+        }
+    }
+}
+
+const LAST_PLAYED_ID: &str = "last_played.ron";
+
+/// Moves a corrupt save out of its slot, leaving the slot free, to the first of
+/// [corrupt_save_id_of_index] not already taken. On error the save is left where it is
+pub fn set_aside_corrupt_save(index: usize) -> Result<(), String> {
+    platform_fs::set_aside(index)
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod platform_fs {
-    use crate::profiles::profiles::{filename_of_index, LoadedUserProfile, UserProfile};
+    use crate::profiles::profiles::{
+        corrupt_save_id_of_index, filename_of_index, LoadedUserProfile, ProfileLoadError,
+        UserProfile, LAST_PLAYED_ID,
+    };
     use std::fs::File;
-    use std::io::{BufReader, BufWriter};
+    use std::io::{BufReader, BufWriter, ErrorKind};
+    use std::path::Path;
 
-    pub fn maybe_load(index: usize) -> Option<LoadedUserProfile> {
+    pub fn maybe_load(index: usize) -> Result<Option<LoadedUserProfile>, ProfileLoadError> {
         let filename = filename_of_index(index);
-        let file = File::open(filename).ok()?;
+        let file = match File::open(filename) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ProfileLoadError::Read(e.to_string())),
+        };
         let reader = BufReader::new(file);
-        let user_profile = ron::de::from_reader(reader).ok()?;
-        Some(LoadedUserProfile {
+        let user_profile =
+            ron::de::from_reader(reader).map_err(|e| ProfileLoadError::Parse(e.to_string()))?;
+        Ok(Some(LoadedUserProfile {
             user_profile,
             file_index: index,
-        })
+        }))
+    }
+
+    pub fn set_aside(index: usize) -> Result<(), String> {
+        let filename = filename_of_index(index);
+        let corrupt_filename = (0..)
+            .map(|attempt| format!("saves/{}", corrupt_save_id_of_index(index, attempt)))
+            .find(|corrupt_filename| !Path::new(corrupt_filename).exists())
+            .unwrap();
+        std::fs::rename(filename, corrupt_filename).map_err(|e| e.to_string())
     }
 
     pub fn save(index: usize, user_profile: &UserProfile) {
@@ -136,22 +377,79 @@ mod platform_fs {
         let writer = BufWriter::new(file);
         ron::ser::to_writer(writer, user_profile).unwrap();
     }
+
+    pub fn maybe_load_last_played() -> Option<usize> {
+        let file = File::open(format!("saves/{}", LAST_PLAYED_ID)).ok()?;
+        ron::de::from_reader(BufReader::new(file)).ok()
+    }
+
+    pub fn save_last_played(index: usize) {
+        match File::create(format!("saves/{}", LAST_PLAYED_ID)) {
+            Ok(file) => {
+                if let Err(e) = ron::ser::to_writer(BufWriter::new(file), &index) {
+                    log::warn!("Failed to write last played slot: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to create last played file: {}", e),
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
 mod platform_fs {
-    use crate::profiles::profiles::{save_id_of_index, LoadedUserProfile, UserProfile};
+    use crate::profiles::profiles::{
+        corrupt_save_id_of_index, save_id_of_index, LoadedUserProfile, ProfileLoadError,
+        UserProfile, LAST_PLAYED_ID,
+    };
 
-    pub fn maybe_load(index: usize) -> Option<LoadedUserProfile> {
+    fn local_storage() -> Option<web_sys::Storage> {
         let window: web_sys::Window = web_sys::window()?;
-        let local_storage: web_sys::Storage = window.local_storage().ok()??;
+        window.local_storage().ok()?
+    }
+
+    pub fn maybe_load(index: usize) -> Result<Option<LoadedUserProfile>, ProfileLoadError> {
+        let local_storage = match local_storage() {
+            Some(local_storage) => local_storage,
+            None => return Ok(None),
+        };
         let save_id = save_id_of_index(index);
-        let profile_entry = local_storage.get_item(&save_id).ok()??;
-        let user_profile = ron::de::from_str(&profile_entry).ok()?;
-        Some(LoadedUserProfile {
+        let profile_entry = match local_storage.get_item(&save_id) {
+            Ok(Some(profile_entry)) => profile_entry,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(ProfileLoadError::Read(format!("{:?}", e))),
+        };
+        let user_profile = ron::de::from_str(&profile_entry)
+            .map_err(|e| ProfileLoadError::Parse(e.to_string()))?;
+        Ok(Some(LoadedUserProfile {
             user_profile,
             file_index: index,
-        })
+        }))
+    }
+
+    pub fn set_aside(index: usize) -> Result<(), String> {
+        let local_storage = local_storage().ok_or_else(|| String::from("No local storage"))?;
+        let save_id = save_id_of_index(index);
+        // Only removed once it's safely copied
+        if let Some(profile_entry) = local_storage
+            .get_item(&save_id)
+            .map_err(|e| format!("{:?}", e))?
+        {
+            let mut attempt = 0;
+            while local_storage
+                .get_item(&corrupt_save_id_of_index(index, attempt))
+                .map_err(|e| format!("{:?}", e))?
+                .is_some()
+            {
+                attempt += 1;
+            }
+            let corrupt_save_id = corrupt_save_id_of_index(index, attempt);
+            local_storage
+                .set_item(&corrupt_save_id, &profile_entry)
+                .map_err(|e| format!("{:?}", e))?;
+        }
+        local_storage
+            .remove_item(&save_id)
+            .map_err(|e| format!("{:?}", e))
     }
 
     pub fn save(index: usize, user_profile: &UserProfile) {
@@ -161,4 +459,93 @@ mod platform_fs {
         let user_profile_ron = ron::ser::to_string(user_profile).unwrap();
         local_storage.set_item(&save_id, &user_profile_ron).unwrap();
     }
+
+    pub fn maybe_load_last_played() -> Option<usize> {
+        let entry = local_storage()?.get_item(LAST_PLAYED_ID).ok()??;
+        ron::de::from_str(&entry).ok()
+    }
+
+    pub fn save_last_played(index: usize) {
+        if let Some(local_storage) = local_storage() {
+            if let Ok(index_ron) = ron::ser::to_string(&index) {
+                let _ = local_storage.set_item(LAST_PLAYED_ID, &index_ron);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        corrupt_save_id_of_index, LevelPerformance, PerformanceHistory, SeedPaths, UserProfile,
+    };
+
+    #[test]
+    fn test_corrupt_save_ids_distinct() {
+        assert_eq!("save_01.ron.corrupt", corrupt_save_id_of_index(1, 0));
+        assert_eq!("save_01.ron.corrupt.2", corrupt_save_id_of_index(1, 2));
+    }
+
+    #[test]
+    fn test_difficulty_offset() {
+        let mut user_profile = UserProfile {
+            best_level_reached: UserProfile::NEW_GAME_PLUS_MIN_LEVEL - 1,
+            new_game_plus: true,
+            ..Default::default()
+        };
+        // Still locked, whatever the flag says
+        assert_eq!(0, user_profile.difficulty_offset());
+        user_profile.best_level_reached = 12;
+        assert_eq!(11, user_profile.difficulty_offset());
+        user_profile.new_game_plus = false;
+        assert_eq!(0, user_profile.difficulty_offset());
+    }
+
+    #[test]
+    fn test_difficulty_adjustment() {
+        let clean = LevelPerformance {
+            damage_taken: 0,
+            died: false,
+            turns: 20,
+        };
+        let death = LevelPerformance {
+            damage_taken: 4,
+            died: true,
+            turns: 20,
+        };
+        let mut history = PerformanceHistory::default();
+        assert_eq!(0, history.difficulty_adjustment());
+        history.record(clean);
+        assert_eq!(0, history.difficulty_adjustment());
+        for _ in 0..PerformanceHistory::WINDOW {
+            history.record(clean);
+        }
+        assert_eq!(2, history.difficulty_adjustment());
+        // Slow or hurt levels don't count as clean
+        history.record(LevelPerformance {
+            turns: 100,
+            ..clean
+        });
+        history.record(LevelPerformance {
+            damage_taken: 1,
+            ..clean
+        });
+        assert_eq!(1, history.difficulty_adjustment());
+        for _ in 0..3 {
+            history.record(death);
+        }
+        assert_eq!(-2, history.difficulty_adjustment());
+    }
+
+    #[test]
+    fn test_seed_paths() {
+        let mut seed_paths = SeedPaths::new(7);
+        seed_paths.record(2, vec![(1, 1), (1, 2)]);
+        assert!(seed_paths.level(1).is_empty());
+        assert_eq!(&[(1, 1), (1, 2)], seed_paths.level(2));
+        assert!(seed_paths.level(3).is_empty());
+        // A retry's path replaces the one before
+        seed_paths.record(2, vec![(3, 3)]);
+        assert_eq!(&[(3, 3)], seed_paths.level(2));
+    }
 }
@@ -0,0 +1,457 @@
+use crate::game::key_bindings::KeyBindings;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Font size multipliers the text size setting cycles through
+pub const FONT_SCALES: [f32; 3] = [1.0, 1.25, 1.5];
+/// Enemy timing multipliers the setting cycles through, larger being slower
+pub const ENEMY_TIMINGS: [f32; 3] = [1.0, 1.5, 2.0];
+/// Lighting intensities the setting cycles through, 0.0 being off
+pub const LIGHTING_INTENSITIES: [f32; 3] = [0.0, 0.35, 0.7];
+/// Fractions of the run's shells kept on death the forgiveness setting cycles through, 0.0 being
+/// off
+pub const DEATH_KEEP_FRACTIONS: [f32; 3] = [0.0, 0.25, 0.5];
+/// Volume levels the volume settings cycle through, 0.0 being silent
+pub const VOLUMES: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+/// Settings that apply across all profiles, saved alongside them. Loaded once at startup and kept
+/// as a resource
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Settings {
+    /// Multiplier applied to every UI font size
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+    /// Moves that would attack need pressing twice to go through
+    #[serde(default)]
+    pub confirm_attacks: bool,
+    /// Swaps what left and right click do in game
+    #[serde(default)]
+    pub swap_mouse_buttons: bool,
+    /// Turns the player toward the nearest enemy in line at the start of each turn
+    #[serde(default)]
+    pub auto_aim: bool,
+    /// Rumbles the gamepad when the player is hurt or killed
+    #[serde(default = "default_vibration")]
+    pub vibration: bool,
+    /// Slows enemies' movement and sprite animation by this multiplier, leaving the player's
+    /// alone, to give more time to read what they're doing
+    #[serde(default = "default_enemy_timing")]
+    pub enemy_timing: f32,
+    /// How much darker tiles get away from the player, from 0.0 for flat lighting up to 1.0 for
+    /// black at the edge of the light. Off to begin with, so the map looks as it always has
+    #[serde(default)]
+    pub lighting_intensity: f32,
+    /// Briefly pans the camera to enemy actions happening off screen, like jellyfish lightning
+    #[serde(default)]
+    pub spectate_enemies: bool,
+    /// Shakes the player when they're hurt. The damage flash shows either way. Also off under
+    /// [Settings::reduce_motion]
+    #[serde(default = "default_screen_shake")]
+    pub screen_shake: bool,
+    /// Shows a small health bar under the player, alongside the hearts in the bottom bar
+    #[serde(default)]
+    pub player_health_bar: bool,
+    /// Fraction of the shells collected this run still banked when the player dies, from 0.0 for
+    /// losing them all up to 1.0 for keeping them all
+    #[serde(default)]
+    pub death_keep_fraction: f32,
+    /// The first hit of a run that would kill the player leaves them on 1 hp instead
+    #[serde(default)]
+    pub last_stand: bool,
+    /// How the camera follows the player
+    #[serde(default)]
+    pub camera_follow_mode: CameraFollowMode,
+    /// Labels the tile under the cursor with its coordinates, and in debug builds its cost and type
+    #[serde(default)]
+    pub tile_hover_label: bool,
+    /// Holds still everything that moves just for show: screen shake, the drifting menu
+    /// backgrounds and the turn counter's pulse
+    #[serde(default)]
+    pub reduce_motion: bool,
+    /// After dying, step back through the last few turns before heading back to the hub
+    #[serde(default)]
+    pub death_review: bool,
+    /// Where on the map the player starts each level
+    #[serde(default)]
+    pub start_placement: StartPlacement,
+    /// A faint tick as each new turn begins
+    #[serde(default)]
+    pub turn_tick_sound: bool,
+    /// Takes away the time pressure and keeps shells on death, for just exploring. Overrides
+    /// [Settings::death_keep_fraction] and [Settings::enemy_timing] while on
+    #[serde(default)]
+    pub relax_mode: bool,
+    /// The power takes two presses, the first aiming it with a target line and the second firing
+    #[serde(default)]
+    pub power_aiming: bool,
+    /// Replays the next level's map being generated, step by step, between levels
+    #[serde(default)]
+    pub map_gen_replay: bool,
+    /// Shows an arrow on each enemy for the way it'll try to move. The moves are rolled before the
+    /// player's, so while on enemies head for where the player was at the start of the turn
+    #[serde(default)]
+    pub intention_preview: bool,
+    /// Volume of everything, from 0.0 for silent up to 1.0
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Volume of the music, on top of [Settings::volume]
+    #[serde(default = "default_music_volume")]
+    pub music_volume: f32,
+    /// Whether deposit chests, which bank the run's shells early, can turn up on levels
+    #[serde(default = "default_deposit_chests")]
+    pub deposit_chests: bool,
+    /// A bot plays a level after the main menu is left idle for a while
+    #[serde(default = "default_idle_demo")]
+    pub idle_demo: bool,
+    /// Which kills briefly freeze the game to land the hit
+    #[serde(default)]
+    pub hit_stop: HitStopMode,
+    /// Save the profile after every level cleared, not just on returning to the hub
+    #[serde(default)]
+    pub autosave: bool,
+    /// What the turn counter shows, toggled in game with [KeyBindings::toggle_turn_counter]
+    #[serde(default)]
+    pub turn_counter_display: TurnCounterDisplay,
+    /// The keys for each in-game action. Only remappable by editing the settings file
+    #[serde(default)]
+    pub key_bindings: KeyBindings,
+}
+
+/// Where the player starts on each level's map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StartPlacement {
+    /// The map generator's start, which tends to be out at an edge
+    #[default]
+    Edge,
+    /// As near the middle of the map as there's water
+    Centre,
+}
+
+impl StartPlacement {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Edge => Self::Centre,
+            Self::Centre => Self::Edge,
+        }
+    }
+}
+
+/// How the camera keeps up with the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CameraFollowMode {
+    /// Stays put until the target leaves a box around the centre, then snaps back onto it
+    #[default]
+    Box,
+    /// Keeps the target centred at all times
+    Centred,
+    /// Eases along behind the target to keep it within the box, rather than snapping
+    SmoothBox,
+}
+
+impl CameraFollowMode {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Box => Self::Centred,
+            Self::Centred => Self::SmoothBox,
+            Self::SmoothBox => Self::Box,
+        }
+    }
+}
+
+/// Which kills freeze the game for a moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HitStopMode {
+    Off,
+    /// Only kills by the power's projectile
+    PowerKills,
+    /// Kills by the power and by moving into enemies
+    #[default]
+    AllKills,
+}
+
+impl HitStopMode {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Off => Self::PowerKills,
+            Self::PowerKills => Self::AllKills,
+            Self::AllKills => Self::Off,
+        }
+    }
+}
+
+/// What the in-game turn counter shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TurnCounterDisplay {
+    #[default]
+    Turns,
+    VortexCountdown,
+}
+
+impl TurnCounterDisplay {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Turns => Self::VortexCountdown,
+            Self::VortexCountdown => Self::Turns,
+        }
+    }
+}
+
+fn default_font_scale() -> f32 {
+    1.0
+}
+
+fn default_vibration() -> bool {
+    true
+}
+
+fn default_screen_shake() -> bool {
+    true
+}
+
+fn default_enemy_timing() -> f32 {
+    1.0
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+fn default_music_volume() -> f32 {
+    0.5
+}
+
+fn default_deposit_chests() -> bool {
+    true
+}
+
+fn default_idle_demo() -> bool {
+    true
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font_scale: default_font_scale(),
+            confirm_attacks: false,
+            swap_mouse_buttons: false,
+            auto_aim: false,
+            vibration: default_vibration(),
+            enemy_timing: default_enemy_timing(),
+            lighting_intensity: 0.0,
+            spectate_enemies: false,
+            screen_shake: default_screen_shake(),
+            player_health_bar: false,
+            death_keep_fraction: 0.0,
+            last_stand: false,
+            camera_follow_mode: CameraFollowMode::default(),
+            tile_hover_label: false,
+            reduce_motion: false,
+            death_review: false,
+            start_placement: StartPlacement::default(),
+            turn_tick_sound: false,
+            relax_mode: false,
+            power_aiming: false,
+            map_gen_replay: false,
+            intention_preview: false,
+            volume: default_volume(),
+            music_volume: default_music_volume(),
+            deposit_chests: default_deposit_chests(),
+            idle_demo: default_idle_demo(),
+            hit_stop: HitStopMode::default(),
+            autosave: false,
+            turn_counter_display: TurnCounterDisplay::default(),
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the saved settings, falling back to defaults if there are none or they can't be read
+    pub fn load() -> Self {
+        match platform_fs::maybe_load() {
+            Ok(Some(settings)) => settings,
+            Ok(None) => Self::default(),
+            Err(e) => {
+                warn!("Failed to load settings, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        platform_fs::save(self);
+    }
+
+    /// Shells banked from [collected] at the end of a run, rounding down what's kept on death
+    pub fn shells_banked(&self, collected: usize, died: bool) -> usize {
+        if died {
+            (collected as f32 * self.death_keep_fraction.clamp(0.0, 1.0)).floor() as usize
+        } else {
+            collected
+        }
+    }
+}
+
+/// The first of `options` (in ascending order) above `current`, wrapping back to the first
+fn next_in_cycle(options: &[f32], current: f32) -> f32 {
+    options
+        .iter()
+        .position(|option| *option > current)
+        .map_or(options[0], |i| options[i])
+}
+
+/// The next font scale in [FONT_SCALES] after `current`, wrapping back to the first
+pub fn next_font_scale(current: f32) -> f32 {
+    next_in_cycle(&FONT_SCALES, current)
+}
+
+/// The next enemy timing in [ENEMY_TIMINGS] after `current`, wrapping back to the first
+pub fn next_enemy_timing(current: f32) -> f32 {
+    next_in_cycle(&ENEMY_TIMINGS, current)
+}
+
+/// The next lighting intensity in [LIGHTING_INTENSITIES] after `current`, wrapping back to the
+/// first
+pub fn next_lighting_intensity(current: f32) -> f32 {
+    next_in_cycle(&LIGHTING_INTENSITIES, current)
+}
+
+/// The next death keep fraction in [DEATH_KEEP_FRACTIONS] after `current`, wrapping back to the
+/// first
+pub fn next_death_keep_fraction(current: f32) -> f32 {
+    next_in_cycle(&DEATH_KEEP_FRACTIONS, current)
+}
+
+/// The next volume in [VOLUMES] after `current`, wrapping back to silent
+pub fn next_volume(current: f32) -> f32 {
+    next_in_cycle(&VOLUMES, current)
+}
+
+const SETTINGS_ID: &str = "settings.ron";
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform_fs {
+    use crate::profiles::settings::{Settings, SETTINGS_ID};
+    use std::fs::File;
+    use std::io::{BufReader, BufWriter, ErrorKind};
+
+    fn filename() -> String {
+        format!("saves/{}", SETTINGS_ID)
+    }
+
+    pub fn maybe_load() -> Result<Option<Settings>, String> {
+        let file = match File::open(filename()) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let reader = BufReader::new(file);
+        ron::de::from_reader(reader)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn save(settings: &Settings) {
+        match File::create(filename()) {
+            Ok(file) => {
+                let writer = BufWriter::new(file);
+                if let Err(e) = ron::ser::to_writer(writer, settings) {
+                    log::warn!("Failed to write settings: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to create settings file: {}", e),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform_fs {
+    use crate::profiles::settings::{Settings, SETTINGS_ID};
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        let window: web_sys::Window = web_sys::window()?;
+        window.local_storage().ok()?
+    }
+
+    pub fn maybe_load() -> Result<Option<Settings>, String> {
+        let local_storage = match local_storage() {
+            Some(local_storage) => local_storage,
+            None => return Ok(None),
+        };
+        let entry = match local_storage.get_item(SETTINGS_ID) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(format!("{:?}", e)),
+        };
+        ron::de::from_str(&entry)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn save(settings: &Settings) {
+        if let Some(local_storage) = local_storage() {
+            match ron::ser::to_string(settings) {
+                Ok(settings_ron) => {
+                    let _ = local_storage.set_item(SETTINGS_ID, &settings_ron);
+                }
+                Err(e) => log::warn!("Failed to serialise settings: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        next_enemy_timing, next_font_scale, next_lighting_intensity, next_volume, Settings,
+        ENEMY_TIMINGS, FONT_SCALES, LIGHTING_INTENSITIES, VOLUMES,
+    };
+
+    #[test]
+    fn test_next_font_scale_cycles() {
+        assert_eq!(FONT_SCALES[1], next_font_scale(FONT_SCALES[0]));
+        assert_eq!(FONT_SCALES[2], next_font_scale(FONT_SCALES[1]));
+        assert_eq!(FONT_SCALES[0], next_font_scale(FONT_SCALES[2]));
+        // A scale not in the list, e.g. hand edited, moves to the next one up
+        assert_eq!(FONT_SCALES[2], next_font_scale(1.3));
+    }
+
+    #[test]
+    fn test_next_enemy_timing_cycles() {
+        assert_eq!(ENEMY_TIMINGS[1], next_enemy_timing(ENEMY_TIMINGS[0]));
+        assert_eq!(ENEMY_TIMINGS[0], next_enemy_timing(ENEMY_TIMINGS[2]));
+    }
+
+    #[test]
+    fn test_next_lighting_intensity_cycles() {
+        assert_eq!(
+            LIGHTING_INTENSITIES[1],
+            next_lighting_intensity(LIGHTING_INTENSITIES[0])
+        );
+        assert_eq!(
+            LIGHTING_INTENSITIES[0],
+            next_lighting_intensity(LIGHTING_INTENSITIES[2])
+        );
+    }
+
+    #[test]
+    fn test_next_volume_cycles() {
+        assert_eq!(VOLUMES[1], next_volume(VOLUMES[0]));
+        // Full volume wraps round to silent
+        assert_eq!(VOLUMES[0], next_volume(VOLUMES[4]));
+    }
+
+    #[test]
+    fn test_shells_banked() {
+        let mut settings = Settings::default();
+        assert_eq!(7, settings.shells_banked(7, false));
+        assert_eq!(0, settings.shells_banked(7, true));
+
+        settings.death_keep_fraction = 0.5;
+        assert_eq!(7, settings.shells_banked(7, false));
+        assert_eq!(4, settings.shells_banked(8, true));
+        assert_eq!(3, settings.shells_banked(7, true));
+        assert_eq!(0, settings.shells_banked(1, true));
+        assert_eq!(0, settings.shells_banked(0, true));
+    }
+}